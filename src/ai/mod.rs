@@ -1,3 +1,5 @@
 pub mod model;
+pub mod personality;
 pub mod picker;
 pub mod plugin;
+pub mod solver;
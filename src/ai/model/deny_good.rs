@@ -0,0 +1,130 @@
+use bevy::prelude::*;
+use big_brain::{prelude::ActionState, scorers::Score, thinker::Actor};
+use std::collections::HashMap;
+
+use crate::{
+    ai::model::math::clamp,
+    card_selection::SelectedCard,
+    event::ConfirmTurnEvent,
+    game::{ActivePlayer, Player},
+    game_resources::{
+        card::{ActivePlayerGoodsCard, Card, CardType, MarketCard},
+        game_config::GameConfig,
+    },
+    move_history::MoveHistory,
+    move_validation::MoveType,
+    states::AppState,
+};
+
+#[derive(Component, Debug, Clone)]
+pub struct DenyGoodAction;
+
+pub fn deny_good_action_system(
+    mut commands: Commands,
+    app_state: Res<State<AppState>>,
+    mut action_query: Query<(&Actor, &mut ActionState), With<DenyGoodAction>>,
+    mut ev_confirm_turn: EventWriter<ConfirmTurnEvent>,
+    scorer_states_query: Query<&DenyGoodScorerState>,
+) {
+    if !matches!(app_state.current(), AppState::AiTurn) {
+        return;
+    }
+
+    for (Actor(actor), mut state) in action_query.iter_mut() {
+        match *state {
+            ActionState::Requested => {
+                *state = ActionState::Executing;
+            }
+            ActionState::Executing => {
+                if let Some(good) = scorer_states_query
+                    .get(*actor)
+                    .ok()
+                    .and_then(|scorer_state| scorer_state.card_entity)
+                {
+                    commands.entity(good).insert(SelectedCard);
+                    ev_confirm_turn.send(ConfirmTurnEvent(MoveType::TakeSingleGood));
+                    *state = ActionState::Success;
+                    return;
+                }
+                *state = ActionState::Failure;
+            }
+            ActionState::Cancelled => {
+                *state = ActionState::Failure;
+            }
+            _ => {}
+        }
+    }
+}
+
+#[derive(Clone, Component, Debug)]
+pub struct DenyGoodScorer;
+
+#[derive(Default, Component, Debug)]
+pub struct DenyGoodScorerState {
+    card_entity: Option<Entity>,
+}
+
+// Takes the market good that the opponent is collecting, to stop them building a large sale. The opponent's hand
+// is face down, so only the goods they were seen to take from the market count - see MoveHistory.
+#[allow(clippy::too_many_arguments)]
+pub fn deny_good_scorer_system(
+    app_state: Res<State<AppState>>,
+    game_config: Res<GameConfig>,
+    move_history: Res<MoveHistory>,
+    mut query: Query<(&Actor, &mut Score), With<DenyGoodScorer>>,
+    mut scorer_states_query: Query<&mut DenyGoodScorerState>,
+    market_cards_query: Query<(Entity, &Card), With<MarketCard>>,
+    active_player_goods_hand_query: Query<Entity, With<ActivePlayerGoodsCard>>,
+    opponent_query: Query<Entity, (With<Player>, Without<ActivePlayer>)>,
+) {
+    for (Actor(actor), mut score) in query.iter_mut() {
+        let mut scorer_state = scorer_states_query.get_mut(*actor).unwrap();
+
+        let num_goods_in_hand = active_player_goods_hand_query.iter().count();
+
+        if !matches!(app_state.current(), AppState::AiTurn)
+            || num_goods_in_hand >= game_config.max_goods_in_hand
+        {
+            scorer_state.card_entity = None;
+            score.set(0.0);
+            continue;
+        }
+
+        let opponent_goods_counts = match opponent_query.get_single() {
+            Ok(opponent) => move_history.get_known_goods_in_hand(opponent),
+            Err(_) => HashMap::new(),
+        };
+
+        let good_to_deny = market_cards_query
+            .iter()
+            .filter_map(|(ent, c)| match c.0 {
+                CardType::Good(good_type) => Some((
+                    ent,
+                    good_type,
+                    *opponent_goods_counts.get(&good_type).unwrap_or(&0),
+                )),
+                _ => None,
+            })
+            // Denying a good only matters once the opponent is building a set of it
+            .filter(|(_, _, opponent_count)| *opponent_count >= 2)
+            .max_by_key(|(_, _, opponent_count)| *opponent_count);
+
+        match good_to_deny {
+            Some((e, _, opponent_count)) => {
+                scorer_state.card_entity = Some(e);
+                score.set(calculate_score(opponent_count));
+            }
+            None => {
+                scorer_state.card_entity = None;
+                score.set(0.0);
+            }
+        }
+    }
+}
+
+// 2 of the good known to be in opponent's hand => 50%
+// 3 of the good in opponent's hand => 75%
+// 4 or more of the good in opponent's hand => 100%
+fn calculate_score(num_good_in_opponent_hand: usize) -> f32 {
+    clamp(num_good_in_opponent_hand as f32 * 0.25, 0.0, 1.0)
+}
@@ -0,0 +1,153 @@
+use bevy::prelude::*;
+use big_brain::{prelude::ActionState, scorers::Score, thinker::Actor};
+use itertools::Itertools;
+
+use crate::{
+    ai::model::math::clamp,
+    card_selection::SelectedCard,
+    event::ConfirmTurnEvent,
+    game_resources::{
+        card::{ActivePlayerGoodsCard, Card, CardType},
+        deck_definition::DeckDefinition,
+        game_config::GameConfig,
+        tokens::Tokens,
+    },
+    move_validation::MoveType,
+    states::AppState,
+};
+
+#[derive(Component, Debug, Clone)]
+pub struct DepleteStackAction;
+
+pub fn deplete_stack_action_system(
+    mut commands: Commands,
+    app_state: Res<State<AppState>>,
+    mut action_query: Query<(&Actor, &mut ActionState), With<DepleteStackAction>>,
+    mut ev_confirm_turn: EventWriter<ConfirmTurnEvent>,
+    scorer_states_query: Query<&DepleteStackScorerState>,
+) {
+    if !matches!(app_state.current(), AppState::AiTurn) {
+        return;
+    }
+
+    for (Actor(actor), mut state) in action_query.iter_mut() {
+        match *state {
+            ActionState::Requested => {
+                *state = ActionState::Executing;
+            }
+            ActionState::Executing => {
+                if let Some(goods) = scorer_states_query
+                    .get(*actor)
+                    .ok()
+                    .and_then(|scorer_state| scorer_state.card_entities.clone())
+                {
+                    for good in goods {
+                        commands.entity(good).insert(SelectedCard);
+                    }
+                    ev_confirm_turn.send(ConfirmTurnEvent(MoveType::SellGoods));
+                    *state = ActionState::Success;
+                    return;
+                }
+                *state = ActionState::Failure;
+            }
+            ActionState::Cancelled => {
+                *state = ActionState::Failure;
+            }
+            _ => {}
+        }
+    }
+}
+
+#[derive(Clone, Component, Debug)]
+pub struct DepleteStackScorer;
+
+#[derive(Default, Component, Debug)]
+pub struct DepleteStackScorerState {
+    card_entities: Option<Vec<Entity>>,
+}
+
+// Sells the good whose game token stack is closest to being emptied, racing towards the end of the game
+// when enough stacks are empty. The score increases with the number of stacks that are already empty.
+pub fn deplete_stack_scorer_system(
+    app_state: Res<State<AppState>>,
+    game_config: Res<GameConfig>,
+    deck_definition: Option<Res<DeckDefinition>>,
+    game_tokens: Option<Res<Tokens>>,
+    mut query: Query<(&Actor, &mut Score), With<DepleteStackScorer>>,
+    mut scorer_states_query: Query<&mut DepleteStackScorerState>,
+    active_player_goods_hand_query: Query<(Entity, &Card), With<ActivePlayerGoodsCard>>,
+) {
+    for (Actor(actor), mut score) in query.iter_mut() {
+        let mut scorer_state = scorer_states_query.get_mut(*actor).unwrap();
+
+        let (deck_definition, game_tokens) =
+            match (&deck_definition, &game_tokens, app_state.current()) {
+                (Some(deck_definition), Some(game_tokens), AppState::AiTurn) => {
+                    (deck_definition, game_tokens)
+                }
+                _ => {
+                    scorer_state.card_entities = None;
+                    score.set(0.0);
+                    continue;
+                }
+            };
+
+        let num_empty_stacks = game_tokens
+            .goods
+            .iter()
+            .filter(|(_, token_values)| token_values.is_empty())
+            .count();
+
+        // 50% with no stacks empty, up to 100% when one more empty stack would end the game
+        let urgency = 0.5
+            + 0.5 * num_empty_stacks as f32
+                / game_config
+                    .num_empty_token_stacks_to_end
+                    .saturating_sub(1)
+                    .max(1) as f32;
+
+        let goods_in_hand = active_player_goods_hand_query
+            .iter()
+            .filter_map(|(e, c)| match c.0 {
+                CardType::Good(g) => Some((e, g)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        let best_good_to_sell = goods_in_hand
+            .iter()
+            .counts_by(|(_, good)| *good)
+            .into_iter()
+            // Prevent selling fewer goods than allowed
+            .filter(|(good_type, count)| *count >= deck_definition.goods[*good_type].min_sale_size)
+            .filter_map(|(good_type, count)| {
+                let num_remaining_tokens = game_tokens.goods[good_type].len();
+                if num_remaining_tokens == 0 {
+                    return None;
+                }
+
+                let fraction_of_stack_sold =
+                    count.min(num_remaining_tokens) as f32 / num_remaining_tokens as f32;
+
+                Some((good_type, fraction_of_stack_sold * urgency))
+            })
+            .max_by(|(_, score_a), (_, score_b)| score_a.total_cmp(score_b));
+
+        match best_good_to_sell {
+            Some((good_type_to_sell, score_value)) => {
+                let entities_to_sell = goods_in_hand
+                    .iter()
+                    .filter(|(_, g)| *g == good_type_to_sell)
+                    .map(|(e, _)| *e)
+                    .collect::<Vec<_>>();
+
+                scorer_state.card_entities = Some(entities_to_sell);
+                score.set(clamp(score_value, 0.0, 1.0));
+            }
+            None => {
+                scorer_state.card_entities = None;
+                score.set(0.0);
+            }
+        }
+    }
+}
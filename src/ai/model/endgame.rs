@@ -0,0 +1,274 @@
+use bevy::prelude::*;
+use big_brain::{prelude::ActionState, scorers::Score, thinker::Actor};
+
+use crate::{
+    ai::solver::{is_endgame, solve, SolverMove, SolverPlayer, SolverPosition, SolverRules},
+    card_selection::SelectedCard,
+    event::ConfirmTurnEvent,
+    game::{get_tokens_score, ActivePlayer, CamelsHandOwner, GoodsHandOwner, Player, TokensOwner},
+    game_resources::{
+        card::{ActivePlayerCamelCard, ActivePlayerGoodsCard, Card, CardType, MarketCard},
+        deck::Deck,
+        deck_definition::DeckDefinition,
+        game_config::GameConfig,
+        market::Market,
+        tokens::Tokens,
+    },
+    move_validation::MoveType,
+    states::AppState,
+};
+
+#[derive(Component, Debug, Clone)]
+pub struct EndgameAction;
+
+pub fn endgame_action_system(
+    mut commands: Commands,
+    app_state: Res<State<AppState>>,
+    mut action_query: Query<(&Actor, &mut ActionState), With<EndgameAction>>,
+    mut ev_confirm_turn: EventWriter<ConfirmTurnEvent>,
+    scorer_states_query: Query<&EndgameScorerState>,
+    market_cards_query: Query<(Entity, &Card), With<MarketCard>>,
+    goods_hand_query: Query<(Entity, &Card), With<ActivePlayerGoodsCard>>,
+    camel_hand_query: Query<Entity, With<ActivePlayerCamelCard>>,
+) {
+    if !matches!(app_state.current(), AppState::AiTurn) {
+        return;
+    }
+
+    for (Actor(actor), mut state) in action_query.iter_mut() {
+        match *state {
+            ActionState::Requested => {
+                *state = ActionState::Executing;
+            }
+            ActionState::Executing => {
+                if let Some(solver_move) = scorer_states_query
+                    .get(*actor)
+                    .ok()
+                    .and_then(|scorer_state| scorer_state.solution.clone())
+                {
+                    let market_cards = market_cards_query.iter().collect::<Vec<_>>();
+                    let goods_hand = goods_hand_query.iter().collect::<Vec<_>>();
+                    let camel_hand = camel_hand_query.iter().collect::<Vec<_>>();
+
+                    let (entities, move_type) = get_entities_for_move(
+                        &solver_move,
+                        &market_cards,
+                        &goods_hand,
+                        &camel_hand,
+                    );
+
+                    for entity in entities {
+                        commands.entity(entity).insert(SelectedCard);
+                    }
+                    ev_confirm_turn.send(ConfirmTurnEvent(move_type));
+                    *state = ActionState::Success;
+                    return;
+                }
+                *state = ActionState::Failure;
+            }
+            ActionState::Cancelled => {
+                *state = ActionState::Failure;
+            }
+            _ => {}
+        }
+    }
+}
+
+// The solver treats the market and hands as multisets, so pick any distinct card entities of the required types
+fn get_entities_for_move(
+    solver_move: &SolverMove,
+    market_cards: &[(Entity, &Card)],
+    goods_hand: &[(Entity, &Card)],
+    camel_hand: &[Entity],
+) -> (Vec<Entity>, MoveType) {
+    let mut entities: Vec<Entity> = vec![];
+
+    let mut select_card = |cards: &[(Entity, &Card)], card_type: CardType| {
+        let (entity, _) = cards
+            .iter()
+            .find(|(e, c)| c.0 == card_type && !entities.contains(e))
+            .unwrap();
+        entities.push(*entity);
+    };
+
+    let move_type = match solver_move {
+        SolverMove::TakeSingleGood(good) => {
+            select_card(market_cards, CardType::Good(*good));
+            MoveType::TakeSingleGood
+        }
+        SolverMove::TakeAllCamels => {
+            entities.extend(
+                market_cards
+                    .iter()
+                    .filter(|(_, c)| c.0 == CardType::Camel)
+                    .map(|(e, _)| *e),
+            );
+            MoveType::TakeAllCamels
+        }
+        SolverMove::ExchangeGoods {
+            take,
+            give_goods,
+            give_camels,
+        } => {
+            for good in take {
+                select_card(market_cards, CardType::Good(*good));
+            }
+            for good in give_goods {
+                select_card(goods_hand, CardType::Good(*good));
+            }
+            entities.extend(camel_hand.iter().take(*give_camels));
+            MoveType::ExchangeForGoodsFromMarket
+        }
+        SolverMove::SellGoods { good, count } => {
+            for _ in 0..*count {
+                select_card(goods_hand, CardType::Good(*good));
+            }
+            MoveType::SellGoods
+        }
+    };
+
+    (entities, move_type)
+}
+
+#[derive(Clone, Component, Debug)]
+pub struct EndgameScorer;
+
+#[derive(Default, Component, Debug)]
+pub struct EndgameScorerState {
+    solution: Option<SolverMove>,
+    // The solver is expensive, so only run it once per turn
+    has_solved_this_turn: bool,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn endgame_scorer_system(
+    app_state: Res<State<AppState>>,
+    game_config: Res<GameConfig>,
+    deck_definition: Option<Res<DeckDefinition>>,
+    deck: Option<Res<Deck>>,
+    market: Option<Res<Market>>,
+    game_tokens: Option<Res<Tokens>>,
+    mut query: Query<(&Actor, &mut Score), With<EndgameScorer>>,
+    mut scorer_states_query: Query<&mut EndgameScorerState>,
+    active_player_query: Query<
+        (&GoodsHandOwner, &CamelsHandOwner, &TokensOwner),
+        With<ActivePlayer>,
+    >,
+    inactive_player_query: Query<
+        (&GoodsHandOwner, &CamelsHandOwner, &TokensOwner),
+        (With<Player>, Without<ActivePlayer>),
+    >,
+) {
+    for (Actor(actor), mut score) in query.iter_mut() {
+        let mut scorer_state = scorer_states_query.get_mut(*actor).unwrap();
+
+        let (deck_definition, deck, market, game_tokens) = match (
+            &deck_definition,
+            &deck,
+            &market,
+            &game_tokens,
+            app_state.current(),
+        ) {
+            (
+                Some(deck_definition),
+                Some(deck),
+                Some(market),
+                Some(game_tokens),
+                AppState::AiTurn,
+            ) => (deck_definition, deck, market, game_tokens),
+            _ => {
+                scorer_state.solution = None;
+                scorer_state.has_solved_this_turn = false;
+                score.set(0.0);
+                continue;
+            }
+        };
+
+        if !scorer_state.has_solved_this_turn {
+            scorer_state.has_solved_this_turn = true;
+            scorer_state.solution = None;
+
+            let rules = SolverRules::new(&game_config, deck_definition);
+
+            if is_endgame(deck.cards.len(), game_tokens, &rules) {
+                let (goods_hand, camels_hand, tokens_owner) = active_player_query.single();
+                let (opponent_goods_hand, opponent_camels_hand, opponent_tokens_owner) =
+                    inactive_player_query.single();
+
+                let unseen = deck
+                    .cards
+                    .iter()
+                    .copied()
+                    .chain(opponent_goods_hand.0.iter().map(|g| CardType::Good(*g)))
+                    .collect::<Vec<_>>();
+
+                let position = SolverPosition {
+                    market: market.cards.clone(),
+                    tokens: game_tokens.as_ref().clone(),
+                    players: [
+                        SolverPlayer {
+                            goods: goods_hand.0.clone(),
+                            camels: camels_hand.0,
+                            rupees: get_tokens_score(&tokens_owner.0) as f32,
+                        },
+                        SolverPlayer {
+                            goods: vec![],
+                            camels: opponent_camels_hand.0,
+                            rupees: get_opponent_rupees(
+                                &opponent_tokens_owner.0,
+                                &tokens_owner.0,
+                                deck_definition,
+                            ),
+                        },
+                    ],
+                    unseen,
+                    opponent_goods_hand_size: opponent_goods_hand.0.len(),
+                    rules,
+                };
+
+                scorer_state.solution = solve(&position);
+            }
+        }
+
+        if scorer_state.solution.is_some() {
+            score.set(1.0);
+        } else {
+            score.set(0.0);
+        }
+    }
+}
+
+// The opponent's bonus tokens are face down, so each one is valued at the average of the bonus tokens of its type
+// that the AI hasn't won itself
+fn get_opponent_rupees(
+    opponent_tokens: &Tokens,
+    own_tokens: &Tokens,
+    deck_definition: &DeckDefinition,
+) -> f32 {
+    let goods_tokens_rupees = opponent_tokens
+        .goods
+        .iter()
+        .flat_map(|(_, values)| values)
+        .sum::<usize>() as f32;
+
+    let bonus_tokens_rupees = opponent_tokens
+        .bonus
+        .iter()
+        .map(|(bonus_type, values)| {
+            let pool = &deck_definition.bonus_tokens[&bonus_type];
+            let own_values = &own_tokens.bonus[bonus_type];
+            let num_unseen = pool.len().saturating_sub(own_values.len());
+            if num_unseen == 0 {
+                return 0.0;
+            }
+
+            let unseen_sum = pool
+                .iter()
+                .sum::<usize>()
+                .saturating_sub(own_values.iter().sum());
+            values.len() as f32 * unseen_sum as f32 / num_unseen as f32
+        })
+        .sum::<f32>();
+
+    goods_tokens_rupees + bonus_tokens_rupees
+}
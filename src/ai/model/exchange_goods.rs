@@ -5,11 +5,13 @@ use big_brain::{prelude::ActionState, scorers::Score, thinker::Actor};
 use itertools::{Either, Itertools};
 
 use crate::{
-    ai::model::math::clamp,
+    ai::{model::math::clamp, personality::ScorerWeights},
     card_selection::SelectedCard,
     event::ConfirmTurnEvent,
-    game_resources::card::{
-        ActivePlayerCamelCard, ActivePlayerGoodsCard, Card, CardType, MarketCard,
+    game_resources::{
+        card::{ActivePlayerCamelCard, ActivePlayerGoodsCard, Card, CardType, MarketCard},
+        deck_definition::DeckDefinition,
+        game_config::GameConfig,
     },
     move_validation::MoveType,
     states::AppState,
@@ -63,16 +65,25 @@ pub struct ExchangeGoodsScorerState {
 
 pub fn exchange_goods_scorer_system(
     app_state: Res<State<AppState>>,
+    game_config: Res<GameConfig>,
+    deck_definition: Option<Res<DeckDefinition>>,
     mut query: Query<(&Actor, &mut Score), With<ExchangeGoodsScorer>>,
     mut scorer_states_query: Query<&mut ExchangeGoodsScorerState>,
+    weights_query: Query<&ScorerWeights>,
     active_player_cards_hand_query: Query<
         (Entity, &Card),
         Or<(With<ActivePlayerGoodsCard>, With<ActivePlayerCamelCard>)>,
     >,
     market_cards_query: Query<(Entity, &Card), With<MarketCard>>,
 ) {
+    let deck_definition = match deck_definition {
+        Some(deck_definition) => deck_definition,
+        None => return,
+    };
+
     for (Actor(actor), mut score) in query.iter_mut() {
         let mut scorer_state = scorer_states_query.get_mut(*actor).unwrap();
+        let weights = weights_query.get(*actor).unwrap();
 
         if !matches!(app_state.current(), AppState::AiTurn) {
             scorer_state.card_entities = None;
@@ -100,8 +111,10 @@ pub fn exchange_goods_scorer_system(
 
         // Exchanging camels will add extra goods to your hand - ensure this would not exceed max number of goods allowed in hand
         // And you cannot exchange more camels than you have
-        let num_camels_permitted_to_exchange =
-            cmp::min(7 - num_goods_in_hand as i32, num_camels_in_hand as i32);
+        let num_camels_permitted_to_exchange = cmp::min(
+            game_config.max_goods_in_hand as i32 - num_goods_in_hand as i32,
+            num_camels_in_hand as i32,
+        );
 
         // For each good in the market, if you took all of that type of good, how many would you get in your hand?
         let goods_hand_counts = goods_in_hand.iter().counts_by(|(_, good)| good);
@@ -114,7 +127,9 @@ pub fn exchange_goods_scorer_system(
         // Filter out goods_hand_counts_after_market_take for which there is count of one, unless its a high value good
         let goods_hand_counts_after_market_take = goods_hand_counts_after_market_take
             .iter()
-            .filter(|(good_type, count)| **count > 1 || good_type.is_high_value())
+            .filter(|(good_type, count)| {
+                **count > 1 || deck_definition.goods[***good_type].is_high_value()
+            })
             .collect::<HashMap<_, _>>();
 
         // Find goods in hand of which there are only one, that are not in the market
@@ -191,7 +206,11 @@ pub fn exchange_goods_scorer_system(
 
                 // TODO: I am adding one to make this a higher score than take single good for the corresponding number of goods in hand after
                 // This is because we are exchanging less "valuable" cards (camels and single goods in hand) for more "valuable" goods cards from the market
-                let score_value = clamp(((**highest_count_pair.1 + 1) * 2) as f32 / 10.0, 0.0, 1.0);
+                let score_value = clamp(
+                    ((**highest_count_pair.1 + 1) * 2) as f32 / 10.0 * weights.exchange_goods,
+                    0.0,
+                    1.0,
+                );
                 println!("SCORE: {}", score_value);
 
                 score.set(score_value);
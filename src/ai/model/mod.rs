@@ -1,3 +1,6 @@
+pub mod deny_good;
+pub mod deplete_stack;
+pub mod endgame;
 pub mod exchange_goods;
 mod math;
 pub mod sell_goods;
@@ -3,10 +3,13 @@ use big_brain::{prelude::ActionState, scorers::Score, thinker::Actor};
 use itertools::Itertools;
 
 use crate::{
-    ai::model::math::clamp,
+    ai::{model::math::clamp, personality::ScorerWeights},
     card_selection::SelectedCard,
     event::ConfirmTurnEvent,
-    game_resources::card::{ActivePlayerGoodsCard, Card, CardType},
+    game_resources::{
+        card::{ActivePlayerGoodsCard, Card, CardType},
+        deck_definition::DeckDefinition,
+    },
     move_validation::MoveType,
     states::AppState,
 };
@@ -60,12 +63,20 @@ pub struct SellGoodsScorerState {
 
 pub fn sell_goods_scorer_system(
     app_state: Res<State<AppState>>,
+    deck_definition: Option<Res<DeckDefinition>>,
     mut query: Query<(&Actor, &mut Score), With<SellGoodsScorer>>,
     mut scorer_states_query: Query<&mut SellGoodsScorerState>,
+    weights_query: Query<&ScorerWeights>,
     active_player_goods_hand_query: Query<(Entity, &Card), With<ActivePlayerGoodsCard>>,
 ) {
+    let deck_definition = match deck_definition {
+        Some(deck_definition) => deck_definition,
+        None => return,
+    };
+
     for (Actor(actor), mut score) in query.iter_mut() {
         let mut scorer_state = scorer_states_query.get_mut(*actor).unwrap();
+        let weights = weights_query.get(*actor).unwrap();
 
         if !matches!(app_state.current(), AppState::AiTurn) {
             scorer_state.card_entities = None;
@@ -83,8 +94,9 @@ pub fn sell_goods_scorer_system(
 
         let mut counts = goods_in_hand.iter().counts_by(|(_, good)| good);
 
-        // Prevent selling a single high value good
-        counts.retain(|good_type, count| !(good_type.is_high_value() && *count < 2));
+        // Prevent selling fewer goods than allowed
+        counts
+            .retain(|good_type, count| *count >= deck_definition.goods[**good_type].min_sale_size);
 
         let most_frequent_good = counts.iter().max_by_key(|(_, freq)| *freq);
 
@@ -102,7 +114,17 @@ pub fn sell_goods_scorer_system(
                     .collect::<Vec<_>>();
 
                 scorer_state.card_entities = Some(entities_to_sell);
-                let score_value = calculate_score(*freq, good_type_to_sell.is_high_value());
+                let good_value_multiplier =
+                    if deck_definition.goods[**good_type_to_sell].is_high_value() {
+                        weights.high_value_multiplier
+                    } else {
+                        1.0
+                    };
+                let score_value = clamp(
+                    calculate_score(*freq, good_value_multiplier) * weights.sell_goods,
+                    0.0,
+                    1.0,
+                );
 
                 println!("COULD SELL {} GOODS, SCORE {}", freq, score_value);
 
@@ -122,12 +144,10 @@ pub fn sell_goods_scorer_system(
 // 3 of same good in hand => 60%
 // 2 of same good in hand => 40%
 // 1 of same good in hand => 20%
-fn calculate_score(highest_frequency_of_good: usize, is_high_value_good: bool) -> f32 {
+fn calculate_score(highest_frequency_of_good: usize, good_value_multiplier: f32) -> f32 {
     let mut raw_score = (highest_frequency_of_good as f32 * 20.0) / 100.0;
 
-    if is_high_value_good {
-        raw_score *= 1.5;
-    }
+    raw_score *= good_value_multiplier;
 
     clamp(raw_score, 0.0, 1.0)
 }
@@ -2,7 +2,7 @@ use bevy::prelude::*;
 use big_brain::{prelude::ActionState, scorers::Score, thinker::Actor};
 
 use crate::{
-    ai::model::math::clamp,
+    ai::{model::math::clamp, personality::ScorerWeights},
     card_selection::SelectedCard,
     event::ConfirmTurnEvent,
     game_resources::card::{
@@ -63,12 +63,14 @@ pub fn take_all_camels_scorer_system(
     app_state: Res<State<AppState>>,
     mut query: Query<(&Actor, &mut Score), With<TakeAllCamelsScorer>>,
     mut scorer_states_query: Query<&mut TakeAllCamelsScorerState>,
+    weights_query: Query<&ScorerWeights>,
     all_market_card_query: Query<(Entity, &Card), With<MarketCard>>,
     active_player_goods_cards: Query<Entity, With<ActivePlayerGoodsCard>>,
     opponent_goods_cards: Query<Entity, With<InactivePlayerGoodsCard>>,
 ) {
     for (Actor(actor), mut score) in query.iter_mut() {
         let mut scorer_state = scorer_states_query.get_mut(*actor).unwrap();
+        let weights = weights_query.get(*actor).unwrap();
 
         if !matches!(app_state.current(), AppState::AiTurn) {
             scorer_state.card_entities = None;
@@ -90,10 +92,14 @@ pub fn take_all_camels_scorer_system(
             let num_goods_in_hand = active_player_goods_cards.iter().count();
             let num_goods_in_opponent_hand = opponent_goods_cards.iter().count();
 
-            let score_value = calculate_score(
-                num_camels_in_market,
-                num_goods_in_hand,
-                num_goods_in_opponent_hand,
+            let score_value = clamp(
+                calculate_score(
+                    num_camels_in_market,
+                    num_goods_in_hand,
+                    num_goods_in_opponent_hand,
+                ) * weights.take_all_camels,
+                0.0,
+                1.0,
             );
 
             println!("START CAMELS SCORE INFO");
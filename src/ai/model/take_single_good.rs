@@ -2,9 +2,14 @@ use bevy::prelude::*;
 use big_brain::{prelude::ActionState, scorers::Score, thinker::Actor};
 
 use crate::{
+    ai::personality::ScorerWeights,
     card_selection::SelectedCard,
     event::ConfirmTurnEvent,
-    game_resources::card::{ActivePlayerGoodsCard, Card, CardType, GoodType, MarketCard},
+    game_resources::{
+        card::{ActivePlayerGoodsCard, Card, CardType, GoodType, MarketCard},
+        deck_definition::DeckDefinition,
+        game_config::GameConfig,
+    },
     move_validation::MoveType,
     states::AppState,
 };
@@ -61,17 +66,28 @@ pub struct TakeSingleGoodScorerState {
 
 pub fn take_single_good_scorer_system(
     app_state: Res<State<AppState>>,
+    game_config: Res<GameConfig>,
+    deck_definition: Option<Res<DeckDefinition>>,
     mut query: Query<(&Actor, &mut Score), With<TakeSingleGoodScorer>>,
     mut scorer_states_query: Query<&mut TakeSingleGoodScorerState>,
+    weights_query: Query<&ScorerWeights>,
     market_cards_query: Query<(Entity, &Card), With<MarketCard>>,
     active_player_goods_hand_query: Query<&Card, With<ActivePlayerGoodsCard>>,
 ) {
+    let deck_definition = match deck_definition {
+        Some(deck_definition) => deck_definition,
+        None => return,
+    };
+
     for (Actor(actor), mut score) in query.iter_mut() {
         let mut scorer_state = scorer_states_query.get_mut(*actor).unwrap();
+        let weights = weights_query.get(*actor).unwrap();
 
         let num_goods_in_hand = active_player_goods_hand_query.iter().count();
 
-        if !matches!(app_state.current(), AppState::AiTurn) || num_goods_in_hand >= 7 {
+        if !matches!(app_state.current(), AppState::AiTurn)
+            || num_goods_in_hand >= game_config.max_goods_in_hand
+        {
             scorer_state.card_entity = None;
             score.set(0.0);
             continue;
@@ -94,7 +110,11 @@ pub fn take_single_good_scorer_system(
                     // TODO: refactor to pass good_type and goods_in_hand to calculate_score
                     calculate_score(
                         get_num_goods_in_hand(good_type, &goods_in_hand),
-                        good_type.is_high_value(),
+                        if deck_definition.goods[good_type].is_high_value() {
+                            weights.high_value_multiplier
+                        } else {
+                            1.0
+                        },
                     ),
                 )),
                 _ => None,
@@ -108,7 +128,7 @@ pub fn take_single_good_scorer_system(
                     good_type, score_for_good
                 );
                 scorer_state.card_entity = Some(e);
-                score.set(score_for_good);
+                score.set(clamp(score_for_good * weights.take_single_good, 0.0, 1.0));
             }
             None => {
                 println!("NO GOOD TO TAKE");
@@ -128,12 +148,10 @@ fn get_num_goods_in_hand(good_type: GoodType, goods_in_hand: &[GoodType]) -> usi
 // 3 of that good in your hand => 60%
 // 2 of that good in your hand => 40%
 // 1 of that good in your hand => 20%
-fn calculate_score(num_good_in_hand: usize, is_high_value_good: bool) -> f32 {
+fn calculate_score(num_good_in_hand: usize, good_value_multiplier: f32) -> f32 {
     let mut raw_score = ((num_good_in_hand + 1) * 2) as f32;
 
-    if is_high_value_good {
-        raw_score *= 1.5;
-    }
+    raw_score *= good_value_multiplier;
 
     raw_score /= 10.0;
 
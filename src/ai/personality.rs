@@ -0,0 +1,178 @@
+use bevy::prelude::*;
+use big_brain::thinker::{Thinker, ThinkerBuilder};
+use serde::{Deserialize, Serialize};
+
+use crate::localization::Localization;
+
+use super::{
+    model::{
+        deny_good::{DenyGoodAction, DenyGoodScorer, DenyGoodScorerState},
+        deplete_stack::{DepleteStackAction, DepleteStackScorer, DepleteStackScorerState},
+        endgame::{EndgameAction, EndgameScorer, EndgameScorerState},
+        exchange_goods::{ExchangeGoodsAction, ExchangeGoodsScorer, ExchangeGoodsScorerState},
+        sell_goods::{SellGoodsAction, SellGoodsScorer, SellGoodsScorerState},
+        take_all_camels::{TakeAllCamelsAction, TakeAllCamelsScorer, TakeAllCamelsScorerState},
+        take_single_good::{TakeSingleGoodAction, TakeSingleGoodScorer, TakeSingleGoodScorerState},
+    },
+    picker::{highest_score::HighestScorePicker, weighted::WeightedPicker},
+};
+
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub enum AiPersonality {
+    #[default]
+    Balanced,
+    CamelHoarder,
+    AggressiveSeller,
+    HighValueHoarder,
+    MarketDenier,
+}
+
+impl AiPersonality {
+    pub fn next(&self) -> Self {
+        match self {
+            AiPersonality::Balanced => AiPersonality::CamelHoarder,
+            AiPersonality::CamelHoarder => AiPersonality::AggressiveSeller,
+            AiPersonality::AggressiveSeller => AiPersonality::HighValueHoarder,
+            AiPersonality::HighValueHoarder => AiPersonality::MarketDenier,
+            AiPersonality::MarketDenier => AiPersonality::Balanced,
+        }
+    }
+
+    pub fn get_scorer_weights(&self) -> ScorerWeights {
+        match self {
+            AiPersonality::Balanced => ScorerWeights::default(),
+            // Chases the camel bonus, so is reluctant to give camels back to the market in exchanges
+            AiPersonality::CamelHoarder => ScorerWeights {
+                take_single_good: 0.9,
+                sell_goods: 0.9,
+                take_all_camels: 1.6,
+                exchange_goods: 0.6,
+                ..default()
+            },
+            // Sells early and often to empty token stacks before the opponent has built up large sets
+            AiPersonality::AggressiveSeller => ScorerWeights {
+                take_single_good: 0.8,
+                sell_goods: 1.4,
+                take_all_camels: 0.7,
+                exchange_goods: 0.9,
+                high_value_multiplier: 1.2,
+            },
+            AiPersonality::HighValueHoarder => ScorerWeights {
+                sell_goods: 0.9,
+                take_all_camels: 0.9,
+                high_value_multiplier: 2.2,
+                ..default()
+            },
+            AiPersonality::MarketDenier => ScorerWeights::default(),
+        }
+    }
+
+    pub fn localize(&self, localization: &Localization) -> String {
+        localization.get(match self {
+            AiPersonality::Balanced => "personality.balanced",
+            AiPersonality::CamelHoarder => "personality.camel_hoarder",
+            AiPersonality::AggressiveSeller => "personality.aggressive_seller",
+            AiPersonality::HighValueHoarder => "personality.high_value_hoarder",
+            AiPersonality::MarketDenier => "personality.market_denier",
+        })
+    }
+}
+
+// How strongly the computer plays, whatever its personality
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum AiDifficulty {
+    // Picks moves at random, weighted by their scores
+    Easy,
+    // Always picks the highest scoring move
+    Medium,
+    // Also searches for the best line of play once the game is nearly over
+    #[default]
+    Hard,
+}
+
+impl AiDifficulty {
+    pub fn next(&self) -> Self {
+        match self {
+            AiDifficulty::Easy => AiDifficulty::Medium,
+            AiDifficulty::Medium => AiDifficulty::Hard,
+            AiDifficulty::Hard => AiDifficulty::Easy,
+        }
+    }
+
+    pub fn localize(&self, localization: &Localization) -> String {
+        localization.get(match self {
+            AiDifficulty::Easy => "difficulty.easy",
+            AiDifficulty::Medium => "difficulty.medium",
+            AiDifficulty::Hard => "difficulty.hard",
+        })
+    }
+}
+
+// Multipliers applied by each scorer to its final score, before clamping to 0..1
+#[derive(Component, Debug, Copy, Clone)]
+pub struct ScorerWeights {
+    pub take_single_good: f32,
+    pub sell_goods: f32,
+    pub take_all_camels: f32,
+    pub exchange_goods: f32,
+    pub high_value_multiplier: f32,
+}
+
+impl Default for ScorerWeights {
+    fn default() -> Self {
+        Self {
+            take_single_good: 1.0,
+            sell_goods: 1.0,
+            take_all_camels: 1.0,
+            exchange_goods: 1.0,
+            high_value_multiplier: 1.5,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct AiThinker;
+
+pub fn build_thinker(personality: AiPersonality, difficulty: AiDifficulty) -> ThinkerBuilder {
+    let thinker = match difficulty {
+        AiDifficulty::Easy => Thinker::build().picker(WeightedPicker),
+        AiDifficulty::Medium => Thinker::build().picker(HighestScorePicker { threshold: 0.0 }),
+        // The endgame solver scores 100% whenever it finds a move, and comes first so it wins ties with the other
+        // scorers
+        AiDifficulty::Hard => Thinker::build()
+            .picker(HighestScorePicker { threshold: 0.0 })
+            .when(EndgameScorer, EndgameAction),
+    };
+
+    let thinker = thinker
+        .when(TakeSingleGoodScorer, TakeSingleGoodAction)
+        .when(SellGoodsScorer, SellGoodsAction)
+        .when(TakeAllCamelsScorer, TakeAllCamelsAction)
+        .when(ExchangeGoodsScorer, ExchangeGoodsAction);
+
+    match personality {
+        AiPersonality::AggressiveSeller => thinker.when(DepleteStackScorer, DepleteStackAction),
+        AiPersonality::MarketDenier => thinker.when(DenyGoodScorer, DenyGoodAction),
+        _ => thinker,
+    }
+}
+
+pub fn spawn_ai_thinker(
+    commands: &mut Commands,
+    personality: AiPersonality,
+    difficulty: AiDifficulty,
+) -> Entity {
+    commands
+        .spawn()
+        .insert(AiThinker)
+        .insert(personality.get_scorer_weights())
+        .insert(SellGoodsScorerState::default())
+        .insert(TakeSingleGoodScorerState::default())
+        .insert(TakeAllCamelsScorerState::default())
+        .insert(ExchangeGoodsScorerState::default())
+        .insert(DepleteStackScorerState::default())
+        .insert(DenyGoodScorerState::default())
+        .insert(EndgameScorerState::default())
+        .insert(build_thinker(personality, difficulty))
+        .id()
+}
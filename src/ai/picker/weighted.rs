@@ -18,7 +18,8 @@ impl Picker for WeightedPicker {
             .map(|choice| choice.calculate(scores))
             .collect::<Vec<_>>();
 
-        let dist = WeightedIndex::new(&weights).unwrap();
+        // Nothing is picked when every choice scores 0
+        let dist = WeightedIndex::new(&weights).ok()?;
 
         Some(&choices[dist.sample(&mut rng)])
     }
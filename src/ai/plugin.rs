@@ -1,45 +1,46 @@
 use bevy::prelude::*;
-use big_brain::{thinker::Thinker, BigBrainPlugin, BigBrainStage};
+use big_brain::{BigBrainPlugin, BigBrainStage};
 
-use crate::label::Label;
+use crate::{
+    common_systems::despawn_entity_with_component, daily_challenge::DAILY_CHALLENGE_AI_PERSONALITY,
+    label::Label, resources::GameState, settings::Settings, states::AppState,
+};
 
 use super::{
     model::{
-        exchange_goods::{
-            exchange_goods_action_system, exchange_goods_scorer_system, ExchangeGoodsAction,
-            ExchangeGoodsScorer, ExchangeGoodsScorerState,
-        },
-        sell_goods::{
-            sell_goods_action_system, sell_goods_scorer_system, SellGoodsAction, SellGoodsScorer,
-            SellGoodsScorerState,
-        },
-        take_all_camels::{
-            take_all_camels_action_system, take_all_camels_scorer_system, TakeAllCamelsAction,
-            TakeAllCamelsScorer, TakeAllCamelsScorerState,
-        },
-        take_single_good::{
-            take_single_good_action_system, take_single_good_scorer_system, TakeSingleGoodAction,
-            TakeSingleGoodScorer, TakeSingleGoodScorerState,
-        },
+        deny_good::{deny_good_action_system, deny_good_scorer_system},
+        deplete_stack::{deplete_stack_action_system, deplete_stack_scorer_system},
+        endgame::{endgame_action_system, endgame_scorer_system},
+        exchange_goods::{exchange_goods_action_system, exchange_goods_scorer_system},
+        sell_goods::{sell_goods_action_system, sell_goods_scorer_system},
+        take_all_camels::{take_all_camels_action_system, take_all_camels_scorer_system},
+        take_single_good::{take_single_good_action_system, take_single_good_scorer_system},
     },
-    picker::highest_score::HighestScorePicker,
+    personality::{spawn_ai_thinker, AiDifficulty, AiThinker},
 };
 
-pub fn init(mut commands: Commands) {
-    commands
-        .spawn()
-        .insert(SellGoodsScorerState::default())
-        .insert(TakeSingleGoodScorerState::default())
-        .insert(TakeAllCamelsScorerState::default())
-        .insert(ExchangeGoodsScorerState::default())
-        .insert(
-            Thinker::build()
-                .picker(HighestScorePicker { threshold: 0.0 })
-                .when(TakeSingleGoodScorer, TakeSingleGoodAction)
-                .when(SellGoodsScorer, SellGoodsAction)
-                .when(TakeAllCamelsScorer, TakeAllCamelsAction)
-                .when(ExchangeGoodsScorer, ExchangeGoodsAction),
-        );
+// The thinker is rebuilt for each game, since its scorers depend on the chosen personality
+pub fn init(
+    mut commands: Commands,
+    game_state: Res<GameState>,
+    settings: Res<Settings>,
+    thinker_query: Query<Entity, With<AiThinker>>,
+) {
+    for entity in thinker_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    // The computer's moves in the tutorial are scripted
+    if game_state.is_playing_ai && !game_state.is_playing_tutorial {
+        // The daily challenge is always played against the same opponent
+        let (personality, difficulty) = if game_state.is_playing_daily_challenge {
+            (DAILY_CHALLENGE_AI_PERSONALITY, AiDifficulty::default())
+        } else {
+            (game_state.ai_personality, settings.ai_difficulty)
+        };
+
+        spawn_ai_thinker(&mut commands, personality, difficulty);
+    }
 }
 
 pub struct JaipurAiPlugin;
@@ -47,7 +48,12 @@ pub struct JaipurAiPlugin;
 impl Plugin for JaipurAiPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
         app.add_plugin(BigBrainPlugin)
-            .add_startup_system(init)
+            .add_system_set(SystemSet::on_enter(AppState::InitGame).with_system(init))
+            // No thinker is needed until the next game against the computer
+            .add_system_set(
+                SystemSet::on_enter(AppState::MainMenu)
+                    .with_system(despawn_entity_with_component::<AiThinker>),
+            )
             .add_system_set_to_stage(
                 BigBrainStage::Actions,
                 SystemSet::new()
@@ -55,7 +61,10 @@ impl Plugin for JaipurAiPlugin {
                     .with_system(take_single_good_action_system)
                     .with_system(sell_goods_action_system)
                     .with_system(take_all_camels_action_system)
-                    .with_system(exchange_goods_action_system),
+                    .with_system(exchange_goods_action_system)
+                    .with_system(deplete_stack_action_system)
+                    .with_system(deny_good_action_system)
+                    .with_system(endgame_action_system),
             )
             .add_system_set_to_stage(
                 BigBrainStage::Scorers,
@@ -63,7 +72,10 @@ impl Plugin for JaipurAiPlugin {
                     .with_system(take_single_good_scorer_system)
                     .with_system(sell_goods_scorer_system)
                     .with_system(take_all_camels_scorer_system)
-                    .with_system(exchange_goods_scorer_system),
+                    .with_system(exchange_goods_scorer_system)
+                    .with_system(deplete_stack_scorer_system)
+                    .with_system(deny_good_scorer_system)
+                    .with_system(endgame_scorer_system),
             );
     }
 }
@@ -0,0 +1,849 @@
+use std::collections::HashMap;
+
+use itertools::Itertools;
+
+use crate::game_resources::{
+    card::{CardType, GoodType, GoodsMap},
+    deck_definition::DeckDefinition,
+    game_config::GameConfig,
+    tokens::{BonusType, Tokens},
+};
+
+// The solver only runs once the game tree is small: the deck is nearly empty or one more empty goods token stack would end the game
+pub const ENDGAME_DECK_THRESHOLD: usize = 6;
+// Unknown cards are those in the deck and the opponent's goods hand
+pub const MAX_UNKNOWN_CARDS: usize = 12;
+
+const MAX_SEARCH_DEPTH: usize = 4;
+// Bounds the total number of nodes visited across all possible opponent hands and search depths, so the AI turn doesn't stall
+const MAX_NODES: usize = 100_000;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SolverMove {
+    TakeSingleGood(GoodType),
+    TakeAllCamels,
+    ExchangeGoods {
+        take: Vec<GoodType>,
+        give_goods: Vec<GoodType>,
+        give_camels: usize,
+    },
+    SellGoods {
+        good: GoodType,
+        count: usize,
+    },
+}
+
+// The parts of the game config and deck definition that affect the game tree
+#[derive(Clone, Debug)]
+pub struct SolverRules {
+    pub max_goods_in_hand: usize,
+    pub camel_bonus: f32,
+    pub num_empty_token_stacks_to_end: usize,
+    pub min_sale_sizes: GoodsMap<usize>,
+}
+
+impl SolverRules {
+    pub fn new(config: &GameConfig, deck_definition: &DeckDefinition) -> Self {
+        Self {
+            max_goods_in_hand: config.max_goods_in_hand,
+            camel_bonus: config.camel_bonus as f32,
+            num_empty_token_stacks_to_end: config.num_empty_token_stacks_to_end,
+            min_sale_sizes: deck_definition.goods.map(|g| g.min_sale_size),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SolverPlayer {
+    pub goods: Vec<GoodType>,
+    pub camels: usize,
+    pub rupees: f32,
+}
+
+// The position as seen by the player to move, who is always players[0]
+#[derive(Clone, Debug)]
+pub struct SolverPosition {
+    pub market: Vec<CardType>,
+    pub tokens: Tokens,
+    pub players: [SolverPlayer; 2],
+    // Cards the player to move cannot see: the deck and the opponent's goods hand
+    pub unseen: Vec<CardType>,
+    pub opponent_goods_hand_size: usize,
+    pub rules: SolverRules,
+}
+
+#[derive(Clone, Debug)]
+struct SearchState<'a> {
+    market: Vec<CardType>,
+    deck: Vec<CardType>,
+    tokens: Tokens,
+    players: [SolverPlayer; 2],
+    to_move: usize,
+    is_game_over: bool,
+    rules: &'a SolverRules,
+}
+
+pub fn is_endgame(deck_size: usize, tokens: &Tokens, rules: &SolverRules) -> bool {
+    let num_empty_stacks = tokens
+        .goods
+        .iter()
+        .filter(|(_, token_values)| token_values.is_empty())
+        .count();
+
+    deck_size <= ENDGAME_DECK_THRESHOLD
+        || num_empty_stacks + 1 >= rules.num_empty_token_stacks_to_end
+}
+
+// Finds the move with the best expected outcome, by searching every possible opponent hand and order of the remaining deck.
+// Returns None if there are too many unknowns, or the node budget runs out before a depth 1 search completes.
+pub fn solve(position: &SolverPosition) -> Option<SolverMove> {
+    if position.unseen.len() > MAX_UNKNOWN_CARDS {
+        return None;
+    }
+
+    let possible_opponent_hands = get_possible_opponent_hands(position);
+    let total_weight: usize = possible_opponent_hands.iter().map(|(_, w)| w).sum();
+
+    if total_weight == 0 {
+        return None;
+    }
+
+    let root_moves = get_legal_moves(&position.market, &position.players[0], &position.rules);
+
+    let mut nodes_visited = 0;
+    let mut best_move = None;
+
+    // Iterative deepening - keep the result of the deepest search that completed within the node budget
+    for depth in 1..=MAX_SEARCH_DEPTH {
+        let mut expected_values = vec![0.0; root_moves.len()];
+
+        for (opponent_hand, weight) in possible_opponent_hands.iter() {
+            let probability = *weight as f32 / total_weight as f32;
+
+            let mut deck = position.unseen.clone();
+            for good in opponent_hand {
+                let idx = deck
+                    .iter()
+                    .position(|c| *c == CardType::Good(*good))
+                    .unwrap();
+                deck.remove(idx);
+            }
+
+            let mut opponent = position.players[1].clone();
+            opponent.goods = opponent_hand.clone();
+
+            let state = SearchState {
+                market: position.market.clone(),
+                deck,
+                tokens: position.tokens.clone(),
+                players: [position.players[0].clone(), opponent],
+                to_move: 0,
+                is_game_over: false,
+                rules: &position.rules,
+            };
+
+            for (move_idx, root_move) in root_moves.iter().enumerate() {
+                let value =
+                    match expected_value_of_move(&state, root_move, depth, &mut nodes_visited) {
+                        Some(v) => v,
+                        None => return best_move,
+                    };
+                expected_values[move_idx] += probability * value;
+            }
+        }
+
+        best_move = expected_values
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(idx, _)| root_moves[idx].clone());
+    }
+
+    best_move
+}
+
+// Each distinct multiset of goods the opponent could be holding, weighted by the number of ways it can be dealt from the unseen cards
+fn get_possible_opponent_hands(position: &SolverPosition) -> Vec<(Vec<GoodType>, usize)> {
+    let unseen_goods = position
+        .unseen
+        .iter()
+        .filter_map(|c| match c {
+            CardType::Good(g) => Some(*g),
+            CardType::Camel => None,
+        })
+        .collect::<Vec<_>>();
+
+    let mut hands: HashMap<Vec<GoodType>, usize> = HashMap::new();
+
+    for combination in unseen_goods
+        .iter()
+        .copied()
+        .combinations(position.opponent_goods_hand_size)
+    {
+        let hand = combination.into_iter().sorted().collect::<Vec<_>>();
+        *hands.entry(hand).or_insert(0) += 1;
+    }
+
+    hands.into_iter().collect()
+}
+
+fn expected_value_of_move(
+    state: &SearchState,
+    solver_move: &SolverMove,
+    depth: usize,
+    nodes_visited: &mut usize,
+) -> Option<f32> {
+    let mut expected_value = 0.0;
+
+    for (probability, next_state) in apply_move(state, solver_move) {
+        expected_value += probability * search(&next_state, depth - 1, nodes_visited)?;
+    }
+
+    Some(expected_value)
+}
+
+fn search(state: &SearchState, depth: usize, nodes_visited: &mut usize) -> Option<f32> {
+    *nodes_visited += 1;
+    if *nodes_visited > MAX_NODES {
+        return None;
+    }
+
+    if state.is_game_over || depth == 0 {
+        return Some(evaluate(state));
+    }
+
+    let moves = get_legal_moves(&state.market, &state.players[state.to_move], state.rules);
+
+    if moves.is_empty() {
+        return Some(evaluate(state));
+    }
+
+    let mut best_value: Option<f32> = None;
+
+    for solver_move in moves.iter() {
+        let value = expected_value_of_move(state, solver_move, depth, nodes_visited)?;
+
+        best_value = match best_value {
+            // The player at the root maximises, the opponent minimises
+            Some(best) if state.to_move == 0 => Some(best.max(value)),
+            Some(best) => Some(best.min(value)),
+            None => Some(value),
+        };
+    }
+
+    best_value
+}
+
+// Rupee difference from the perspective of the player at the root, including the camel bonus as if the game ended now
+fn evaluate(state: &SearchState) -> f32 {
+    let [player, opponent] = &state.players;
+
+    let camel_bonus = match player.camels.cmp(&opponent.camels) {
+        std::cmp::Ordering::Greater => state.rules.camel_bonus,
+        std::cmp::Ordering::Less => -state.rules.camel_bonus,
+        std::cmp::Ordering::Equal => 0.0,
+    };
+
+    player.rupees - opponent.rupees + camel_bonus
+}
+
+fn get_legal_moves(
+    market: &[CardType],
+    player: &SolverPlayer,
+    rules: &SolverRules,
+) -> Vec<SolverMove> {
+    let mut moves = vec![];
+
+    let market_goods = market
+        .iter()
+        .filter_map(|c| match c {
+            CardType::Good(g) => Some(*g),
+            CardType::Camel => None,
+        })
+        .collect::<Vec<_>>();
+
+    if player.goods.len() < rules.max_goods_in_hand {
+        for good in market_goods.iter().unique() {
+            moves.push(SolverMove::TakeSingleGood(*good));
+        }
+    }
+
+    if market.contains(&CardType::Camel) {
+        moves.push(SolverMove::TakeAllCamels);
+    }
+
+    for (good, count) in player.goods.iter().counts() {
+        for sell_count in rules.min_sale_sizes[*good]..=count {
+            moves.push(SolverMove::SellGoods {
+                good: *good,
+                count: sell_count,
+            });
+        }
+    }
+
+    for num_to_take in 2..=market_goods.len() {
+        for take in get_distinct_multisets(&market_goods, num_to_take) {
+            // Cannot exchange a good for the same type of good
+            let eligible_goods_in_hand = player
+                .goods
+                .iter()
+                .filter(|g| !take.contains(g))
+                .copied()
+                .collect::<Vec<_>>();
+
+            for give_camels in 0..=num_to_take.min(player.camels) {
+                // Each camel given adds a good to the hand
+                if player.goods.len() + give_camels > rules.max_goods_in_hand {
+                    break;
+                }
+
+                for give_goods in
+                    get_distinct_multisets(&eligible_goods_in_hand, num_to_take - give_camels)
+                {
+                    moves.push(SolverMove::ExchangeGoods {
+                        take: take.clone(),
+                        give_goods,
+                        give_camels,
+                    });
+                }
+            }
+        }
+    }
+
+    moves
+}
+
+fn get_distinct_multisets(goods: &[GoodType], size: usize) -> Vec<Vec<GoodType>> {
+    if size > goods.len() {
+        return vec![];
+    }
+
+    goods
+        .iter()
+        .copied()
+        .sorted()
+        .combinations(size)
+        .unique()
+        .collect()
+}
+
+// Returns each possible resulting state, with its probability
+fn apply_move<'a>(
+    state: &SearchState<'a>,
+    solver_move: &SolverMove,
+) -> Vec<(f32, SearchState<'a>)> {
+    let mut next_state = state.clone();
+    let player = &mut next_state.players[state.to_move];
+    let mut num_cards_to_draw = 0;
+    let mut bonus_type_won = None;
+
+    match solver_move {
+        SolverMove::TakeSingleGood(good) => {
+            remove_card(&mut next_state.market, CardType::Good(*good));
+            player.goods.push(*good);
+            num_cards_to_draw = 1;
+        }
+        SolverMove::TakeAllCamels => {
+            let num_camels = next_state
+                .market
+                .iter()
+                .filter(|c| **c == CardType::Camel)
+                .count();
+            next_state.market.retain(|c| *c != CardType::Camel);
+            player.camels += num_camels;
+            num_cards_to_draw = num_camels;
+        }
+        SolverMove::ExchangeGoods {
+            take,
+            give_goods,
+            give_camels,
+        } => {
+            for good in take {
+                remove_card(&mut next_state.market, CardType::Good(*good));
+                player.goods.push(*good);
+            }
+            for good in give_goods {
+                let idx = player.goods.iter().position(|g| g == good).unwrap();
+                player.goods.remove(idx);
+                next_state.market.push(CardType::Good(*good));
+            }
+            player.camels -= give_camels;
+            for _ in 0..*give_camels {
+                next_state.market.push(CardType::Camel);
+            }
+        }
+        SolverMove::SellGoods { good, count } => {
+            for _ in 0..*count {
+                let idx = player.goods.iter().position(|g| g == good).unwrap();
+                player.goods.remove(idx);
+
+                if let Some(val) = next_state.tokens.goods[*good].pop() {
+                    player.rupees += val as f32;
+                }
+            }
+
+            bonus_type_won = BonusType::from_num_sold(*count);
+
+            if next_state
+                .tokens
+                .goods
+                .iter()
+                .filter(|(_, token_values)| token_values.is_empty())
+                .count()
+                >= state.rules.num_empty_token_stacks_to_end
+            {
+                next_state.is_game_over = true;
+            }
+        }
+    }
+
+    next_state.to_move = 1 - state.to_move;
+
+    if let Some(bonus_type) = bonus_type_won {
+        return get_bonus_token_outcomes(next_state, bonus_type, state.to_move);
+    }
+
+    if num_cards_to_draw == 0 {
+        return vec![(1.0, next_state)];
+    }
+
+    get_draw_outcomes(&next_state.deck, num_cards_to_draw)
+        .into_iter()
+        .map(|(probability, drawn, remaining_deck)| {
+            let mut outcome_state = next_state.clone();
+            // The market cannot be fully refilled, which ends the game
+            if drawn.len() < num_cards_to_draw {
+                outcome_state.is_game_over = true;
+            }
+            outcome_state.market.extend(drawn);
+            outcome_state.deck = remaining_deck;
+            (probability, outcome_state)
+        })
+        .collect()
+}
+
+// Bonus tokens are shuffled, so the seller could be given any token left in the pile. Each value is an outcome of its
+// own, so that the token given is the one that leaves the pile.
+fn get_bonus_token_outcomes<'a>(
+    state: SearchState<'a>,
+    bonus_type: BonusType,
+    seller: usize,
+) -> Vec<(f32, SearchState<'a>)> {
+    let bonus_tokens = &state.tokens.bonus[bonus_type];
+    if bonus_tokens.is_empty() {
+        return vec![(1.0, state)];
+    }
+
+    bonus_tokens
+        .iter()
+        .counts()
+        .into_iter()
+        .map(|(value, count)| {
+            let mut outcome_state = state.clone();
+            let outcome_bonus_tokens = &mut outcome_state.tokens.bonus[bonus_type];
+            let idx = outcome_bonus_tokens
+                .iter()
+                .position(|v| v == value)
+                .unwrap();
+            outcome_bonus_tokens.remove(idx);
+            outcome_state.players[seller].rupees += *value as f32;
+
+            (count as f32 / bonus_tokens.len() as f32, outcome_state)
+        })
+        .collect()
+}
+
+// Each distinct set of cards that could be drawn from the deck, with its probability.
+// The order cards are placed in the market doesn't matter, so draws are merged regardless of order.
+fn get_draw_outcomes(
+    deck: &[CardType],
+    num_cards: usize,
+) -> Vec<(f32, Vec<CardType>, Vec<CardType>)> {
+    let mut outcomes: Vec<(f32, Vec<CardType>, Vec<CardType>)> = vec![(1.0, vec![], deck.to_vec())];
+
+    for _ in 0..num_cards {
+        let mut next_outcomes: Vec<(f32, Vec<CardType>, Vec<CardType>)> = vec![];
+
+        for (probability, drawn, remaining) in outcomes {
+            if remaining.is_empty() {
+                merge_outcome(&mut next_outcomes, probability, drawn, remaining);
+                continue;
+            }
+
+            for (card, count) in remaining.iter().counts() {
+                let mut next_drawn = drawn.clone();
+                next_drawn.push(*card);
+
+                let mut next_remaining = remaining.clone();
+                remove_card(&mut next_remaining, *card);
+
+                merge_outcome(
+                    &mut next_outcomes,
+                    probability * count as f32 / remaining.len() as f32,
+                    next_drawn,
+                    next_remaining,
+                );
+            }
+        }
+
+        outcomes = next_outcomes;
+    }
+
+    outcomes
+}
+
+fn merge_outcome(
+    outcomes: &mut Vec<(f32, Vec<CardType>, Vec<CardType>)>,
+    probability: f32,
+    drawn: Vec<CardType>,
+    remaining: Vec<CardType>,
+) {
+    let key = get_sort_key(&drawn);
+
+    match outcomes
+        .iter_mut()
+        .find(|(_, existing, _)| get_sort_key(existing) == key)
+    {
+        Some((existing_probability, _, _)) => *existing_probability += probability,
+        None => outcomes.push((probability, drawn, remaining)),
+    }
+}
+
+fn get_sort_key(cards: &[CardType]) -> Vec<usize> {
+    cards
+        .iter()
+        .map(|c| match c {
+            CardType::Camel => 0,
+            CardType::Good(g) => g.0 + 1,
+        })
+        .sorted()
+        .collect()
+}
+
+fn remove_card(cards: &mut Vec<CardType>, card: CardType) {
+    let idx = cards.iter().position(|c| *c == card).unwrap();
+    cards.remove(idx);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::move_validation::{
+        validate_sell, validate_take, BoardState, CardSelection, MoveType, MoveValidity,
+    };
+    use crate::test_fixtures::{official_goods, CLOTH, DIAMOND, LEATHER, SPICE};
+    use proptest::prelude::*;
+
+    use CardType::{Camel, Good};
+
+    fn official_rules() -> SolverRules {
+        SolverRules {
+            max_goods_in_hand: 7,
+            camel_bonus: 5.0,
+            num_empty_token_stacks_to_end: 3,
+            min_sale_sizes: official_goods().map(|goods_definition| goods_definition.min_sale_size),
+        }
+    }
+
+    fn player(goods: Vec<GoodType>, camels: usize) -> SolverPlayer {
+        SolverPlayer {
+            goods,
+            camels,
+            rupees: 0.0,
+        }
+    }
+
+    // Every goods token stack has a single 1 rupee token left, unless it is listed as empty
+    fn tokens(empty_stacks: &[GoodType]) -> Tokens {
+        let goods = official_goods();
+        let mut tokens = Tokens::create_empty(goods.len());
+        for good_type in goods.keys() {
+            if !empty_stacks.contains(&good_type) {
+                tokens.goods[good_type].push(1);
+            }
+        }
+        tokens
+    }
+
+    fn search_state<'a>(
+        market: Vec<CardType>,
+        deck: Vec<CardType>,
+        tokens: Tokens,
+        players: [SolverPlayer; 2],
+        rules: &'a SolverRules,
+    ) -> SearchState<'a> {
+        SearchState {
+            market,
+            deck,
+            tokens,
+            players,
+            to_move: 0,
+            is_game_over: false,
+            rules,
+        }
+    }
+
+    // The move the selection makes, if move validation allows it
+    fn get_validated_move(selection: &CardSelection, board: &BoardState) -> Option<SolverMove> {
+        let market_goods = selection
+            .market
+            .iter()
+            .filter_map(|c| match c {
+                Good(g) => Some(*g),
+                Camel => None,
+            })
+            .sorted()
+            .collect::<Vec<_>>();
+
+        if let MoveValidity::Valid(move_type) = validate_take(selection, board) {
+            return Some(match move_type {
+                MoveType::TakeSingleGood => SolverMove::TakeSingleGood(market_goods[0]),
+                MoveType::TakeAllCamels => SolverMove::TakeAllCamels,
+                _ => SolverMove::ExchangeGoods {
+                    take: market_goods,
+                    give_goods: selection.goods_hand.iter().copied().sorted().collect(),
+                    give_camels: selection.num_camels_from_hand,
+                },
+            });
+        }
+
+        match validate_sell(selection, &official_goods()) {
+            MoveValidity::Valid(_) => Some(SolverMove::SellGoods {
+                good: selection.goods_hand[0],
+                count: selection.goods_hand.len(),
+            }),
+            MoveValidity::Invalid(_) => None,
+        }
+    }
+
+    // The items whose bits are set in the mask
+    fn select<T: Copy>(items: &[T], mask: usize) -> Vec<T> {
+        items
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| mask & (1 << idx) != 0)
+            .map(|(_, item)| *item)
+            .collect()
+    }
+
+    // Every move that move validation allows, found by trying every selection of cards
+    fn get_validated_moves(market: &[CardType], player: &SolverPlayer) -> Vec<SolverMove> {
+        let board = BoardState {
+            market: market.to_vec(),
+            num_goods_in_hand: player.goods.len(),
+            max_goods_in_hand: official_rules().max_goods_in_hand,
+        };
+
+        let mut moves = vec![];
+        for market_mask in 0..(1 << market.len()) {
+            for hand_mask in 0..(1 << player.goods.len()) {
+                for num_camels_from_hand in 0..=player.camels {
+                    let selection = CardSelection {
+                        market: select(market, market_mask),
+                        goods_hand: select(&player.goods, hand_mask),
+                        num_camels_from_hand,
+                    };
+
+                    if let Some(validated_move) = get_validated_move(&selection, &board) {
+                        if !moves.contains(&validated_move) {
+                            moves.push(validated_move);
+                        }
+                    }
+                }
+            }
+        }
+        moves
+    }
+
+    fn good_type_strategy() -> impl Strategy<Value = GoodType> {
+        (0..official_goods().len()).prop_map(GoodType)
+    }
+
+    fn card_type_strategy() -> impl Strategy<Value = CardType> {
+        prop_oneof![Just(Camel), good_type_strategy().prop_map(Good)]
+    }
+
+    proptest! {
+        #[test]
+        fn draw_outcome_probabilities_sum_to_one(
+            deck in prop::collection::vec(card_type_strategy(), 0..=8),
+            num_cards in 1..=5_usize,
+        ) {
+            let outcomes = get_draw_outcomes(&deck, num_cards);
+            let total_probability = outcomes.iter().map(|(p, _, _)| p).sum::<f32>();
+
+            prop_assert!((total_probability - 1.0).abs() < 1e-4);
+
+            for (_, drawn, remaining) in outcomes {
+                prop_assert_eq!(drawn.len(), num_cards.min(deck.len()));
+                prop_assert_eq!(
+                    get_sort_key(&[drawn, remaining].concat()),
+                    get_sort_key(&deck)
+                );
+            }
+        }
+
+        #[test]
+        fn legal_moves_match_move_validation(
+            market in prop::collection::vec(card_type_strategy(), 5),
+            goods in prop::collection::vec(good_type_strategy(), 0..=7),
+            camels in 0..=3_usize,
+        ) {
+            let player = player(goods, camels);
+            let legal_moves = get_legal_moves(&market, &player, &official_rules());
+            let validated_moves = get_validated_moves(&market, &player);
+
+            for legal_move in legal_moves.iter() {
+                prop_assert!(validated_moves.contains(legal_move), "{:?} is not valid", legal_move);
+            }
+            for validated_move in validated_moves.iter() {
+                prop_assert!(legal_moves.contains(validated_move), "{:?} is missing", validated_move);
+            }
+        }
+    }
+
+    #[test]
+    fn draw_outcomes_merge_draws_of_the_same_cards() {
+        let outcomes = get_draw_outcomes(&[Camel, Camel, Good(DIAMOND)], 2);
+
+        assert_eq!(outcomes.len(), 2);
+        for (probability, drawn, _) in outcomes {
+            let expected_probability = if drawn.contains(&Good(DIAMOND)) {
+                2.0 / 3.0
+            } else {
+                1.0 / 3.0
+            };
+            assert!((probability - expected_probability).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn sale_emptying_the_last_stack_needed_ends_the_game() {
+        let rules = official_rules();
+        let state = search_state(
+            vec![Camel; 5],
+            vec![Camel; 10],
+            tokens(&[DIAMOND, CLOTH]),
+            [player(vec![LEATHER], 0), player(vec![], 0)],
+            &rules,
+        );
+
+        let outcomes = apply_move(
+            &state,
+            &SolverMove::SellGoods {
+                good: LEATHER,
+                count: 1,
+            },
+        );
+
+        assert_eq!(outcomes.len(), 1);
+        assert!(outcomes[0].1.is_game_over);
+        assert_eq!(outcomes[0].1.players[0].rupees, 1.0);
+    }
+
+    #[test]
+    fn sale_leaving_too_few_empty_stacks_does_not_end_the_game() {
+        let rules = official_rules();
+        let state = search_state(
+            vec![Camel; 5],
+            vec![Camel; 10],
+            tokens(&[DIAMOND]),
+            [player(vec![LEATHER], 0), player(vec![], 0)],
+            &rules,
+        );
+
+        let outcomes = apply_move(
+            &state,
+            &SolverMove::SellGoods {
+                good: LEATHER,
+                count: 1,
+            },
+        );
+
+        assert!(outcomes.iter().all(|(_, s)| !s.is_game_over));
+    }
+
+    #[test]
+    fn take_that_empties_the_deck_does_not_end_the_game() {
+        let rules = official_rules();
+        let state = search_state(
+            vec![Good(DIAMOND), Camel, Camel, Camel, Camel],
+            vec![Good(CLOTH)],
+            tokens(&[]),
+            [player(vec![], 0), player(vec![], 0)],
+            &rules,
+        );
+
+        let outcomes = apply_move(&state, &SolverMove::TakeSingleGood(DIAMOND));
+
+        assert_eq!(outcomes.len(), 1);
+        assert!(!outcomes[0].1.is_game_over);
+        assert!(outcomes[0].1.deck.is_empty());
+        assert_eq!(outcomes[0].1.market.len(), 5);
+    }
+
+    #[test]
+    fn take_that_cannot_refill_the_market_ends_the_game() {
+        let rules = official_rules();
+        let state = search_state(
+            vec![Good(DIAMOND), Good(SPICE), Good(LEATHER), Camel, Camel],
+            vec![Good(CLOTH)],
+            tokens(&[]),
+            [player(vec![], 0), player(vec![], 0)],
+            &rules,
+        );
+
+        let outcomes = apply_move(&state, &SolverMove::TakeAllCamels);
+
+        assert_eq!(outcomes.len(), 1);
+        assert!(outcomes[0].1.is_game_over);
+        assert_eq!(outcomes[0].1.players[0].camels, 2);
+    }
+
+    #[test]
+    fn sale_winning_a_bonus_gives_each_token_left_in_the_pile() {
+        let rules = official_rules();
+        let mut tokens = tokens(&[]);
+        tokens.goods[CLOTH] = vec![1, 2, 3];
+        tokens.bonus[BonusType::Three] = vec![1, 3, 3];
+        let state = search_state(
+            vec![Camel; 5],
+            vec![Camel; 10],
+            tokens,
+            [player(vec![CLOTH; 3], 0), player(vec![], 0)],
+            &rules,
+        );
+
+        let outcomes = apply_move(
+            &state,
+            &SolverMove::SellGoods {
+                good: CLOTH,
+                count: 3,
+            },
+        );
+
+        assert_eq!(outcomes.len(), 2);
+        for (probability, outcome_state) in outcomes {
+            let bonus_value = outcome_state.players[0].rupees - 6.0;
+            let mut expected_pile = vec![1, 3, 3];
+            let idx = expected_pile
+                .iter()
+                .position(|v| *v as f32 == bonus_value)
+                .unwrap();
+            expected_pile.remove(idx);
+
+            let mut pile = outcome_state.tokens.bonus[BonusType::Three].clone();
+            pile.sort();
+            assert_eq!(pile, expected_pile);
+            assert_eq!(
+                probability,
+                if bonus_value == 1.0 {
+                    1.0 / 3.0
+                } else {
+                    2.0 / 3.0
+                }
+            );
+            assert_eq!(outcome_state.to_move, 1);
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_tweening::lens::{TransformPositionLens, TransformRotationLens};
+use bevy_tweening::{Animator, EaseFunction, Tracks, Tween, TweeningType};
+use serde::{Deserialize, Serialize};
+
+use crate::localization::Localization;
+use crate::move_execution::TweenState;
+
+// How quickly cards and tokens move at the end of each turn, chosen in the settings menu
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum AnimationSpeed {
+    Slow,
+    #[default]
+    Normal,
+    Fast,
+    // Moves everything straight to where it ends up, without tweening
+    Instant,
+}
+
+impl AnimationSpeed {
+    pub fn next(&self) -> Self {
+        match self {
+            AnimationSpeed::Slow => AnimationSpeed::Normal,
+            AnimationSpeed::Normal => AnimationSpeed::Fast,
+            AnimationSpeed::Fast => AnimationSpeed::Instant,
+            AnimationSpeed::Instant => AnimationSpeed::Slow,
+        }
+    }
+
+    pub fn get_tween_duration(&self) -> Option<Duration> {
+        match self {
+            AnimationSpeed::Slow => Some(Duration::from_secs(3)),
+            AnimationSpeed::Normal => Some(Duration::from_secs(2)),
+            AnimationSpeed::Fast => Some(Duration::from_millis(800)),
+            AnimationSpeed::Instant => None,
+        }
+    }
+
+    // The pause after everything has stopped moving, before the next turn starts
+    pub fn get_screen_transition_delay(&self) -> Duration {
+        match self {
+            AnimationSpeed::Slow => Duration::from_secs(3),
+            AnimationSpeed::Normal => Duration::from_secs(2),
+            AnimationSpeed::Fast => Duration::from_millis(800),
+            AnimationSpeed::Instant => Duration::ZERO,
+        }
+    }
+
+    pub fn localize(&self, localization: &Localization) -> String {
+        localization.get(match self {
+            AnimationSpeed::Slow => "animation_speed.slow",
+            AnimationSpeed::Normal => "animation_speed.normal",
+            AnimationSpeed::Fast => "animation_speed.fast",
+            AnimationSpeed::Instant => "animation_speed.instant",
+        })
+    }
+}
+
+// Tweens the entity from start to end, or moves it there straight away when animations are instant. Each tween is
+// added to the TweenState, so the turn doesn't end until the entity has arrived.
+pub fn animate_transform(
+    commands: &mut Commands,
+    tween_state: &mut TweenState,
+    animation_speed: AnimationSpeed,
+    entity: Entity,
+    start: &Transform,
+    end: Transform,
+    user_data: u64,
+) {
+    // Any tweens the entity is still running are replaced, so won't complete
+    tween_state.tweening_entities.retain(|e| *e != entity);
+
+    let duration = match animation_speed.get_tween_duration() {
+        Some(duration) => duration,
+        None => {
+            commands
+                .entity(entity)
+                .remove::<Animator<Transform>>()
+                .insert(end);
+            return;
+        }
+    };
+
+    let mut tweens = vec![Tween::new(
+        EaseFunction::QuadraticInOut,
+        TweeningType::Once,
+        duration,
+        TransformPositionLens {
+            start: start.translation,
+            end: end.translation,
+        },
+    )
+    .with_completed_event(user_data)];
+    tween_state.tweening_entities.push(entity);
+
+    if start.rotation != end.rotation {
+        tweens.push(
+            Tween::new(
+                EaseFunction::QuadraticInOut,
+                TweeningType::Once,
+                duration,
+                TransformRotationLens {
+                    start: start.rotation,
+                    end: end.rotation,
+                },
+            )
+            .with_completed_event(user_data),
+        );
+        tween_state.tweening_entities.push(entity);
+    }
+
+    commands
+        .entity(entity)
+        .insert(Animator::new(Tracks::new(tweens)));
+}
@@ -0,0 +1,392 @@
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::{DrawMode, GeometryBuilder, StrokeMode};
+use bevy_prototype_lyon::shapes::{Rectangle, RectangleOrigin};
+
+use crate::card_selection::SelectedCardState;
+use crate::common_systems::despawn_entity_with_component;
+use crate::event::SelectionAssistEvent;
+use crate::game_resources::card::{ActivePlayerCamelCard, ActivePlayerGoodsCard, MarketCard};
+use crate::positioning::CARD_DIMENSION;
+use crate::states::AppState;
+
+// The rows of cards the focus cursor moves between, from the top of the screen down
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum FocusRow {
+    #[default]
+    Market,
+    CamelHand,
+    GoodsHand,
+}
+
+impl FocusRow {
+    fn up(&self) -> Option<Self> {
+        match self {
+            FocusRow::Market => None,
+            FocusRow::CamelHand => Some(FocusRow::Market),
+            FocusRow::GoodsHand => Some(FocusRow::CamelHand),
+        }
+    }
+
+    fn down(&self) -> Option<Self> {
+        match self {
+            FocusRow::Market => Some(FocusRow::CamelHand),
+            FocusRow::CamelHand => Some(FocusRow::GoodsHand),
+            FocusRow::GoodsHand => None,
+        }
+    }
+}
+
+// The card the keyboard acts on. The cursor is only shown once the keyboard has been used, so it does not
+// get in the way of playing with the mouse.
+#[derive(Debug, Default)]
+pub struct CardFocus {
+    pub row: FocusRow,
+    pub index: usize,
+    pub is_visible: bool,
+}
+
+#[derive(Component)]
+struct CardFocusCursor;
+
+type MarketCardQuery<'w, 's> = Query<'w, 's, (Entity, &'static MarketCard)>;
+type GoodsHandCardQuery<'w, 's> = Query<'w, 's, (Entity, &'static ActivePlayerGoodsCard)>;
+type CamelHandCardQuery<'w, 's> = Query<'w, 's, (Entity, &'static ActivePlayerCamelCard)>;
+
+// The cards in a row, from left to right
+fn get_row_entities(
+    row: FocusRow,
+    market_card_query: &MarketCardQuery,
+    goods_hand_card_query: &GoodsHandCardQuery,
+    camel_hand_card_query: &CamelHandCardQuery,
+) -> Vec<Entity> {
+    let mut cards = match row {
+        FocusRow::Market => market_card_query
+            .iter()
+            .map(|(entity, card)| (card.0, entity))
+            .collect::<Vec<_>>(),
+        FocusRow::GoodsHand => goods_hand_card_query
+            .iter()
+            .map(|(entity, card)| (card.0, entity))
+            .collect(),
+        FocusRow::CamelHand => camel_hand_card_query
+            .iter()
+            .map(|(entity, card)| (card.0, entity))
+            .collect(),
+    };
+
+    cards.sort_by_key(|(idx, _)| *idx);
+    cards.into_iter().map(|(_, entity)| entity).collect()
+}
+
+fn get_focused_entity(
+    card_focus: &CardFocus,
+    market_card_query: &MarketCardQuery,
+    goods_hand_card_query: &GoodsHandCardQuery,
+    camel_hand_card_query: &CamelHandCardQuery,
+) -> Option<Entity> {
+    let entities = get_row_entities(
+        card_focus.row,
+        market_card_query,
+        goods_hand_card_query,
+        camel_hand_card_query,
+    );
+
+    // Cards leave a row when they are taken or sold, so the focus stays on the last card left
+    entities
+        .get(card_focus.index.min(entities.len().saturating_sub(1)))
+        .copied()
+}
+
+// Arrow keys move the focus, Space selects or deselects the focused card and Escape clears the selection. With
+// nothing selected, Escape pauses the game instead - see game_ui.rs
+fn handle_card_focus_keys(
+    keyboard_input: Res<Input<KeyCode>>,
+    selected_card_state: Res<SelectedCardState>,
+    mut card_focus: ResMut<CardFocus>,
+    mut ev_selection_assist: EventWriter<SelectionAssistEvent>,
+    market_card_query: MarketCardQuery,
+    goods_hand_card_query: GoodsHandCardQuery,
+    camel_hand_card_query: CamelHandCardQuery,
+) {
+    let row_len = |row: FocusRow| {
+        get_row_entities(
+            row,
+            &market_card_query,
+            &goods_hand_card_query,
+            &camel_hand_card_query,
+        )
+        .len()
+    };
+
+    for key in keyboard_input.get_just_pressed() {
+        match key {
+            KeyCode::Left => {
+                card_focus.index = card_focus
+                    .index
+                    .min(row_len(card_focus.row).saturating_sub(1))
+                    .saturating_sub(1);
+            }
+            KeyCode::Right => {
+                card_focus.index =
+                    (card_focus.index + 1).min(row_len(card_focus.row).saturating_sub(1));
+            }
+            KeyCode::Up | KeyCode::Down => {
+                let next_row = |row: FocusRow| {
+                    if *key == KeyCode::Up {
+                        row.up()
+                    } else {
+                        row.down()
+                    }
+                };
+
+                // Empty rows, like a hand without camels, are skipped over
+                let mut row = next_row(card_focus.row);
+                while let Some(r) = row {
+                    if row_len(r) > 0 {
+                        break;
+                    }
+                    row = next_row(r);
+                }
+
+                if let Some(row) = row {
+                    card_focus.row = row;
+                }
+            }
+            KeyCode::Space => {
+                if let Some(entity) = get_focused_entity(
+                    &card_focus,
+                    &market_card_query,
+                    &goods_hand_card_query,
+                    &camel_hand_card_query,
+                ) {
+                    ev_selection_assist.send(SelectionAssistEvent::ToggleCard(entity));
+                }
+            }
+            KeyCode::Escape if !selected_card_state.0.is_empty() => {
+                ev_selection_assist.send(SelectionAssistEvent::ClearSelection);
+            }
+            _ => continue,
+        }
+
+        card_focus.is_visible = true;
+    }
+}
+
+fn spawn_card_focus_cursor(mut commands: Commands) {
+    let outline = Rectangle {
+        extents: CARD_DIMENSION + Vec2::splat(24.0),
+        origin: RectangleOrigin::Center,
+    };
+
+    commands
+        .spawn_bundle(GeometryBuilder::build_as(
+            &outline,
+            DrawMode::Stroke(StrokeMode::new(Color::WHITE, 4.0)),
+            Transform::default(),
+        ))
+        .insert(CardFocusCursor);
+}
+
+fn update_card_focus_cursor(
+    card_focus: Res<CardFocus>,
+    market_card_query: MarketCardQuery,
+    goods_hand_card_query: GoodsHandCardQuery,
+    camel_hand_card_query: CamelHandCardQuery,
+    card_transform_query: Query<&Transform, Without<CardFocusCursor>>,
+    mut cursor_query: Query<(&mut Transform, &mut Visibility), With<CardFocusCursor>>,
+) {
+    let focused_card_transform = get_focused_entity(
+        &card_focus,
+        &market_card_query,
+        &goods_hand_card_query,
+        &camel_hand_card_query,
+    )
+    .and_then(|entity| card_transform_query.get(entity).ok());
+
+    for (mut transform, mut visibility) in cursor_query.iter_mut() {
+        match focused_card_transform {
+            // Cards are children of the game root at the origin, so their local translation is their position on screen
+            Some(card_transform) if card_focus.is_visible => {
+                transform.translation = card_transform.translation + Vec3::Z;
+                visibility.is_visible = true;
+            }
+            _ => visibility.is_visible = false,
+        }
+    }
+}
+
+pub struct CardFocusPlugin;
+
+impl Plugin for CardFocusPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CardFocus>()
+            .add_system_set(
+                SystemSet::on_enter(AppState::InGame).with_system(spawn_card_focus_cursor),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::InGame)
+                    .with_system(handle_card_focus_keys)
+                    .with_system(update_card_focus_cursor.after(handle_card_focus_keys)),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::InGame)
+                    .with_system(despawn_entity_with_component::<CardFocusCursor>),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::event::Events;
+
+    fn create_app() -> App {
+        let mut app = App::new();
+        app.init_resource::<Input<KeyCode>>()
+            .init_resource::<CardFocus>()
+            .init_resource::<SelectedCardState>()
+            .add_event::<SelectionAssistEvent>()
+            .add_system(handle_card_focus_keys);
+        app
+    }
+
+    fn press_key(app: &mut App, key: KeyCode) {
+        app.world.resource_mut::<Input<KeyCode>>().press(key);
+        app.update();
+
+        let mut keyboard_input = app.world.resource_mut::<Input<KeyCode>>();
+        keyboard_input.release(key);
+        keyboard_input.clear();
+    }
+
+    fn get_sent_events(app: &App) -> Vec<&SelectionAssistEvent> {
+        let events = app.world.resource::<Events<SelectionAssistEvent>>();
+        events.get_reader().iter(events).collect()
+    }
+
+    fn spawn_row<C: Component>(
+        app: &mut App,
+        create_card: fn(usize) -> C,
+        len: usize,
+    ) -> Vec<Entity> {
+        (0..len)
+            .map(|idx| app.world.spawn().insert(create_card(idx)).id())
+            .collect()
+    }
+
+    #[test]
+    fn arrow_keys_move_the_focus_within_a_row() {
+        let mut app = create_app();
+        spawn_row(&mut app, MarketCard, 5);
+
+        press_key(&mut app, KeyCode::Right);
+        press_key(&mut app, KeyCode::Right);
+        assert_eq!(app.world.resource::<CardFocus>().index, 2);
+        assert!(app.world.resource::<CardFocus>().is_visible);
+
+        press_key(&mut app, KeyCode::Left);
+        assert_eq!(app.world.resource::<CardFocus>().index, 1);
+
+        // The focus stops at the ends of the row
+        for _ in 0..6 {
+            press_key(&mut app, KeyCode::Right);
+        }
+        assert_eq!(app.world.resource::<CardFocus>().index, 4);
+
+        for _ in 0..6 {
+            press_key(&mut app, KeyCode::Left);
+        }
+        assert_eq!(app.world.resource::<CardFocus>().index, 0);
+    }
+
+    #[test]
+    fn arrow_keys_move_the_focus_between_rows_skipping_empty_ones() {
+        let mut app = create_app();
+        spawn_row(&mut app, MarketCard, 5);
+        spawn_row(&mut app, ActivePlayerGoodsCard, 3);
+
+        // The player has no camels, so their camel hand is skipped
+        press_key(&mut app, KeyCode::Down);
+        assert_eq!(app.world.resource::<CardFocus>().row, FocusRow::GoodsHand);
+
+        press_key(&mut app, KeyCode::Down);
+        assert_eq!(app.world.resource::<CardFocus>().row, FocusRow::GoodsHand);
+
+        spawn_row(&mut app, ActivePlayerCamelCard, 2);
+        press_key(&mut app, KeyCode::Up);
+        assert_eq!(app.world.resource::<CardFocus>().row, FocusRow::CamelHand);
+
+        press_key(&mut app, KeyCode::Up);
+        assert_eq!(app.world.resource::<CardFocus>().row, FocusRow::Market);
+    }
+
+    #[test]
+    fn unused_keys_leave_the_cursor_hidden() {
+        let mut app = create_app();
+        spawn_row(&mut app, MarketCard, 5);
+
+        press_key(&mut app, KeyCode::A);
+
+        assert!(!app.world.resource::<CardFocus>().is_visible);
+        assert!(get_sent_events(&app).is_empty());
+    }
+
+    #[test]
+    fn space_toggles_the_focused_card() {
+        let mut app = create_app();
+        let market_cards = spawn_row(&mut app, MarketCard, 5);
+
+        press_key(&mut app, KeyCode::Right);
+        press_key(&mut app, KeyCode::Space);
+
+        assert!(matches!(
+            get_sent_events(&app)[..],
+            [SelectionAssistEvent::ToggleCard(entity)] if *entity == market_cards[1]
+        ));
+    }
+
+    #[test]
+    fn space_toggles_the_last_card_left_in_a_row() {
+        let mut app = create_app();
+        let goods_hand_cards = spawn_row(&mut app, ActivePlayerGoodsCard, 3);
+        app.world.resource_mut::<CardFocus>().row = FocusRow::GoodsHand;
+        app.world.resource_mut::<CardFocus>().index = 2;
+
+        // The focused card is sold
+        app.world.despawn(goods_hand_cards[2]);
+        press_key(&mut app, KeyCode::Space);
+
+        assert!(matches!(
+            get_sent_events(&app)[..],
+            [SelectionAssistEvent::ToggleCard(entity)] if *entity == goods_hand_cards[1]
+        ));
+    }
+
+    #[test]
+    fn escape_clears_the_selection() {
+        let mut app = create_app();
+        let market_cards = spawn_row(&mut app, MarketCard, 5);
+        app.world
+            .resource_mut::<SelectedCardState>()
+            .0
+            .push(market_cards[0]);
+
+        press_key(&mut app, KeyCode::Escape);
+
+        assert!(matches!(
+            get_sent_events(&app)[..],
+            [SelectionAssistEvent::ClearSelection]
+        ));
+    }
+
+    #[test]
+    fn escape_without_a_selection_leaves_the_cursor_hidden() {
+        let mut app = create_app();
+        spawn_row(&mut app, MarketCard, 5);
+
+        press_key(&mut app, KeyCode::Escape);
+
+        assert!(!app.world.resource::<CardFocus>().is_visible);
+        assert!(get_sent_events(&app).is_empty());
+    }
+}
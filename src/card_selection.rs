@@ -7,8 +7,12 @@ use bevy_prototype_lyon::{
 };
 use itertools::Itertools;
 
-use crate::game_resources::card::{ActivePlayerCamelCard, ActivePlayerGoodsCard, Card, MarketCard};
-use crate::positioning::CARD_DIMENSION;
+use crate::event::SelectionAssistEvent;
+use crate::game_resources::card::{
+    ActivePlayerCamelCard, ActivePlayerGoodsCard, Card, CardType, MarketCard,
+};
+use crate::positioning::{Layout, CARD_DIMENSION};
+use crate::states::TurnState;
 use crate::{event::ConfirmTurnEvent, label::Label, states::AppState};
 
 #[derive(Component)]
@@ -23,12 +27,120 @@ pub struct CardOutline;
 #[derive(Default)]
 pub struct SelectedCardState(pub Vec<Entity>);
 
-fn update_card_as_clicked(
-    mut commands: Commands,
+// How far the cursor must move while a card is pressed for it to be dragged rather than clicked
+const DRAG_THRESHOLD: f32 = 10.0;
+
+// A card pressed with the mouse. It is clicked when the button is released, unless the cursor moved far enough
+// to drag it, in which case it is dropped instead.
+struct PressedCard {
+    entity: Entity,
+    start_cursor_position: Vec2,
+    start_translation: Vec3,
+    is_dragging: bool,
+}
+
+#[derive(Default)]
+pub struct CardDragState(Option<PressedCard>);
+
+// Where a dragged card can be dropped to build a move
+#[derive(Debug, Copy, Clone)]
+enum DropTarget {
+    Hand,
+    MarketCard(Entity),
+    DiscardPile,
+}
+
+fn get_cursor_world_position(
+    windows: &Windows,
+    camera_query: &Query<(&Camera, &GlobalTransform)>,
+) -> Option<Vec2> {
+    let window = windows.get_primary()?;
+    let cursor_position = window.cursor_position()?;
+    let (camera, camera_transform) = camera_query.get_single().ok()?;
+
+    let window_size = Vec2::new(window.width(), window.height());
+    let ndc = (cursor_position / window_size) * 2.0 - Vec2::ONE;
+    let ndc_to_world = camera_transform.compute_matrix() * camera.projection_matrix().inverse();
+
+    Some(ndc_to_world.project_point3(ndc.extend(-1.0)).truncate())
+}
+
+fn is_over_card(card_translation: Vec3, position: Vec2) -> bool {
+    (position - card_translation.truncate())
+        .abs()
+        .cmple(0.5 * CARD_DIMENSION)
+        .all()
+}
+
+fn get_drop_target(
+    position: Vec2,
+    layout: &Layout,
+    dragged_entity: Entity,
+    market_card_query: &Query<(Entity, &Card, Option<&SelectedCard>), With<MarketCard>>,
+    transform_query: &Query<&mut Transform, With<Card>>,
+) -> Option<DropTarget> {
+    if is_over_card(layout.get_discard_pile_translation(), position) {
+        return Some(DropTarget::DiscardPile);
+    }
+
+    let market_entity = market_card_query
+        .iter()
+        .map(|(entity, _, _)| entity)
+        .filter(|entity| *entity != dragged_entity)
+        .find(|entity| {
+            transform_query.get(*entity).map_or(false, |transform| {
+                is_over_card(transform.translation, position)
+            })
+        });
+
+    if let Some(market_entity) = market_entity {
+        return Some(DropTarget::MarketCard(market_entity));
+    }
+
+    // Anywhere over the active player's goods and camels
+    if position.y < layout.get_camel_hand_start_translation().y + 0.5 * CARD_DIMENSION.y {
+        return Some(DropTarget::Hand);
+    }
+
+    None
+}
+
+fn click_card(
+    commands: &mut Commands,
+    card_entity: Entity,
+    turn_state: &TurnState,
+    market_card_query: &Query<(Entity, &Card, Option<&SelectedCard>), With<MarketCard>>,
+) {
+    commands.entity(card_entity).insert(ClickedCard);
+
+    // All camels in the market must be taken together, so clicking one toggles them all
+    if let Ok((_, Card(CardType::Camel), selected)) = market_card_query.get(card_entity) {
+        if *turn_state == TurnState::Take {
+            let is_selected = selected.is_some();
+
+            for (other_camel_entity, _, _) in
+                market_card_query
+                    .iter()
+                    .filter(|(e, card, other_selected)| {
+                        *e != card_entity
+                            && card.0 == CardType::Camel
+                            && other_selected.is_some() == is_selected
+                    })
+            {
+                commands.entity(other_camel_entity).insert(ClickedCard);
+            }
+        }
+    }
+}
+
+fn press_card(
     mouse_button_input: Res<Input<MouseButton>>,
     interaction_state: Res<InteractionState>,
-    mut card_query: Query<
-        Entity,
+    windows: Res<Windows>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    mut card_drag_state: ResMut<CardDragState>,
+    card_query: Query<
+        (Entity, &Transform),
         Or<(
             With<MarketCard>,
             With<ActivePlayerGoodsCard>,
@@ -36,15 +148,165 @@ fn update_card_as_clicked(
         )>,
     >,
 ) {
-    if mouse_button_input.just_pressed(MouseButton::Left) {
-        for card_entity in card_query.iter_mut() {
-            if interaction_state
-                .get_group(Group(0))
-                .iter()
-                .any(|(e, _)| *e == card_entity)
-            {
-                commands.entity(card_entity).insert(ClickedCard);
+    if !mouse_button_input.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    let cursor_position = match get_cursor_world_position(&windows, &camera_query) {
+        Some(cursor_position) => cursor_position,
+        None => return,
+    };
+
+    let interacting_entities = interaction_state.get_group(Group(0));
+
+    card_drag_state.0 = card_query
+        .iter()
+        .find(|(card_entity, _)| interacting_entities.iter().any(|(e, _)| e == card_entity))
+        .map(|(entity, transform)| PressedCard {
+            entity,
+            start_cursor_position: cursor_position,
+            start_translation: transform.translation,
+            is_dragging: false,
+        });
+}
+
+fn drag_card(
+    mouse_button_input: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    mut card_drag_state: ResMut<CardDragState>,
+    mut transform_query: Query<&mut Transform, With<Card>>,
+) {
+    let pressed_card = match card_drag_state.0.as_mut() {
+        Some(pressed_card) if mouse_button_input.pressed(MouseButton::Left) => pressed_card,
+        _ => return,
+    };
+
+    let cursor_position = match get_cursor_world_position(&windows, &camera_query) {
+        Some(cursor_position) => cursor_position,
+        None => return,
+    };
+
+    let offset = cursor_position - pressed_card.start_cursor_position;
+
+    if !pressed_card.is_dragging && offset.length() < DRAG_THRESHOLD {
+        return;
+    }
+
+    pressed_card.is_dragging = true;
+
+    if let Ok(mut transform) = transform_query.get_mut(pressed_card.entity) {
+        // Raised above the other cards while it is dragged
+        transform.translation = pressed_card.start_translation + offset.extend(10.0);
+    }
+}
+
+// Clicks the pressed card, or turns the drop into the same selection that clicking would build:
+// a market card dropped on the hand is taken, a hand card dropped on a market card is exchanged for it,
+// and a good dropped on the discard pile is sold. The move is then validated and confirmed as usual.
+#[allow(clippy::too_many_arguments)]
+fn release_card(
+    mut commands: Commands,
+    mouse_button_input: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    layout: Res<Layout>,
+    mut turn_state: ResMut<State<TurnState>>,
+    mut card_drag_state: ResMut<CardDragState>,
+    mut transform_query: Query<&mut Transform, With<Card>>,
+    market_card_query: Query<(Entity, &Card, Option<&SelectedCard>), With<MarketCard>>,
+    hand_card_query: Query<
+        (Entity, &Card, Option<&SelectedCard>),
+        Or<(With<ActivePlayerGoodsCard>, With<ActivePlayerCamelCard>)>,
+    >,
+) {
+    if !mouse_button_input.just_released(MouseButton::Left) {
+        return;
+    }
+
+    let pressed_card = match card_drag_state.0.take() {
+        Some(pressed_card) => pressed_card,
+        None => return,
+    };
+
+    if !pressed_card.is_dragging {
+        click_card(
+            &mut commands,
+            pressed_card.entity,
+            turn_state.current(),
+            &market_card_query,
+        );
+        return;
+    }
+
+    if let Ok(mut transform) = transform_query.get_mut(pressed_card.entity) {
+        transform.translation = pressed_card.start_translation;
+    }
+
+    let drop_target = get_cursor_world_position(&windows, &camera_query).and_then(|position| {
+        get_drop_target(
+            position,
+            &layout,
+            pressed_card.entity,
+            &market_card_query,
+            &transform_query,
+        )
+    });
+
+    let dragged_market_card = market_card_query.get(pressed_card.entity).ok();
+    let dragged_hand_card = hand_card_query.get(pressed_card.entity).ok();
+
+    let (desired_turn_state, entities_to_select) =
+        match (drop_target, dragged_market_card, dragged_hand_card) {
+            (Some(DropTarget::Hand), Some((_, Card(CardType::Camel), _)), _) => (
+                TurnState::Take,
+                market_card_query
+                    .iter()
+                    .filter(|(_, card, _)| card.0 == CardType::Camel)
+                    .map(|(entity, _, _)| entity)
+                    .collect::<Vec<_>>(),
+            ),
+            (Some(DropTarget::Hand), Some((entity, _, _)), _) => (TurnState::Take, vec![entity]),
+            (Some(DropTarget::MarketCard(market_entity)), _, Some((entity, _, _))) => {
+                (TurnState::Take, vec![entity, market_entity])
+            }
+            (Some(DropTarget::DiscardPile), _, Some((entity, Card(CardType::Good(_)), _))) => {
+                (TurnState::Sell, vec![entity])
             }
+            _ => return,
+        };
+
+    if *turn_state.current() != desired_turn_state {
+        turn_state.set(desired_turn_state).unwrap();
+    }
+
+    let is_selected = |entity: Entity| {
+        market_card_query
+            .get(entity)
+            .map(|(_, _, selected)| selected.is_some())
+            .or_else(|_| {
+                hand_card_query
+                    .get(entity)
+                    .map(|(_, _, selected)| selected.is_some())
+            })
+            .unwrap_or(false)
+    };
+
+    for entity in entities_to_select {
+        if !is_selected(entity) {
+            commands.entity(entity).insert(ClickedCard);
+        }
+    }
+}
+
+// Puts back a card that was still being dragged when the turn ended
+fn cancel_card_drag(
+    mut card_drag_state: ResMut<CardDragState>,
+    mut transform_query: Query<&mut Transform, With<Card>>,
+) {
+    if let Some(pressed_card) = card_drag_state.0.take() {
+        if let Ok(mut transform) = transform_query.get_mut(pressed_card.entity) {
+            transform.translation = pressed_card.start_translation;
         }
     }
 }
@@ -129,6 +391,80 @@ fn handle_selected_card_removed(
     }
 }
 
+fn deselect_cards(
+    commands: &mut Commands,
+    selected_card_state: &mut ResMut<SelectedCardState>,
+    entities: &[Entity],
+) {
+    for entity in entities {
+        commands.entity(*entity).remove::<SelectedCard>();
+    }
+
+    selected_card_state.0.retain(|e| !entities.contains(e));
+}
+
+fn handle_selection_assist_events(
+    mut commands: Commands,
+    mut ev_selection_assist: EventReader<SelectionAssistEvent>,
+    mut selected_card_state: ResMut<SelectedCardState>,
+    turn_state: Res<State<TurnState>>,
+    goods_hand_query: Query<(Entity, &Card, Option<&SelectedCard>), With<ActivePlayerGoodsCard>>,
+    market_card_query: Query<(Entity, &Card, Option<&SelectedCard>), With<MarketCard>>,
+) {
+    for ev in ev_selection_assist.iter() {
+        match ev {
+            SelectionAssistEvent::ToggleCard(entity) => {
+                click_card(
+                    &mut commands,
+                    *entity,
+                    turn_state.current(),
+                    &market_card_query,
+                );
+            }
+            SelectionAssistEvent::ClearSelection => {
+                let selected_entities = selected_card_state.0.clone();
+                deselect_cards(&mut commands, &mut selected_card_state, &selected_entities);
+            }
+            SelectionAssistEvent::SelectAllMatchingGoods(good_type) => {
+                for (entity, _, _) in goods_hand_query.iter().filter(|(_, card, selected)| {
+                    card.0 == CardType::Good(*good_type) && selected.is_none()
+                }) {
+                    commands.entity(entity).insert(ClickedCard);
+                }
+            }
+        }
+    }
+}
+
+// Market cards and camels from the hand can never be part of a sale
+fn remove_incompatible_selections_for_sell(
+    mut commands: Commands,
+    mut selected_card_state: ResMut<SelectedCardState>,
+    incompatible_selected_cards_query: Query<
+        Entity,
+        (
+            With<SelectedCard>,
+            Or<(With<MarketCard>, With<ActivePlayerCamelCard>)>,
+        ),
+    >,
+) {
+    let incompatible_entities = incompatible_selected_cards_query.iter().collect::<Vec<_>>();
+    deselect_cards(
+        &mut commands,
+        &mut selected_card_state,
+        &incompatible_entities,
+    );
+}
+
+fn remove_all_selections(
+    mut commands: Commands,
+    mut selected_card_state: ResMut<SelectedCardState>,
+    selected_cards_query: Query<Entity, With<SelectedCard>>,
+) {
+    let selected_entities = selected_cards_query.iter().collect::<Vec<_>>();
+    deselect_cards(&mut commands, &mut selected_card_state, &selected_entities);
+}
+
 fn remove_card_selections_on_confirm_turn(
     mut commands: Commands,
     mut ev_confirm_turn: EventReader<ConfirmTurnEvent>,
@@ -172,20 +508,25 @@ impl Plugin for CardSelectionPlugin {
         app.add_plugin(ShapePlugin)
             .add_plugin(InteractionPlugin)
             .init_resource::<SelectedCardState>()
+            .init_resource::<CardDragState>()
             .add_system_set(
                 SystemSet::on_enter(AppState::InGame).with_system(setup_interactable_cards),
             )
             .add_system_set(
                 SystemSet::on_update(AppState::InGame)
-                    .with_system(update_card_as_clicked)
-                    .with_system(update_card_as_selected.after(update_card_as_clicked))
-                    .with_system(update_card_as_unselected.after(update_card_as_clicked))
+                    .with_system(press_card)
+                    .with_system(drag_card.after(press_card))
+                    .with_system(release_card.after(drag_card))
+                    .with_system(update_card_as_selected.after(release_card))
+                    .with_system(update_card_as_unselected.after(release_card))
+                    .with_system(handle_selection_assist_events)
                     .with_system(
                         remove_card_selections_on_confirm_turn
                             .label(Label::ConfirmTurnEventReader)
                             .after(Label::ConfirmTurnEventWriter),
                     ),
             )
+            .add_system_set(SystemSet::on_exit(AppState::InGame).with_system(cancel_card_drag))
             .add_system_set(
                 SystemSet::on_update(AppState::AiTurn).with_system(
                     remove_card_selections_on_confirm_turn
@@ -193,6 +534,11 @@ impl Plugin for CardSelectionPlugin {
                         .after(Label::ConfirmTurnEventWriter),
                 ),
             )
+            .add_system_set(
+                SystemSet::on_enter(TurnState::Sell)
+                    .with_system(remove_incompatible_selections_for_sell),
+            )
+            .add_system_set(SystemSet::on_enter(TurnState::None).with_system(remove_all_selections))
             // component removal occurs at the end of the stage (i.e. update stage), so this system needs to go in PostUpdate
             .add_system_to_stage(CoreStage::PostUpdate, handle_selected_card_removed);
     }
@@ -0,0 +1,296 @@
+use std::fmt;
+
+use bevy::prelude::*;
+use rand::{rngs::StdRng, SeedableRng};
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+
+use crate::ai::personality::AiPersonality;
+use crate::game::{
+    get_final_score, AiPlayer, CamelsHandOwner, Forfeited, HumanPlayer, TokensOwner,
+};
+use crate::game_resources::game_config::GameConfig;
+use crate::label::Label;
+use crate::localization::Localization;
+use crate::resources::GameState;
+use crate::states::AppState;
+use crate::storage;
+
+// The computer plays every daily challenge with the same personality, so that scores on the same deal can be compared
+pub const DAILY_CHALLENGE_AI_PERSONALITY: AiPersonality = AiPersonality::Balanced;
+
+const RESULTS_STORAGE_KEY: &str = "daily_challenge_results";
+
+const SECS_PER_DAY: u64 = 60 * 60 * 24;
+
+// Days since the Unix epoch in UTC, so that everyone is dealt the same cards on the same day
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct ChallengeDay(pub u64);
+
+impl ChallengeDay {
+    pub fn today() -> Self {
+        Self(storage::get_unix_time_secs() / SECS_PER_DAY)
+    }
+
+    // Shuffles the deck and the bonus tokens the same way for every game on this day
+    pub fn create_rng(&self) -> StdRng {
+        StdRng::seed_from_u64(self.0)
+    }
+}
+
+impl fmt::Display for ChallengeDay {
+    // Formats as YYYY-MM-DD, converting from days to a civil date with the algorithm from
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let z = self.0 + 719_468;
+        let era = z / 146_097;
+        let day_of_era = z - era * 146_097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        };
+        let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+        write!(f, "{:04}-{:02}-{:02}", year, month, day)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyChallengeResult {
+    // The day's date, as YYYY-MM-DD
+    pub date: String,
+    pub score: usize,
+    pub opponent_score: usize,
+    // A forfeited game is lost whatever the score, so it's never the best score of the day
+    pub is_forfeit: bool,
+}
+
+// Every daily challenge finished on this device, oldest first
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DailyChallengeResults {
+    pub results: Vec<DailyChallengeResult>,
+}
+
+impl DailyChallengeResults {
+    pub fn load() -> Self {
+        let results = match storage::load(RESULTS_STORAGE_KEY) {
+            Some(results) => results,
+            None => return Self::default(),
+        };
+
+        ron::from_str(&results).unwrap_or_else(|err| {
+            warn!("Could not read daily challenge results: {}", err);
+            Self::default()
+        })
+    }
+
+    pub fn save(&self) {
+        let saved = ron::ser::to_string_pretty(self, PrettyConfig::default())
+            .map_err(|err| err.to_string())
+            .and_then(|results| storage::save(RESULTS_STORAGE_KEY, &results));
+
+        if let Err(err) = saved {
+            warn!("Could not save daily challenge results: {}", err);
+        }
+    }
+
+    pub fn get_best_result(&self, day: ChallengeDay) -> Option<&DailyChallengeResult> {
+        let date = day.to_string();
+
+        self.results
+            .iter()
+            .filter(|result| result.date == date && !result.is_forfeit)
+            .max_by_key(|result| result.score)
+    }
+
+    pub fn get_num_games(&self, day: ChallengeDay) -> usize {
+        let date = day.to_string();
+
+        self.results
+            .iter()
+            .filter(|result| result.date == date)
+            .count()
+    }
+}
+
+// Present while a daily challenge is played. The game is played against the computer by the official rules.
+pub struct DailyChallenge {
+    pub day: ChallengeDay,
+    // Set when the game ends, once the result has been recorded
+    pub result: Option<DailyChallengeResult>,
+    // The best score on this day from earlier games, not including this one
+    pub previous_best_score: Option<usize>,
+    pub num_games: usize,
+}
+
+impl DailyChallenge {
+    pub fn new(day: ChallengeDay) -> Self {
+        Self {
+            day,
+            result: None,
+            previous_best_score: None,
+            num_games: 0,
+        }
+    }
+
+    pub fn get_result_text(&self, localization: &Localization) -> Option<String> {
+        let result = self.result.as_ref()?;
+
+        let best_score_text = match self.previous_best_score {
+            _ if result.is_forfeit => localization.get("daily_challenge.forfeit"),
+            Some(best_score) if best_score >= result.score => {
+                localization.format("daily_challenge.best_today", &[("score", &best_score)])
+            }
+            Some(_) => localization.get("daily_challenge.new_best"),
+            None => localization.get("daily_challenge.first_score"),
+        };
+
+        Some(localization.format(
+            "daily_challenge.result",
+            &[
+                ("day", &self.day),
+                ("best", &best_score_text),
+                ("count", &self.num_games),
+            ],
+        ))
+    }
+}
+
+pub fn get_menu_text(localization: &Localization) -> String {
+    let day = ChallengeDay::today();
+
+    match DailyChallengeResults::load().get_best_result(day) {
+        Some(result) => localization.format(
+            "menu.daily_challenge_best",
+            &[("day", &day), ("score", &result.score)],
+        ),
+        None => localization.format("menu.daily_challenge", &[("day", &day)]),
+    }
+}
+
+// Runs as each turn ends, like puzzle::check_puzzle_goal, so the result is recorded before the game over screen is shown.
+// Also runs as the game over screen is set up, since a forfeited game goes straight to it.
+fn record_daily_challenge_result(
+    daily_challenge: Option<ResMut<DailyChallenge>>,
+    game_state: Res<GameState>,
+    game_config: Res<GameConfig>,
+    human_player_query: Query<
+        (&TokensOwner, &CamelsHandOwner, Option<&Forfeited>),
+        With<HumanPlayer>,
+    >,
+    ai_player_query: Query<(&TokensOwner, &CamelsHandOwner), With<AiPlayer>>,
+) {
+    let mut daily_challenge = match daily_challenge {
+        Some(daily_challenge) if game_state.is_game_over && daily_challenge.result.is_none() => {
+            daily_challenge
+        }
+        _ => return,
+    };
+
+    let (tokens_owner, camels_hand, forfeited) = human_player_query.single();
+    let (opponent_tokens_owner, opponent_camels_hand) = ai_player_query.single();
+
+    let result = DailyChallengeResult {
+        date: daily_challenge.day.to_string(),
+        score: get_final_score(
+            &tokens_owner.0,
+            camels_hand.0,
+            opponent_camels_hand.0,
+            &game_config,
+        ),
+        opponent_score: get_final_score(
+            &opponent_tokens_owner.0,
+            opponent_camels_hand.0,
+            camels_hand.0,
+            &game_config,
+        ),
+        is_forfeit: forfeited.is_some(),
+    };
+
+    let mut results = DailyChallengeResults::load();
+    daily_challenge.previous_best_score = results
+        .get_best_result(daily_challenge.day)
+        .map(|best_result| best_result.score);
+
+    results.results.push(result.clone());
+    results.save();
+
+    daily_challenge.num_games = results.get_num_games(daily_challenge.day);
+    daily_challenge.result = Some(result);
+}
+
+pub struct DailyChallengePlugin;
+
+impl Plugin for DailyChallengePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_enter(AppState::WaitForTweensToFinish)
+                .with_system(record_daily_challenge_result),
+        )
+        .add_system_set(
+            SystemSet::on_enter(AppState::GameOver).with_system(
+                record_daily_challenge_result.label(Label::DailyChallengeResultWriter),
+            ),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(date: &str, score: usize, is_forfeit: bool) -> DailyChallengeResult {
+        DailyChallengeResult {
+            date: date.to_string(),
+            score,
+            opponent_score: 50,
+            is_forfeit,
+        }
+    }
+
+    #[test]
+    fn challenge_day_displays_known_dates() {
+        assert_eq!(ChallengeDay(0).to_string(), "1970-01-01");
+        assert_eq!(ChallengeDay(10_956).to_string(), "1999-12-31");
+        assert_eq!(ChallengeDay(10_957).to_string(), "2000-01-01");
+        assert_eq!(ChallengeDay(20_088).to_string(), "2024-12-31");
+    }
+
+    #[test]
+    fn challenge_day_displays_leap_days() {
+        // 2000 is a leap year, since it is divisible by 400
+        assert_eq!(ChallengeDay(11_016).to_string(), "2000-02-29");
+        assert_eq!(ChallengeDay(11_017).to_string(), "2000-03-01");
+        assert_eq!(ChallengeDay(19_782).to_string(), "2024-02-29");
+    }
+
+    #[test]
+    fn challenge_day_skips_leap_days_in_common_years() {
+        assert_eq!(ChallengeDay(19_416).to_string(), "2023-02-28");
+        assert_eq!(ChallengeDay(19_417).to_string(), "2023-03-01");
+        // 2100 is not a leap year, since it is divisible by 100 but not by 400
+        assert_eq!(ChallengeDay(47_540).to_string(), "2100-02-28");
+        assert_eq!(ChallengeDay(47_541).to_string(), "2100-03-01");
+    }
+
+    #[test]
+    fn forfeits_count_as_games_but_never_as_the_best_score() {
+        let day = ChallengeDay(19_782);
+        let results = DailyChallengeResults {
+            results: vec![
+                result("2024-02-28", 90, false),
+                result("2024-02-29", 60, false),
+                result("2024-02-29", 80, true),
+            ],
+        };
+
+        assert_eq!(results.get_best_result(day).unwrap().score, 60);
+        assert_eq!(results.get_num_games(day), 2);
+    }
+}
@@ -1,13 +1,36 @@
 use bevy::prelude::*;
 
-use crate::move_validation::MoveType;
+use crate::{
+    game_resources::{card::GoodType, tokens::BonusType},
+    move_validation::MoveType,
+};
 
 pub struct ConfirmTurnEvent(pub MoveType);
 
+// Sent when the active player sells goods, with the tokens they were given
+pub struct TokensAwardedEvent {
+    // In the order they were taken from the top of the stack
+    pub goods_tokens: Vec<(GoodType, usize)>,
+    pub bonus_type: Option<BonusType>,
+}
+
+pub enum SelectionAssistEvent {
+    ClearSelection,
+    // Selects or deselects a card as if it were clicked
+    ToggleCard(Entity),
+    SelectAllMatchingGoods(GoodType),
+}
+
+// Sent from the pause menu when the player whose turn it is gives up the game
+pub struct ForfeitEvent;
+
 pub struct EventsPlugin;
 
 impl Plugin for EventsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<ConfirmTurnEvent>();
+        app.add_event::<ConfirmTurnEvent>()
+            .add_event::<TokensAwardedEvent>()
+            .add_event::<SelectionAssistEvent>()
+            .add_event::<ForfeitEvent>();
     }
 }
@@ -1,24 +1,46 @@
+use bevy::asset::LoadState;
 use bevy::prelude::*;
 use itertools::{Either, Itertools};
+use rand::{rngs::StdRng, SeedableRng};
 use std::cmp::Ordering;
 
 use crate::ai::plugin::JaipurAiPlugin;
+use crate::card_focus::CardFocusPlugin;
 use crate::card_selection::{CardSelectionPlugin, SelectedCardState};
 use crate::common_systems::despawn_entity_with_component;
+use crate::daily_challenge::{ChallengeDay, DailyChallenge, DailyChallengePlugin};
+use crate::event::ForfeitEvent;
 use crate::game_resources::card::*;
 use crate::game_resources::deck::Deck;
+use crate::game_resources::deck_definition::{
+    DeckDefinition, DeckDefinitionHandle, DeckDefinitionPlugin,
+};
 use crate::game_resources::discard_pile::DiscardPile;
+use crate::game_resources::game_config::GameConfig;
 use crate::game_resources::market::Market;
+use crate::game_resources::puzzle_definition::{PuzzleDefinition, PuzzleDefinitionHandle};
 use crate::game_resources::tokens::*;
+use crate::hand_sorting::HandSortingPlugin;
+#[cfg(debug_assertions)]
+use crate::invariants::InvariantCheckPlugin;
+use crate::label::Label;
+use crate::localization::Localization;
 use crate::move_execution::{MoveExecutionPlugin, ScreenTransitionDelayTimer, TweenState};
+use crate::move_history::{MoveHistory, MoveHistoryPlugin};
 use crate::move_validation::{MoveValidationPlugin, MoveValidity};
-use crate::positioning::{
-    get_active_player_camel_card_translation, get_active_player_goods_card_translation,
-    get_market_card_translation, get_opponent_camel_hand_translation, CARD_DIMENSION, CARD_PADDING,
-    DECK_START_POS, DISCARD_PILE_POS, INACTIVE_PLAYER_GOODS_HAND_START_POS,
-};
+use crate::positioning::{Layout, CARD_DIMENSION};
+use crate::puzzle::{PuzzleOutcome, PuzzlePlugin, PuzzleState, PUZZLES};
 use crate::resources::GameState;
-use crate::states::AppState;
+use crate::score_breakdown::{
+    get_rupee_totals_by_turn, spawn_rupee_chart, spawn_score_breakdown, ScoreBreakdown,
+    PLAYER_CHART_COLORS,
+};
+use crate::settings::Settings;
+use crate::states::{AppState, TurnState};
+use crate::token_stacks::TokenStacksPlugin;
+use crate::tutorial::{
+    create_tutorial_puzzle, get_end_of_tutorial_text, TutorialPlugin, TutorialState,
+};
 
 #[allow(clippy::too_many_arguments)]
 fn handle_when_resources_ready(
@@ -42,9 +64,14 @@ fn handle_when_resources_ready(
         && move_validity.is_some()
         && discard_pile.is_some();
 
-    match (resources_are_ready, game_state.is_playing_ai) {
-        (true, true) => state.set(AppState::InGame).unwrap(),
-        (true, false) => state.set(AppState::TurnTransition).unwrap(),
+    match (
+        resources_are_ready,
+        game_state.is_playing_ai,
+        game_state.is_second_player_starting,
+    ) {
+        (true, true, false) => state.set(AppState::InGame).unwrap(),
+        (true, true, true) => state.set(AppState::AiTurn).unwrap(),
+        (true, false, _) => state.set(AppState::TurnTransition).unwrap(),
         _ => {}
     }
 }
@@ -56,9 +83,36 @@ const PRESSED_BUTTON: Color = Color::rgb(0.35, 0.75, 0.35);
 #[derive(Component)]
 struct TurnTransitionScreen;
 
+#[derive(Component)]
+struct GameOverScreen;
+
+#[derive(Component, Copy, Clone)]
+enum GameOverButton {
+    // The same kind of game again, started by the other player
+    Rematch,
+    NewGame,
+}
+
+impl GameOverButton {
+    fn get_text_key(&self) -> &'static str {
+        match self {
+            GameOverButton::Rematch => "game_over.rematch",
+            GameOverButton::NewGame => "game_over.new_game",
+        }
+    }
+}
+
+// Puzzles, the daily challenge and the tutorial always start from the same position, with the human player to move
+fn can_rematch(game_state: &GameState) -> bool {
+    !game_state.is_playing_puzzle
+        && !game_state.is_playing_daily_challenge
+        && !game_state.is_playing_tutorial
+}
+
 fn setup_turn_transition_screen(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
+    localization: Res<Localization>,
     query: Query<&PlayerName, With<ActivePlayer>>,
 ) {
     let current_player_name = &query.single().0;
@@ -79,7 +133,10 @@ fn setup_turn_transition_screen(
         .with_children(|parent| {
             parent.spawn_bundle(
                 TextBundle::from_section(
-                    format!("{}: your turn", current_player_name),
+                    localization.format(
+                        "turn_transition.your_turn",
+                        &[("name", current_player_name)],
+                    ),
                     TextStyle {
                         font: asset_server.load("fonts/FiraSans-Bold.ttf"),
                         font_size: 40.0,
@@ -110,7 +167,7 @@ fn setup_turn_transition_screen(
                 })
                 .with_children(|parent| {
                     parent.spawn_bundle(TextBundle::from_section(
-                        "Start turn",
+                        localization.get("turn_transition.start_turn"),
                         TextStyle {
                             font: asset_server.load("fonts/FiraSans-Bold.ttf"),
                             font_size: 40.0,
@@ -123,11 +180,17 @@ fn setup_turn_transition_screen(
 
 fn handle_turn_transition_screen_interaction(
     mut state: ResMut<State<AppState>>,
+    keyboard_input: Res<Input<KeyCode>>,
     mut interaction_query: Query<
         (&Interaction, &mut UiColor),
         (Changed<Interaction>, With<Button>),
     >,
 ) {
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        state.set(AppState::InGame).unwrap();
+        return;
+    }
+
     for (interaction, mut color) in &mut interaction_query {
         match *interaction {
             Interaction::Clicked => {
@@ -144,10 +207,26 @@ fn handle_turn_transition_screen_interaction(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn setup_game_over_screen(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
-    players_query: Query<(&PlayerName, &TokensOwner, &CamelsHandOwner)>,
+    localization: Res<Localization>,
+    game_state: Res<GameState>,
+    game_config: Res<GameConfig>,
+    deck_definition: Res<DeckDefinition>,
+    move_history: Res<MoveHistory>,
+    puzzle_state: Option<Res<PuzzleState>>,
+    daily_challenge: Option<Res<DailyChallenge>>,
+    tutorial_state: Option<Res<TutorialState>>,
+    players_query: Query<(
+        Entity,
+        &PlayerName,
+        &TokensOwner,
+        &CamelsHandOwner,
+        &GoodsHandOwner,
+        Option<&Forfeited>,
+    )>,
 ) {
     let root_entity = commands
         .spawn_bundle(NodeBundle {
@@ -161,10 +240,11 @@ fn setup_game_over_screen(
             color: Color::CRIMSON.into(),
             ..default()
         })
+        .insert(GameOverScreen)
         .with_children(|parent| {
             parent.spawn_bundle(
                 TextBundle::from_section(
-                    "Game Over".to_string(),
+                    localization.get("game_over.title"),
                     TextStyle {
                         font: asset_server.load("fonts/FiraSans-Bold.ttf"),
                         font_size: 40.0,
@@ -184,45 +264,113 @@ fn setup_game_over_screen(
     let players: Vec<_> = players_query.iter().collect();
 
     let first_player = players[0];
-    let first_player_num_camels = first_player.2 .0;
+    let first_player_num_camels = first_player.3 .0;
     let mut first_player_stats = PlayerStats {
-        name: first_player.0 .0.to_string(),
-        final_score: get_tokens_score(&first_player.1 .0),
+        name: first_player.1 .0.to_string(),
+        final_score: get_tokens_score(&first_player.2 .0),
         camel_bonus_awarded: false,
     };
 
     let second_player = players[1];
-    let second_player_num_camels = second_player.2 .0;
+    let second_player_num_camels = second_player.3 .0;
 
     let mut second_player_stats = PlayerStats {
-        name: second_player.0 .0.to_string(),
-        final_score: get_tokens_score(&second_player.1 .0),
+        name: second_player.1 .0.to_string(),
+        final_score: get_tokens_score(&second_player.2 .0),
         camel_bonus_awarded: false,
     };
 
+    // Worked out before the camel bonus is added to the final scores
+    let rupee_totals_by_turn = get_rupee_totals_by_turn(
+        &move_history,
+        &[
+            (first_player.0, first_player_stats.final_score),
+            (second_player.0, second_player_stats.final_score),
+        ],
+    );
+
     match first_player_num_camels.cmp(&second_player_num_camels) {
         Ordering::Greater => {
             first_player_stats.camel_bonus_awarded = true;
-            first_player_stats.final_score += 5;
+            first_player_stats.final_score += game_config.camel_bonus;
         }
         Ordering::Less => {
             second_player_stats.camel_bonus_awarded = true;
-            second_player_stats.final_score += 5;
+            second_player_stats.final_score += game_config.camel_bonus;
         }
         Ordering::Equal => {}
     }
 
-    for stats in &[first_player_stats.clone(), second_player_stats.clone()] {
-        let camel_bonus_text = if stats.camel_bonus_awarded {
-            " (Camel bonus awarded)"
+    // Each player's score, with what it was made of beneath it, side by side. FlexEnd lines the columns up along
+    // their tops, since the UI's y axis points up.
+    let breakdowns_entity = commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Row,
+                align_items: AlignItems::FlexEnd,
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .id();
+
+    for ((stats, player), color) in [
+        (first_player_stats.clone(), first_player),
+        (second_player_stats.clone(), second_player),
+    ]
+    .into_iter()
+    .zip(PLAYER_CHART_COLORS)
+    {
+        let (_, _, tokens_owner, camels_hand_owner, goods_hand_owner, _) = player;
+        let score_key = if stats.camel_bonus_awarded {
+            "game_over.score_with_camel_bonus"
         } else {
-            ""
+            "game_over.score"
         };
 
-        let txt = commands
+        let score_breakdown = ScoreBreakdown {
+            heading: localization.format(
+                score_key,
+                &[("name", &stats.name), ("score", &stats.final_score)],
+            ),
+            tokens: &tokens_owner.0,
+            goods_hand: &goods_hand_owner.0,
+            num_camels: camels_hand_owner.0,
+            camel_bonus: if stats.camel_bonus_awarded {
+                game_config.camel_bonus
+            } else {
+                0
+            },
+        };
+
+        let score_breakdown_entity = spawn_score_breakdown(
+            &mut commands,
+            &asset_server,
+            &localization,
+            &deck_definition,
+            &score_breakdown,
+            color,
+        );
+
+        commands
+            .entity(breakdowns_entity)
+            .add_child(score_breakdown_entity);
+    }
+
+    children.push(breakdowns_entity);
+
+    // A player who forfeits loses, whatever the score
+    let forfeiting_player = players
+        .iter()
+        .find(|(_, _, _, _, _, forfeited)| forfeited.is_some())
+        .map(|(_, name, _, _, _, _)| name.0.clone());
+
+    if let Some(forfeiting_player) = &forfeiting_player {
+        let forfeit_text = commands
             .spawn_bundle(
                 TextBundle::from_section(
-                    format!("{}: {}{}", stats.name, stats.final_score, camel_bonus_text),
+                    localization.format("game_over.forfeit", &[("name", forfeiting_player)]),
                     TextStyle {
                         font: asset_server.load("fonts/FiraSans-Bold.ttf"),
                         font_size: 40.0,
@@ -236,21 +384,33 @@ fn setup_game_over_screen(
             )
             .id();
 
-        children.push(txt);
+        children.push(forfeit_text);
     }
 
-    let winning_player = match first_player_stats
-        .final_score
-        .cmp(&second_player_stats.final_score)
-    {
-        Ordering::Greater => Some(first_player_stats),
-        Ordering::Less => Some(second_player_stats),
-        Ordering::Equal => None,
+    let winning_player = match (first_player.5, second_player.5) {
+        (Some(_), _) => Some(second_player_stats),
+        (_, Some(_)) => Some(first_player_stats),
+        _ => match first_player_stats
+            .final_score
+            .cmp(&second_player_stats.final_score)
+        {
+            Ordering::Greater => Some(first_player_stats),
+            Ordering::Less => Some(second_player_stats),
+            Ordering::Equal => None,
+        },
     };
 
-    let winning_player_str = match winning_player {
-        Some(stats) => format!("{} wins!", stats.name),
-        None => "It's a tie!".to_string(),
+    let puzzle_outcome = puzzle_state.and_then(|puzzle_state| match forfeiting_player {
+        Some(_) => Some(PuzzleOutcome::Failed),
+        None => puzzle_state.outcome,
+    });
+
+    let winning_player_str = match (puzzle_outcome, winning_player) {
+        _ if tutorial_state.is_some() => localization.get("game_over.tutorial_complete"),
+        (Some(PuzzleOutcome::Solved), _) => localization.get("game_over.puzzle_solved"),
+        (Some(PuzzleOutcome::Failed), _) => localization.get("game_over.puzzle_failed"),
+        (None, Some(stats)) => localization.format("game_over.wins", &[("name", &stats.name)]),
+        (None, None) => localization.get("game_over.tie"),
     };
 
     let winner_text = commands
@@ -272,16 +432,190 @@ fn setup_game_over_screen(
 
     children.push(winner_text);
 
+    let rupee_chart_entity = spawn_rupee_chart(
+        &mut commands,
+        &asset_server,
+        &localization,
+        &[first_player.1 .0.clone(), second_player.1 .0.clone()],
+        &rupee_totals_by_turn,
+    );
+
+    children.push(rupee_chart_entity);
+
+    if let Some(result_text) = daily_challenge.and_then(|d| d.get_result_text(&localization)) {
+        let daily_challenge_text = commands
+            .spawn_bundle(
+                TextBundle::from_section(
+                    result_text,
+                    TextStyle {
+                        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                        font_size: 30.0,
+                        color: Color::rgb(0.9, 0.9, 0.9),
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::all(Val::Px(10.0)),
+                    ..default()
+                }),
+            )
+            .id();
+
+        children.push(daily_challenge_text);
+    }
+
+    if tutorial_state.is_some() {
+        for line in get_end_of_tutorial_text(&game_config, &localization) {
+            let tutorial_text = commands
+                .spawn_bundle(
+                    TextBundle::from_section(
+                        line,
+                        TextStyle {
+                            font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                            font_size: 30.0,
+                            color: Color::rgb(0.9, 0.9, 0.9),
+                        },
+                    )
+                    .with_style(Style {
+                        margin: UiRect::all(Val::Px(10.0)),
+                        ..default()
+                    }),
+                )
+                .id();
+
+            children.push(tutorial_text);
+        }
+    }
+
+    let game_over_buttons = if can_rematch(&game_state) {
+        vec![GameOverButton::Rematch, GameOverButton::NewGame]
+    } else {
+        vec![GameOverButton::NewGame]
+    };
+
+    // Side by side, to leave room for the score breakdown
+    let buttons_entity = commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Row,
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .id();
+
+    for game_over_button in game_over_buttons {
+        let button_entity = commands
+            .spawn_bundle(ButtonBundle {
+                style: Style {
+                    size: Size::new(Val::Px(250.0), Val::Px(65.0)),
+                    margin: UiRect::all(Val::Px(10.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                color: NORMAL_BUTTON.into(),
+                ..default()
+            })
+            .insert(game_over_button)
+            .with_children(|parent| {
+                parent.spawn_bundle(TextBundle::from_section(
+                    localization.get(game_over_button.get_text_key()),
+                    TextStyle {
+                        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                        font_size: 40.0,
+                        color: Color::rgb(0.9, 0.9, 0.9),
+                    },
+                ));
+            })
+            .id();
+
+        commands.entity(buttons_entity).add_child(button_entity);
+    }
+
+    children.push(buttons_entity);
+
     commands.entity(root_entity).push_children(&children);
 }
 
-fn get_tokens_score(tokens: &Tokens) -> usize {
+fn press_game_over_button(
+    game_over_button: GameOverButton,
+    state: &mut State<AppState>,
+    game_state: &mut GameState,
+) {
+    match game_over_button {
+        // The last game is torn down as the next one starts - see teardown_game
+        GameOverButton::Rematch => {
+            game_state.is_second_player_starting = !game_state.is_second_player_starting;
+            state.set(AppState::InitGame).unwrap();
+        }
+        GameOverButton::NewGame => {
+            state.set(AppState::MainMenu).unwrap();
+        }
+    }
+}
+
+// Enter plays a rematch where there can be one, otherwise it starts a new game
+fn handle_game_over_screen_interaction(
+    mut state: ResMut<State<AppState>>,
+    mut game_state: ResMut<GameState>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut interaction_query: Query<
+        (&Interaction, &mut UiColor, &GameOverButton),
+        (Changed<Interaction>, With<Button>),
+    >,
+) {
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        let game_over_button = if can_rematch(&game_state) {
+            GameOverButton::Rematch
+        } else {
+            GameOverButton::NewGame
+        };
+
+        press_game_over_button(game_over_button, &mut state, &mut game_state);
+        return;
+    }
+
+    for (interaction, mut color, game_over_button) in &mut interaction_query {
+        match *interaction {
+            Interaction::Clicked => {
+                *color = PRESSED_BUTTON.into();
+                press_game_over_button(*game_over_button, &mut state, &mut game_state);
+                return;
+            }
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
+            }
+        }
+    }
+}
+
+pub fn get_tokens_score(tokens: &Tokens) -> usize {
     let all_goods_tokens_values = tokens.goods.iter().flat_map(|(_, values)| values);
     let all_bonus_tokens_values = tokens.bonus.iter().flat_map(|(_, values)| values);
 
     all_goods_tokens_values.chain(all_bonus_tokens_values).sum()
 }
 
+// Rupees from tokens, plus the camel bonus if the player has more camels than their opponent
+pub fn get_final_score(
+    tokens: &Tokens,
+    num_camels: usize,
+    opponent_num_camels: usize,
+    game_config: &GameConfig,
+) -> usize {
+    let camel_bonus = if num_camels > opponent_num_camels {
+        game_config.camel_bonus
+    } else {
+        0
+    };
+
+    get_tokens_score(tokens) + camel_bonus
+}
+
 #[derive(Clone)]
 struct PlayerStats {
     name: String,
@@ -289,31 +623,206 @@ struct PlayerStats {
     camel_bonus_awarded: bool,
 }
 
-fn setup_game(mut commands: Commands, game_state: Res<GameState>) {
-    let mut deck = Deck::default();
-    let market = Market::new(&mut deck);
-    let tokens = Tokens::create_game_tokens();
+fn load_game_config(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    game_state: Res<GameState>,
+) {
+    commands.remove_resource::<PuzzleDefinition>();
+    commands.remove_resource::<PuzzleState>();
+    commands.remove_resource::<DailyChallenge>();
+    commands.remove_resource::<TutorialState>();
+
+    // The tutorial's position is built in, and set up like a puzzle's by the official rules
+    if game_state.is_playing_tutorial {
+        let game_config = GameConfig::default();
+        commands.insert_resource(DeckDefinitionHandle(
+            asset_server.load(&game_config.deck_definition_path),
+        ));
+        commands.insert_resource(create_tutorial_puzzle());
+        commands.insert_resource(game_config);
+        return;
+    }
+
+    // Puzzles are played by the official rules. The deck definition is loaded once the puzzle has, as the puzzle names it.
+    if game_state.is_playing_puzzle {
+        commands.insert_resource(PuzzleDefinitionHandle(
+            asset_server.load(PUZZLES[game_state.selected_puzzle]),
+        ));
+        commands.insert_resource(GameConfig::default());
+        return;
+    }
 
-    let player_one_cards = deck.get_cards(5);
-    let player_two_cards = deck.get_cards(5);
+    // The daily challenge is played by the official rules, so that everyone's game on the same day starts from the same deal
+    let game_config = if game_state.is_playing_daily_challenge {
+        commands.insert_resource(DailyChallenge::new(ChallengeDay::today()));
+        GameConfig::default()
+    } else {
+        game_state.rules_preset.get_game_config()
+    };
 
-    let (player_one_num_camels, player_one_goods_hand) = partition_hand(player_one_cards);
-    let (player_two_num_camels, player_two_goods_hand) = partition_hand(player_two_cards);
+    commands.insert_resource(DeckDefinitionHandle(
+        asset_server.load(&game_config.deck_definition_path),
+    ));
+    commands.insert_resource(game_config);
+}
 
-    commands
+// Waits for the deck definition to load, returning to the main menu with the error if it cannot be used.
+// Deals a new game, or sets up the position of the puzzle being played.
+#[allow(clippy::too_many_arguments)]
+fn setup_game(
+    mut commands: Commands,
+    mut state: ResMut<State<AppState>>,
+    mut game_state: ResMut<GameState>,
+    game_config: Res<GameConfig>,
+    asset_server: Res<AssetServer>,
+    deck_definitions: Res<Assets<DeckDefinition>>,
+    deck_definition_handle: Option<Res<DeckDefinitionHandle>>,
+    puzzle_definition: Option<Res<PuzzleDefinition>>,
+    daily_challenge: Option<Res<DailyChallenge>>,
+    settings: Res<Settings>,
+    localization: Res<Localization>,
+) {
+    let deck_definition_handle = match deck_definition_handle {
+        Some(handle) => handle,
+        None => return,
+    };
+
+    let deck_definition = match asset_server.get_load_state(&deck_definition_handle.0) {
+        LoadState::Loaded => deck_definitions.get(&deck_definition_handle.0).unwrap(),
+        LoadState::Failed => {
+            commands.remove_resource::<DeckDefinitionHandle>();
+            game_state.setup_error = Some(localization.format(
+                "menu.could_not_load",
+                &[("path", &game_config.deck_definition_path)],
+            ));
+            state.set(AppState::MainMenu).unwrap();
+            return;
+        }
+        _ => return,
+    };
+
+    commands.remove_resource::<DeckDefinitionHandle>();
+
+    if let Err(err) = deck_definition.validate_for_config(&game_config) {
+        error!("{}: {}", game_config.deck_definition_path, err);
+        game_state.setup_error = Some(format!(
+            "{}: {}",
+            game_config.deck_definition_path,
+            err.localize(&localization)
+        ));
+        state.set(AppState::MainMenu).unwrap();
+        return;
+    }
+
+    let (deck, market, discard_pile, tokens, player_one, player_two) = match puzzle_definition {
+        Some(puzzle_definition) => {
+            let position = match puzzle_definition.create_position(deck_definition, &game_config) {
+                Ok(position) => position,
+                Err(err) => {
+                    error!("{}: {}", puzzle_definition.name, err);
+                    game_state.setup_error = Some(format!(
+                        "{}: {}",
+                        puzzle_definition.name,
+                        err.localize(&localization)
+                    ));
+                    state.set(AppState::MainMenu).unwrap();
+                    return;
+                }
+            };
+
+            if game_state.is_playing_tutorial {
+                match TutorialState::new(deck_definition) {
+                    Ok(tutorial_state) => commands.insert_resource(tutorial_state),
+                    Err(err) => {
+                        error!("{}: {}", puzzle_definition.name, err);
+                        game_state.setup_error = Some(format!(
+                            "{}: {}",
+                            puzzle_definition.name,
+                            err.localize(&localization)
+                        ));
+                        state.set(AppState::MainMenu).unwrap();
+                        return;
+                    }
+                }
+            } else {
+                commands.insert_resource(PuzzleState::new(
+                    &puzzle_definition,
+                    get_tokens_score(&position.player.tokens),
+                ));
+            }
+
+            (
+                position.deck,
+                position.market,
+                position.discard_pile,
+                position.tokens,
+                (
+                    position.player.goods,
+                    position.player.camels,
+                    position.player.tokens,
+                ),
+                (
+                    position.opponent.goods,
+                    position.opponent.camels,
+                    position.opponent.tokens,
+                ),
+            )
+        }
+        None => {
+            let mut rng = match daily_challenge {
+                Some(daily_challenge) => daily_challenge.day.create_rng(),
+                None => StdRng::from_entropy(),
+            };
+
+            let mut deck = Deck::new(deck_definition, &mut rng);
+            let market = Market::new(&mut deck, &game_config);
+            let tokens = Tokens::create_game_tokens(deck_definition, &mut rng);
+
+            let player_one_cards = deck.get_cards(game_config.num_starting_hand_cards);
+            let player_two_cards = deck.get_cards(game_config.num_starting_hand_cards);
+
+            let (player_one_num_camels, player_one_goods_hand) = partition_hand(player_one_cards);
+            let (player_two_num_camels, player_two_goods_hand) = partition_hand(player_two_cards);
+
+            (
+                deck,
+                market,
+                DiscardPile::default(),
+                tokens,
+                (
+                    player_one_goods_hand,
+                    player_one_num_camels,
+                    Tokens::create_empty(deck_definition.goods.len()),
+                ),
+                (
+                    player_two_goods_hand,
+                    player_two_num_camels,
+                    Tokens::create_empty(deck_definition.goods.len()),
+                ),
+            )
+        }
+    };
+
+    let (player_one_goods_hand, player_one_num_camels, player_one_tokens) = player_one;
+    let (player_two_goods_hand, player_two_num_camels, player_two_tokens) = player_two;
+
+    let first_player_entity = commands
         .spawn_bundle(PlayerBundle::new(
-            "Player 1".to_string(),
+            settings.get_player_name(0, &localization),
             player_one_goods_hand,
             player_one_num_camels,
+            player_one_tokens,
         ))
-        .insert(ActivePlayer)
-        .insert(HumanPlayer);
+        .insert(HumanPlayer)
+        .id();
 
     let second_player_entity = commands
         .spawn_bundle(PlayerBundle::new(
-            "Player 2".to_string(),
+            settings.get_player_name(1, &localization),
             player_two_goods_hand,
             player_two_num_camels,
+            player_two_tokens,
         ))
         .id();
 
@@ -323,10 +832,71 @@ fn setup_game(mut commands: Commands, game_state: Res<GameState>) {
         commands.entity(second_player_entity).insert(HumanPlayer);
     }
 
+    let starting_player_entity = if game_state.is_second_player_starting {
+        second_player_entity
+    } else {
+        first_player_entity
+    };
+    commands.entity(starting_player_entity).insert(ActivePlayer);
+
+    commands.insert_resource(deck_definition.clone());
     commands.insert_resource(deck);
     commands.insert_resource(market);
     commands.insert_resource(tokens);
-    commands.init_resource::<DiscardPile>();
+    commands.insert_resource(discard_pile);
+}
+
+// The player who gave up the game from the pause menu
+#[derive(Component)]
+pub struct Forfeited;
+
+fn handle_forfeit(
+    mut commands: Commands,
+    mut ev_forfeit: EventReader<ForfeitEvent>,
+    mut state: ResMut<State<AppState>>,
+    mut game_state: ResMut<GameState>,
+    active_player_query: Query<Entity, With<ActivePlayer>>,
+) {
+    for _ev in ev_forfeit.iter() {
+        if let Ok(active_player) = active_player_query.get_single() {
+            commands.entity(active_player).insert(Forfeited);
+        }
+
+        game_state.is_game_over = true;
+        // Leaves the pause menu and the game underneath it
+        state.replace(AppState::GameOver).unwrap();
+    }
+}
+
+// Clears away the last game, so that the next one is set up from scratch rather than from its leftover resources.
+// Runs on returning to the main menu and as each game starts. The other plugins despawn their own entities at the
+// same time.
+fn teardown_game(
+    mut commands: Commands,
+    mut game_state: ResMut<GameState>,
+    mut turn_state: ResMut<State<TurnState>>,
+    mut tween_state: ResMut<TweenState>,
+    mut selected_card_state: ResMut<SelectedCardState>,
+    mut move_validity: ResMut<MoveValidity>,
+    players_query: Query<Entity, With<Player>>,
+) {
+    for player in players_query.iter() {
+        commands.entity(player).despawn_recursive();
+    }
+
+    commands.remove_resource::<Deck>();
+    commands.remove_resource::<Market>();
+    commands.remove_resource::<Tokens>();
+    commands.remove_resource::<DiscardPile>();
+
+    *tween_state = TweenState::default();
+    *selected_card_state = SelectedCardState::default();
+    *move_validity = MoveValidity::default();
+    game_state.is_game_over = false;
+
+    if *turn_state.current() != TurnState::None {
+        turn_state.set(TurnState::None).unwrap();
+    }
 }
 
 #[derive(Component)]
@@ -343,19 +913,34 @@ fn run_if_ai_mode(game_state: Res<GameState>) -> bool {
     game_state.is_playing_ai
 }
 
+#[allow(clippy::too_many_arguments)]
 fn setup_game_screen(
     In(should_setup): In<bool>,
     mut commands: Commands,
     asset_server: Res<AssetServer>,
-    deck: Res<Deck>,
-    market: Res<Market>,
-    discard_pile: Res<DiscardPile>,
-    active_player_query: Query<(&GoodsHandOwner, &CamelsHandOwner), With<ActivePlayer>>,
+    layout: Res<Layout>,
+    game_config: Res<GameConfig>,
+    deck_definition: Option<Res<DeckDefinition>>,
+    deck: Option<Res<Deck>>,
+    market: Option<Res<Market>>,
+    discard_pile: Option<Res<DiscardPile>>,
+    game_state: Res<GameState>,
+    active_player_query: Query<
+        ((&GoodsHandOwner, &CamelsHandOwner), Option<&HumanPlayer>),
+        With<ActivePlayer>,
+    >,
     inactive_player_query: Query<(&GoodsHandOwner, &CamelsHandOwner), Without<ActivePlayer>>,
 ) {
-    if !should_setup {
-        return;
-    }
+    // The resources are missing if InitGame returned to the main menu because the game could not be set up
+    let (deck_definition, deck, market, discard_pile) =
+        match (deck_definition, deck, market, discard_pile) {
+            (Some(deck_definition), Some(deck), Some(market), Some(discard_pile))
+                if should_setup =>
+            {
+                (deck_definition, deck, market, discard_pile)
+            }
+            _ => return,
+        };
 
     let game_root_entity = commands
         .spawn_bundle(SpatialBundle::default())
@@ -368,7 +953,7 @@ fn setup_game_screen(
             .spawn_bundle(SpriteBundle {
                 texture: asset_server.load("textures/card/back.png"),
                 transform: Transform::default()
-                    .with_translation(DECK_START_POS + Vec3::new(i as f32, i as f32, i as f32)),
+                    .with_translation(layout.get_deck_card_translation(i)),
                 ..default()
             })
             .insert(Card(*card_type))
@@ -382,8 +967,10 @@ fn setup_game_screen(
     for (idx, market_card) in market.cards.iter().enumerate() {
         let market_entity = commands
             .spawn_bundle(SpriteBundle {
-                texture: asset_server.load(&market_card.get_card_texture()),
-                transform: Transform::default().with_translation(get_market_card_translation(idx)),
+                texture: asset_server.load(&market_card.get_card_texture(&deck_definition.goods)),
+                transform: Transform::default().with_translation(
+                    layout.get_market_card_translation(idx, game_config.market_size),
+                ),
                 ..default()
             })
             .insert(Card(*market_card))
@@ -397,10 +984,12 @@ fn setup_game_screen(
     if let Some(card_type) = discard_pile.cards.last() {
         let discard_pile_entity = commands
             .spawn_bundle(SpriteBundle {
-                texture: asset_server.load(&card_type.get_card_texture()),
-                transform: Transform::default().with_translation(DISCARD_PILE_POS),
+                texture: asset_server.load(&card_type.get_card_texture(&deck_definition.goods)),
+                transform: Transform::default()
+                    .with_translation(layout.get_discard_pile_translation()),
                 ..default()
             })
+            .insert(DiscardPileCard)
             .id();
 
         commands
@@ -408,86 +997,200 @@ fn setup_game_screen(
             .add_child(discard_pile_entity);
     }
 
-    let (active_player_goods_hand, active_player_camels_hand) = active_player_query.single();
+    let ((active_player_goods_hand, active_player_camels_hand), human_player) =
+        active_player_query.single();
+    let (inactive_player_goods_hand, inactive_player_camels_hand) = inactive_player_query.single();
 
-    // Render active player's goods hand
-    for (idx, good) in active_player_goods_hand.0.iter().enumerate() {
-        let active_player_goods_hand_entity = commands
-            .spawn_bundle(SpriteBundle {
-                texture: asset_server.load(&good.get_card_texture()),
-                transform: Transform::default()
-                    .with_translation(get_active_player_goods_card_translation(idx)),
-                ..default()
-            })
-            .insert(Card(CardType::Good(*good)))
-            .insert(ActivePlayerGoodsCard(idx))
-            .id();
+    // The computer may start a rematch, in which case its face down hand at the top is the active one
+    let is_active_player_at_bottom = is_bottom_player(&game_state, true, human_player.is_some());
+    let (bottom_goods_hand, bottom_camels_hand, top_goods_hand, top_camels_hand) =
+        if is_active_player_at_bottom {
+            (
+                active_player_goods_hand,
+                active_player_camels_hand,
+                inactive_player_goods_hand,
+                inactive_player_camels_hand,
+            )
+        } else {
+            (
+                inactive_player_goods_hand,
+                inactive_player_camels_hand,
+                active_player_goods_hand,
+                active_player_camels_hand,
+            )
+        };
 
+    // Render bottom player's goods hand
+    for (idx, good) in bottom_goods_hand.0.iter().enumerate() {
+        let mut bottom_goods_hand_entity = commands.spawn_bundle(SpriteBundle {
+            texture: asset_server.load(&deck_definition.goods[*good].texture),
+            transform: Transform::default()
+                .with_translation(layout.get_active_player_goods_card_translation(idx)),
+            ..default()
+        });
+        bottom_goods_hand_entity.insert(Card(CardType::Good(*good)));
+
+        if is_active_player_at_bottom {
+            bottom_goods_hand_entity.insert(ActivePlayerGoodsCard(idx));
+        } else {
+            bottom_goods_hand_entity.insert(InactivePlayerGoodsCard(idx));
+        }
+
+        let bottom_goods_hand_entity = bottom_goods_hand_entity.id();
         commands
             .entity(game_root_entity)
-            .add_child(active_player_goods_hand_entity);
+            .add_child(bottom_goods_hand_entity);
     }
 
-    // Render active player's camel hand
-    for idx in 0..active_player_camels_hand.0 {
-        let active_player_camels_hand_entity = commands
-            .spawn_bundle(SpriteBundle {
-                texture: asset_server.load("textures/card/camel.png"),
-                transform: Transform::default()
-                    .with_translation(get_active_player_camel_card_translation(idx)),
-                ..default()
-            })
-            .insert(Card(CardType::Camel))
-            .insert(ActivePlayerCamelCard(idx))
-            .id();
+    // Render bottom player's camel hand
+    for idx in 0..bottom_camels_hand.0 {
+        let mut bottom_camels_hand_entity = commands.spawn_bundle(SpriteBundle {
+            texture: asset_server.load("textures/card/camel.png"),
+            transform: Transform::default()
+                .with_translation(layout.get_active_player_camel_card_translation(idx)),
+            ..default()
+        });
+        bottom_camels_hand_entity.insert(Card(CardType::Camel));
+
+        if is_active_player_at_bottom {
+            bottom_camels_hand_entity.insert(ActivePlayerCamelCard(idx));
+        } else {
+            bottom_camels_hand_entity.insert(InactivePlayerCamelCard(idx));
+        }
 
+        let bottom_camels_hand_entity = bottom_camels_hand_entity.id();
         commands
             .entity(game_root_entity)
-            .add_child(active_player_camels_hand_entity);
+            .add_child(bottom_camels_hand_entity);
     }
 
-    let (inactive_player_goods_hand, inactive_player_camels_hand) = inactive_player_query.single();
+    for (idx, good) in top_goods_hand.0.iter().enumerate() {
+        let mut top_goods_hand_entity = commands.spawn_bundle(SpriteBundle {
+            texture: asset_server.load("textures/card/back.png"),
+            transform: Transform::default()
+                .with_translation(layout.get_ai_player_goods_card_translation(idx))
+                .with_rotation(Quat::from_rotation_z((180.0_f32).to_radians())),
+            ..default()
+        });
+        top_goods_hand_entity.insert(Card(CardType::Good(*good)));
 
-    for (idx, good) in inactive_player_goods_hand.0.iter().enumerate() {
-        let inactive_player_goods_hand_entity = commands
-            .spawn_bundle(SpriteBundle {
-                texture: asset_server.load("textures/card/back.png"),
-                transform: Transform::default()
-                    .with_translation(
-                        INACTIVE_PLAYER_GOODS_HAND_START_POS
-                            + Vec3::X * idx as f32 * (CARD_DIMENSION.x + CARD_PADDING),
-                    )
-                    .with_rotation(Quat::from_rotation_z((180.0_f32).to_radians())),
-                ..default()
-            })
-            .insert(Card(CardType::Good(*good)))
-            .insert(InactivePlayerGoodsCard(idx))
-            .id();
+        if is_active_player_at_bottom {
+            top_goods_hand_entity.insert(InactivePlayerGoodsCard(idx));
+        } else {
+            top_goods_hand_entity.insert(ActivePlayerGoodsCard(idx));
+        }
 
+        let top_goods_hand_entity = top_goods_hand_entity.id();
         commands
             .entity(game_root_entity)
-            .add_child(inactive_player_goods_hand_entity);
+            .add_child(top_goods_hand_entity);
     }
 
     // Create entitities for each of opponent's camel cards on top of each other - a player need not reveal how many camels they have,
     // but an entity for each card is important for the AI player
-    for _ in 0..inactive_player_camels_hand.0 {
-        let inactive_player_camels_hand_entity = commands
-            .spawn_bundle(SpriteBundle {
-                texture: asset_server.load("textures/card/camel.png"),
-                transform: Transform::default()
-                    .with_translation(get_opponent_camel_hand_translation())
-                    .with_rotation(Quat::from_rotation_z((180.0_f32).to_radians())),
+    for _ in 0..top_camels_hand.0 {
+        let mut top_camels_hand_entity = commands.spawn_bundle(SpriteBundle {
+            texture: asset_server.load("textures/card/camel.png"),
+            transform: Transform::default()
+                .with_translation(layout.get_opponent_camel_hand_translation())
+                .with_rotation(Quat::from_rotation_z((180.0_f32).to_radians())),
 
-                ..default()
-            })
-            .insert(Card(CardType::Camel))
-            .insert(InactivePlayerCamelCard(0))
-            .id();
+            ..default()
+        });
+        top_camels_hand_entity.insert(Card(CardType::Camel));
 
+        if is_active_player_at_bottom {
+            top_camels_hand_entity.insert(InactivePlayerCamelCard(0));
+        } else {
+            top_camels_hand_entity.insert(ActivePlayerCamelCard(0));
+        }
+
+        let top_camels_hand_entity = top_camels_hand_entity.id();
         commands
             .entity(game_root_entity)
-            .add_child(inactive_player_camels_hand_entity);
+            .add_child(top_camels_hand_entity);
+    }
+
+    // Only shown when chosen in the settings
+    let opponent_camel_count_text_entity = commands
+        .spawn_bundle(Text2dBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                    font_size: 40.0,
+                    color: Color::rgb(0.9, 0.9, 0.9),
+                },
+            )
+            .with_alignment(TextAlignment::CENTER),
+            transform: Transform::from_translation(get_opponent_camel_count_text_translation(
+                &layout,
+            )),
+            ..default()
+        })
+        .insert(OpponentCamelCountText)
+        .id();
+
+    commands
+        .entity(game_root_entity)
+        .add_child(opponent_camel_count_text_entity);
+}
+
+// The player whose hand is at the bottom of the screen: the human player against the computer, otherwise
+// the player whose turn it is
+pub fn is_bottom_player(
+    game_state: &GameState,
+    is_active_player: bool,
+    is_human_player: bool,
+) -> bool {
+    if game_state.is_playing_ai {
+        is_human_player
+    } else {
+        is_active_player
+    }
+}
+
+#[derive(Component)]
+struct OpponentCamelCountText;
+
+// To the left of the opponent's camels
+fn get_opponent_camel_count_text_translation(layout: &Layout) -> Vec3 {
+    layout.get_opponent_camel_hand_translation()
+        + Vec3::new(-CARD_DIMENSION.x / 2.0 - 40.0, 0.0, 1.0)
+}
+
+// Counts the camels of the player at the top of the screen
+fn update_opponent_camel_count_text(
+    game_state: Res<GameState>,
+    settings: Res<Settings>,
+    layout: Res<Layout>,
+    players_query: Query<(
+        &CamelsHandOwner,
+        Option<&ActivePlayer>,
+        Option<&HumanPlayer>,
+    )>,
+    mut text_query: Query<
+        (&mut Text, &mut Visibility, &mut Transform),
+        With<OpponentCamelCountText>,
+    >,
+) {
+    let opponent_camels = players_query
+        .iter()
+        .find(|(_, active_player, human_player)| {
+            !is_bottom_player(&game_state, active_player.is_some(), human_player.is_some())
+        })
+        .map(|(camels_hand_owner, _, _)| camels_hand_owner.0);
+
+    for (mut text, mut visibility, mut transform) in text_query.iter_mut() {
+        visibility.is_visible = settings.show_opponent_camel_count;
+
+        if layout.is_changed() {
+            transform.translation = get_opponent_camel_count_text_translation(&layout);
+        }
+
+        if let Some(num_camels) = opponent_camels {
+            text.sections[0].value = format!("x{}", num_camels);
+        }
     }
 }
 
@@ -522,13 +1225,18 @@ struct PlayerBundle {
 }
 
 impl PlayerBundle {
-    fn new(name: String, initial_goods_hand: Vec<GoodType>, initial_camels: usize) -> Self {
+    fn new(
+        name: String,
+        initial_goods_hand: Vec<GoodType>,
+        initial_camels: usize,
+        initial_tokens: Tokens,
+    ) -> Self {
         Self {
             player: Player {},
             name: PlayerName(name),
             goods_hand_owner: GoodsHandOwner(initial_goods_hand),
             camels_hand_owner: CamelsHandOwner(initial_camels),
-            tokens_owner: TokensOwner(Tokens::create_empty()),
+            tokens_owner: TokensOwner(initial_tokens),
         }
     }
 }
@@ -550,13 +1258,25 @@ pub struct GamePlugin;
 
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugin(CardSelectionPlugin)
+        // Replaced by the chosen preset's config when each game is set up
+        app.init_resource::<GameConfig>()
+            .add_plugin(CardSelectionPlugin)
+            .add_plugin(CardFocusPlugin)
             .add_plugin(MoveValidationPlugin)
             .add_plugin(MoveExecutionPlugin)
             .add_plugin(JaipurAiPlugin)
-            .add_system_set(SystemSet::on_enter(AppState::InitGame).with_system(setup_game))
+            .add_plugin(DeckDefinitionPlugin)
+            .add_plugin(PuzzlePlugin)
+            .add_plugin(DailyChallengePlugin)
+            .add_plugin(TutorialPlugin)
+            .add_plugin(TokenStacksPlugin)
+            .add_plugin(MoveHistoryPlugin)
+            .add_plugin(HandSortingPlugin)
+            .add_system_set(SystemSet::on_enter(AppState::InitGame).with_system(load_game_config))
             .add_system_set(
-                SystemSet::on_update(AppState::InitGame).with_system(handle_when_resources_ready),
+                SystemSet::on_update(AppState::InitGame)
+                    .with_system(setup_game)
+                    .with_system(handle_when_resources_ready),
             )
             .add_system_set(
                 SystemSet::on_exit(AppState::InitGame)
@@ -577,7 +1297,28 @@ impl Plugin for GamePlugin {
                     .with_system(pass_through.chain(setup_game_screen)),
             )
             .add_system_set(
-                SystemSet::on_enter(AppState::GameOver).with_system(setup_game_over_screen),
+                SystemSet::on_enter(AppState::GameOver)
+                    .with_system(setup_game_over_screen.after(Label::DailyChallengeResultWriter)),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::GameOver)
+                    .with_system(handle_game_over_screen_interaction),
+            )
+            .add_system(update_opponent_camel_count_text)
+            .add_system(handle_forfeit);
+
+        for state in [AppState::MainMenu, AppState::InitGame] {
+            app.add_system_set(
+                SystemSet::on_enter(state)
+                    .with_system(teardown_game)
+                    .with_system(despawn_entity_with_component::<GameRoot>)
+                    .with_system(despawn_entity_with_component::<TurnTransitionScreen>)
+                    .with_system(despawn_entity_with_component::<GameOverScreen>),
             );
+        }
+
+        // Verifies that no cards or tokens were lost or duplicated after every turn
+        #[cfg(debug_assertions)]
+        app.add_plugin(InvariantCheckPlugin);
     }
 }
@@ -1,17 +1,21 @@
+use std::ops::{Index, IndexMut};
+
 use bevy::prelude::Component;
-use enum_map::Enum;
+use serde::Deserialize;
+
+use super::deck_definition::GoodsDefinition;
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 pub enum CardType {
     Camel,
     Good(GoodType),
 }
 
 impl CardType {
-    pub fn get_card_texture(&self) -> String {
+    pub fn get_card_texture(&self, goods: &GoodsMap<GoodsDefinition>) -> String {
         match self {
             CardType::Camel => "textures/card/camel.png".to_string(),
-            CardType::Good(good) => good.get_card_texture(),
+            CardType::Good(good) => goods[*good].texture.clone(),
         }
     }
 
@@ -23,30 +27,56 @@ impl CardType {
     }
 }
 
-#[derive(Clone, Copy, Debug, Enum, Eq, PartialEq, Hash)]
-pub enum GoodType {
-    Diamond,
-    Gold,
-    Silver,
-    Cloth,
-    Spice,
-    Leather,
+// The index of a good in the game's DeckDefinition, which defines its name, texture and tokens
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct GoodType(pub usize);
+
+// A value for each good in the game, indexed by GoodType
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(transparent)]
+pub struct GoodsMap<T>(Vec<T>);
+
+impl<T> GoodsMap<T> {
+    pub fn new(values: Vec<T>) -> Self {
+        Self(values)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = GoodType> {
+        (0..self.0.len()).map(GoodType)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &T> {
+        self.0.iter()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (GoodType, &T)> {
+        self.0.iter().enumerate().map(|(i, v)| (GoodType(i), v))
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> GoodsMap<U> {
+        GoodsMap(self.0.iter().map(f).collect())
+    }
 }
 
-impl GoodType {
-    pub fn get_card_texture(&self) -> String {
-        match self {
-            GoodType::Diamond => "textures/card/diamond.png".to_string(),
-            GoodType::Gold => "textures/card/gold.png".to_string(),
-            GoodType::Silver => "textures/card/silver.png".to_string(),
-            GoodType::Cloth => "textures/card/cloth.png".to_string(),
-            GoodType::Spice => "textures/card/spice.png".to_string(),
-            GoodType::Leather => "textures/card/leather.png".to_string(),
-        }
+impl<T> Index<GoodType> for GoodsMap<T> {
+    type Output = T;
+
+    fn index(&self, good: GoodType) -> &T {
+        &self.0[good.0]
     }
+}
 
-    pub fn is_high_value(&self) -> bool {
-        matches!(self, GoodType::Diamond | GoodType::Gold | GoodType::Silver)
+impl<T> IndexMut<GoodType> for GoodsMap<T> {
+    fn index_mut(&mut self, good: GoodType) -> &mut T {
+        &mut self.0[good.0]
     }
 }
 
@@ -67,3 +97,7 @@ pub struct InactivePlayerGoodsCard(pub usize);
 
 #[derive(Component)]
 pub struct InactivePlayerCamelCard(pub usize);
+
+// The top of the discard pile, and the cards sold onto it
+#[derive(Component)]
+pub struct DiscardPileCard;
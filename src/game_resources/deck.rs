@@ -1,70 +1,36 @@
 use std::iter;
 
-use rand::{seq::SliceRandom, thread_rng};
+use rand::{seq::SliceRandom, Rng};
 
-use super::card::{CardType, GoodType};
-
-const NUM_CAMEL_CARDS: usize = 11;
-const NUM_DIAMOND_CARDS: usize = 6;
-const NUM_GOLD_CARDS: usize = 6;
-const NUM_SILVER_CARDS: usize = 6;
-const NUM_CLOTH_CARDS: usize = 8;
-const NUM_SPICE_CARDS: usize = 8;
-const NUM_LEATHER_CARDS: usize = 10;
+use super::{card::CardType, deck_definition::DeckDefinition};
 
 #[derive(Clone)]
 pub struct Deck {
     pub cards: Vec<CardType>,
 }
 
-impl Default for Deck {
-    fn default() -> Self {
-        let mut cards = vec![];
-
-        let mut camel_cards = iter::repeat(CardType::Camel)
-            .take(NUM_CAMEL_CARDS)
-            .collect::<Vec<_>>();
-
-        let mut diamond_cards = iter::repeat(CardType::Good(GoodType::Diamond))
-            .take(NUM_DIAMOND_CARDS)
-            .collect::<Vec<_>>();
-
-        let mut gold_cards = iter::repeat(CardType::Good(GoodType::Gold))
-            .take(NUM_GOLD_CARDS)
-            .collect::<Vec<_>>();
-
-        let mut silver_cards = iter::repeat(CardType::Good(GoodType::Silver))
-            .take(NUM_SILVER_CARDS)
-            .collect::<Vec<_>>();
+impl Deck {
+    pub fn new(deck_definition: &DeckDefinition, rng: &mut impl Rng) -> Self {
+        let mut cards = Deck::create_all_cards(deck_definition);
 
-        let mut cloth_cards = iter::repeat(CardType::Good(GoodType::Cloth))
-            .take(NUM_CLOTH_CARDS)
-            .collect::<Vec<_>>();
+        cards.shuffle(rng);
 
-        let mut spice_cards = iter::repeat(CardType::Good(GoodType::Spice))
-            .take(NUM_SPICE_CARDS)
-            .collect::<Vec<_>>();
+        Self { cards }
+    }
 
-        let mut leather_cards = iter::repeat(CardType::Good(GoodType::Leather))
-            .take(NUM_LEATHER_CARDS)
+    // Every card in the game, in no particular order
+    pub fn create_all_cards(deck_definition: &DeckDefinition) -> Vec<CardType> {
+        let mut cards = iter::repeat(CardType::Camel)
+            .take(deck_definition.num_camel_cards)
             .collect::<Vec<_>>();
 
-        cards.append(&mut camel_cards);
-        cards.append(&mut diamond_cards);
-        cards.append(&mut gold_cards);
-        cards.append(&mut silver_cards);
-        cards.append(&mut cloth_cards);
-        cards.append(&mut spice_cards);
-        cards.append(&mut leather_cards);
+        for (good, goods_definition) in deck_definition.goods.iter() {
+            cards.extend(iter::repeat(CardType::Good(good)).take(goods_definition.num_cards));
+        }
 
-        let mut rng = thread_rng();
-        cards.shuffle(&mut rng);
-
-        Self { cards }
+        cards
     }
-}
 
-impl Deck {
     pub fn get_cards(&mut self, num_cards: usize) -> Vec<CardType> {
         self.cards.drain(0..num_cards).collect()
     }
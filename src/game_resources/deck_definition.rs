@@ -0,0 +1,432 @@
+use std::{collections::HashMap, fmt};
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use enum_map::Enum;
+use itertools::Itertools;
+use serde::Deserialize;
+
+use super::{
+    card::{GoodType, GoodsMap},
+    game_config::GameConfig,
+    tokens::BonusType,
+};
+use crate::localization::Localization;
+
+// The goods, cards and tokens a game is played with, loaded from a `.deck.ron` file in assets/decks
+#[derive(Clone, Debug, Deserialize, TypeUuid)]
+#[uuid = "6f1c1f3e-2a8b-4c55-9d3a-0b7e4f6c2d91"]
+pub struct DeckDefinition {
+    pub num_camel_cards: usize,
+    // A GoodType is the index of its definition in this list
+    pub goods: GoodsMap<GoodsDefinition>,
+    // Each pool is shuffled at the start of the game
+    pub bonus_tokens: HashMap<BonusType, Vec<usize>>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct GoodsDefinition {
+    pub name: String,
+    pub texture: String,
+    // The red, green and blue of the good's tokens, each from 0 to 1
+    pub token_color: (f32, f32, f32),
+    pub num_cards: usize,
+    // Listed from the bottom of the stack to the top, so the most valuable tokens are sold first
+    pub tokens: Vec<usize>,
+    #[serde(default = "default_min_sale_size")]
+    pub min_sale_size: usize,
+}
+
+fn default_min_sale_size() -> usize {
+    1
+}
+
+impl GoodsDefinition {
+    // Goods that cannot be sold one at a time are the most valuable, like diamonds, gold and silver
+    pub fn is_high_value(&self) -> bool {
+        self.min_sale_size > 1
+    }
+
+    pub fn get_token_color(&self) -> Color {
+        let (red, green, blue) = self.token_color;
+        Color::rgb(red, green, blue)
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum DeckDefinitionError {
+    NoGoods,
+    DuplicateGoodName(String),
+    NoTokens(String),
+    TokensNotInAscendingOrder(String),
+    InvalidMinSaleSize(String),
+    MissingBonusTokens(BonusType),
+    NotEnoughCamels { required: usize, available: usize },
+    NotEnoughCards { required: usize, available: usize },
+    NotEnoughGoodsToEndGame { required: usize, available: usize },
+}
+
+impl fmt::Display for DeckDefinitionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DeckDefinitionError::NoGoods => write!(f, "At least one good must be defined"),
+            DeckDefinitionError::DuplicateGoodName(name) => {
+                write!(f, "{} is defined more than once", name)
+            }
+            DeckDefinitionError::NoTokens(name) => {
+                write!(f, "{} must have at least one token", name)
+            }
+            DeckDefinitionError::TokensNotInAscendingOrder(name) => write!(
+                f,
+                "{} tokens must be listed from lowest to highest value",
+                name
+            ),
+            DeckDefinitionError::InvalidMinSaleSize(name) => {
+                write!(f, "{} must have a minimum sale size of at least 1", name)
+            }
+            DeckDefinitionError::MissingBonusTokens(bonus_type) => {
+                write!(f, "No bonus token pool is defined for {:?}", bonus_type)
+            }
+            DeckDefinitionError::NotEnoughCamels {
+                required,
+                available,
+            } => write!(
+                f,
+                "The market starts with {} camels, but the deck only has {}",
+                required, available
+            ),
+            DeckDefinitionError::NotEnoughCards {
+                required,
+                available,
+            } => write!(
+                f,
+                "Setting up the market and hands needs {} cards, but the deck only has {}",
+                required, available
+            ),
+            DeckDefinitionError::NotEnoughGoodsToEndGame {
+                required,
+                available,
+            } => write!(
+                f,
+                "The game ends when {} goods token stacks are empty, but only {} goods are defined",
+                required, available
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DeckDefinitionError {}
+
+impl DeckDefinitionError {
+    // For the main menu. The Display text, in English, is for the log.
+    pub fn localize(&self, localization: &Localization) -> String {
+        match self {
+            DeckDefinitionError::NoGoods => localization.get("deck_error.no_goods"),
+            DeckDefinitionError::DuplicateGoodName(name) => localization.format(
+                "deck_error.duplicate_good_name",
+                &[("good", &localization.get_good_name(name))],
+            ),
+            DeckDefinitionError::NoTokens(name) => localization.format(
+                "deck_error.no_tokens",
+                &[("good", &localization.get_good_name(name))],
+            ),
+            DeckDefinitionError::TokensNotInAscendingOrder(name) => localization.format(
+                "deck_error.tokens_not_in_ascending_order",
+                &[("good", &localization.get_good_name(name))],
+            ),
+            DeckDefinitionError::InvalidMinSaleSize(name) => localization.format(
+                "deck_error.invalid_min_sale_size",
+                &[("good", &localization.get_good_name(name))],
+            ),
+            DeckDefinitionError::MissingBonusTokens(bonus_type) => localization.format(
+                "deck_error.missing_bonus_tokens",
+                &[("bonus", &bonus_type.localize(localization))],
+            ),
+            DeckDefinitionError::NotEnoughCamels {
+                required,
+                available,
+            } => localization.format(
+                "deck_error.not_enough_camels",
+                &[("required", required), ("available", available)],
+            ),
+            DeckDefinitionError::NotEnoughCards {
+                required,
+                available,
+            } => localization.format(
+                "deck_error.not_enough_cards",
+                &[("required", required), ("available", available)],
+            ),
+            DeckDefinitionError::NotEnoughGoodsToEndGame {
+                required,
+                available,
+            } => localization.format(
+                "deck_error.not_enough_goods_to_end_game",
+                &[("required", required), ("available", available)],
+            ),
+        }
+    }
+}
+
+impl DeckDefinition {
+    pub fn get_total_num_cards(&self) -> usize {
+        self.num_camel_cards
+            + self
+                .goods
+                .values()
+                .map(|goods_definition| goods_definition.num_cards)
+                .sum::<usize>()
+    }
+
+    pub fn find_good(&self, name: &str) -> Option<GoodType> {
+        self.goods
+            .iter()
+            .find(|(_, goods_definition)| goods_definition.name == name)
+            .map(|(good, _)| good)
+    }
+
+    // Checks the definition is complete and well formed, independent of the rules it is played with
+    pub fn validate(&self) -> Result<(), DeckDefinitionError> {
+        if self.goods.is_empty() {
+            return Err(DeckDefinitionError::NoGoods);
+        }
+
+        if let Some(goods_definition) = self.goods.values().duplicates_by(|g| &g.name).next() {
+            return Err(DeckDefinitionError::DuplicateGoodName(
+                goods_definition.name.clone(),
+            ));
+        }
+
+        for goods_definition in self.goods.values() {
+            let name = goods_definition.name.clone();
+
+            if goods_definition.tokens.is_empty() {
+                return Err(DeckDefinitionError::NoTokens(name));
+            }
+
+            if !goods_definition.tokens.windows(2).all(|w| w[0] <= w[1]) {
+                return Err(DeckDefinitionError::TokensNotInAscendingOrder(name));
+            }
+
+            if goods_definition.min_sale_size == 0 {
+                return Err(DeckDefinitionError::InvalidMinSaleSize(name));
+            }
+        }
+
+        for bonus_type in (0..BonusType::LENGTH).map(BonusType::from_usize) {
+            if !self.bonus_tokens.contains_key(&bonus_type) {
+                return Err(DeckDefinitionError::MissingBonusTokens(bonus_type));
+            }
+        }
+
+        Ok(())
+    }
+
+    // Checks that a game can be set up with the definition under the given rules
+    pub fn validate_for_config(&self, config: &GameConfig) -> Result<(), DeckDefinitionError> {
+        self.validate()?;
+
+        if self.num_camel_cards < config.num_starting_market_camels {
+            return Err(DeckDefinitionError::NotEnoughCamels {
+                required: config.num_starting_market_camels,
+                available: self.num_camel_cards,
+            });
+        }
+
+        let num_cards_required = config.market_size + 2 * config.num_starting_hand_cards;
+
+        if self.get_total_num_cards() < num_cards_required {
+            return Err(DeckDefinitionError::NotEnoughCards {
+                required: num_cards_required,
+                available: self.get_total_num_cards(),
+            });
+        }
+
+        if self.goods.len() < config.num_empty_token_stacks_to_end {
+            return Err(DeckDefinitionError::NotEnoughGoodsToEndGame {
+                required: config.num_empty_token_stacks_to_end,
+                available: self.goods.len(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct DeckDefinitionLoader;
+
+impl AssetLoader for DeckDefinitionLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let deck_definition = ron::de::from_bytes::<DeckDefinition>(bytes)?;
+            deck_definition.validate()?;
+            load_context.set_default_asset(LoadedAsset::new(deck_definition));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["deck.ron"]
+    }
+}
+
+// The definition being loaded for the game being set up
+pub struct DeckDefinitionHandle(pub Handle<DeckDefinition>);
+
+pub struct DeckDefinitionPlugin;
+
+impl Plugin for DeckDefinitionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<DeckDefinition>()
+            .init_asset_loader::<DeckDefinitionLoader>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{all_game_configs, official_deck_definition, CLOTH, GOLD};
+
+    fn load_shipped_deck_definition(path: &str) -> DeckDefinition {
+        let definition = match path {
+            "decks/official.deck.ron" => include_str!("../../assets/decks/official.deck.ron"),
+            "decks/custom.deck.ron" => include_str!("../../assets/decks/custom.deck.ron"),
+            "decks/camel_caravan.deck.ron" => {
+                include_str!("../../assets/decks/camel_caravan.deck.ron")
+            }
+            "decks/ivory_and_tea.deck.ron" => {
+                include_str!("../../assets/decks/ivory_and_tea.deck.ron")
+            }
+            _ => panic!("{} is not one of the shipped deck definitions", path),
+        };
+
+        ron::from_str(definition).unwrap()
+    }
+
+    fn validate_with(change: impl FnOnce(&mut DeckDefinition)) -> Result<(), DeckDefinitionError> {
+        let mut deck_definition = official_deck_definition();
+        change(&mut deck_definition);
+        deck_definition.validate_for_config(&GameConfig::default())
+    }
+
+    #[test]
+    fn shipped_deck_definitions_can_be_played_with_their_presets() {
+        for game_config in all_game_configs() {
+            let deck_definition = load_shipped_deck_definition(&game_config.deck_definition_path);
+
+            assert_eq!(
+                deck_definition.validate_for_config(&game_config),
+                Ok(()),
+                "{}",
+                game_config.deck_definition_path
+            );
+        }
+    }
+
+    #[test]
+    fn at_least_one_good_is_needed() {
+        assert_eq!(
+            validate_with(|deck_definition| deck_definition.goods = GoodsMap::new(vec![])),
+            Err(DeckDefinitionError::NoGoods)
+        );
+    }
+
+    #[test]
+    fn good_names_must_be_unique() {
+        assert_eq!(
+            validate_with(
+                |deck_definition| deck_definition.goods[GOLD].name = "Diamond".to_string()
+            ),
+            Err(DeckDefinitionError::DuplicateGoodName(
+                "Diamond".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn each_good_needs_a_token() {
+        assert_eq!(
+            validate_with(|deck_definition| deck_definition.goods[CLOTH].tokens.clear()),
+            Err(DeckDefinitionError::NoTokens("Cloth".to_string()))
+        );
+    }
+
+    #[test]
+    fn tokens_must_be_in_ascending_order() {
+        assert_eq!(
+            validate_with(|deck_definition| deck_definition.goods[CLOTH].tokens.reverse()),
+            Err(DeckDefinitionError::TokensNotInAscendingOrder(
+                "Cloth".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn min_sale_size_cannot_be_zero() {
+        assert_eq!(
+            validate_with(|deck_definition| deck_definition.goods[CLOTH].min_sale_size = 0),
+            Err(DeckDefinitionError::InvalidMinSaleSize("Cloth".to_string()))
+        );
+    }
+
+    #[test]
+    fn every_bonus_type_needs_tokens() {
+        assert_eq!(
+            validate_with(|deck_definition| {
+                deck_definition.bonus_tokens.remove(&BonusType::Four);
+            }),
+            Err(DeckDefinitionError::MissingBonusTokens(BonusType::Four))
+        );
+    }
+
+    #[test]
+    fn market_needs_its_starting_camels() {
+        assert_eq!(
+            validate_with(|deck_definition| deck_definition.num_camel_cards = 2),
+            Err(DeckDefinitionError::NotEnoughCamels {
+                required: 3,
+                available: 2
+            })
+        );
+    }
+
+    #[test]
+    fn deck_needs_enough_cards_to_deal() {
+        let game_config = GameConfig {
+            market_size: 50,
+            ..default()
+        };
+
+        assert_eq!(
+            official_deck_definition().validate_for_config(&game_config),
+            Err(DeckDefinitionError::NotEnoughCards {
+                required: 60,
+                available: 55
+            })
+        );
+    }
+
+    #[test]
+    fn game_needs_enough_goods_to_end() {
+        let game_config = GameConfig {
+            num_empty_token_stacks_to_end: 7,
+            ..default()
+        };
+
+        assert_eq!(
+            official_deck_definition().validate_for_config(&game_config),
+            Err(DeckDefinitionError::NotEnoughGoodsToEndGame {
+                required: 7,
+                available: 6
+            })
+        );
+    }
+}
@@ -0,0 +1,105 @@
+use crate::localization::Localization;
+
+// The rules of the game that vary between presets. Inserted when a game is set up, from the preset chosen in the main menu.
+#[derive(Clone, Debug)]
+pub struct GameConfig {
+    pub max_goods_in_hand: usize,
+    pub market_size: usize,
+    // Camels placed in the market before it is filled from the deck
+    pub num_starting_market_camels: usize,
+    pub num_starting_hand_cards: usize,
+    pub camel_bonus: usize,
+    // The game ends when this many goods token stacks are empty
+    pub num_empty_token_stacks_to_end: usize,
+    // The cards and tokens to play with - see DeckDefinition
+    pub deck_definition_path: String,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            max_goods_in_hand: 7,
+            market_size: 5,
+            num_starting_market_camels: 3,
+            num_starting_hand_cards: 5,
+            camel_bonus: 5,
+            num_empty_token_stacks_to_end: 3,
+            deck_definition_path: "decks/official.deck.ron".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub enum RulesPreset {
+    #[default]
+    Official,
+    BigHands,
+    CamelCaravan,
+    WideMarket,
+    ShortGame,
+    CustomDeck,
+    IvoryAndTea,
+}
+
+impl RulesPreset {
+    pub fn next(&self) -> Self {
+        match self {
+            RulesPreset::Official => RulesPreset::BigHands,
+            RulesPreset::BigHands => RulesPreset::CamelCaravan,
+            RulesPreset::CamelCaravan => RulesPreset::WideMarket,
+            RulesPreset::WideMarket => RulesPreset::ShortGame,
+            RulesPreset::ShortGame => RulesPreset::CustomDeck,
+            RulesPreset::CustomDeck => RulesPreset::IvoryAndTea,
+            RulesPreset::IvoryAndTea => RulesPreset::Official,
+        }
+    }
+
+    pub fn get_game_config(&self) -> GameConfig {
+        match self {
+            RulesPreset::Official => GameConfig::default(),
+            // More room in the hand for building up large sets
+            RulesPreset::BigHands => GameConfig {
+                max_goods_in_hand: 9,
+                num_starting_hand_cards: 7,
+                ..GameConfig::default()
+            },
+            // More camels in the deck, and the herd is worth fighting over
+            RulesPreset::CamelCaravan => GameConfig {
+                camel_bonus: 10,
+                deck_definition_path: "decks/camel_caravan.deck.ron".to_string(),
+                ..GameConfig::default()
+            },
+            RulesPreset::WideMarket => GameConfig {
+                market_size: 6,
+                ..GameConfig::default()
+            },
+            RulesPreset::ShortGame => GameConfig {
+                num_empty_token_stacks_to_end: 2,
+                ..GameConfig::default()
+            },
+            // Official rules, with cards and tokens from a file that can be edited for balance experiments and fan variants
+            RulesPreset::CustomDeck => GameConfig {
+                deck_definition_path: "decks/custom.deck.ron".to_string(),
+                ..GameConfig::default()
+            },
+            // Two extra goods to trade, so more token stacks have to run out
+            RulesPreset::IvoryAndTea => GameConfig {
+                num_empty_token_stacks_to_end: 4,
+                deck_definition_path: "decks/ivory_and_tea.deck.ron".to_string(),
+                ..GameConfig::default()
+            },
+        }
+    }
+
+    pub fn localize(&self, localization: &Localization) -> String {
+        localization.get(match self {
+            RulesPreset::Official => "rules.official",
+            RulesPreset::BigHands => "rules.big_hands",
+            RulesPreset::CamelCaravan => "rules.camel_caravan",
+            RulesPreset::WideMarket => "rules.wide_market",
+            RulesPreset::ShortGame => "rules.short_game",
+            RulesPreset::CustomDeck => "rules.custom_deck",
+            RulesPreset::IvoryAndTea => "rules.ivory_and_tea",
+        })
+    }
+}
@@ -1,4 +1,4 @@
-use super::{card::CardType, deck::Deck};
+use super::{card::CardType, deck::Deck, game_config::GameConfig};
 
 #[derive(Clone)]
 pub struct Market {
@@ -6,11 +6,11 @@ pub struct Market {
 }
 
 impl Market {
-    pub fn new(deck: &mut Deck) -> Self {
-        // take 3 camel tokens from deck, and 2 random cards to fill the market
+    pub fn new(deck: &mut Deck, config: &GameConfig) -> Self {
+        // take the starting camels from deck, and random cards to fill the market
         let mut market_cards = vec![];
 
-        for _ in 0..3 {
+        for _ in 0..config.num_starting_market_camels {
             let camel_card_idx = deck
                 .cards
                 .iter()
@@ -21,7 +21,7 @@ impl Market {
             market_cards.push(camel_card);
         }
 
-        for _ in 0..2 {
+        for _ in config.num_starting_market_camels..config.market_size {
             let random_card = deck.cards.remove(0);
             market_cards.push(random_card);
         }
@@ -1,5 +1,8 @@
 pub mod card;
 pub mod deck;
+pub mod deck_definition;
 pub mod discard_pile;
+pub mod game_config;
 pub mod market;
+pub mod puzzle_definition;
 pub mod tokens;
@@ -0,0 +1,597 @@
+use std::{collections::HashMap, fmt, iter};
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use itertools::Itertools;
+use serde::Deserialize;
+
+use super::{
+    card::{CardType, GoodType},
+    deck::Deck,
+    deck_definition::DeckDefinition,
+    discard_pile::DiscardPile,
+    game_config::GameConfig,
+    market::Market,
+    tokens::{BonusType, Tokens},
+};
+use crate::localization::Localization;
+
+// A position to play from and the goal to reach, loaded from a `.puzzle.ron` file in assets/puzzles
+#[derive(Clone, Debug, Deserialize, TypeUuid)]
+#[uuid = "b3d7a0e4-58c1-4f2e-9a6d-1c8e5f7b2a40"]
+pub struct PuzzleDefinition {
+    pub name: String,
+    pub goal: PuzzleGoal,
+    #[serde(default = "default_deck_definition_path")]
+    pub deck_definition: String,
+    pub market: Vec<PuzzleCard>,
+    // Listed from the top of the deck down
+    pub deck: Vec<PuzzleCard>,
+    // The human player, who moves first
+    pub player: PuzzlePlayer,
+    // The computer player
+    pub opponent: PuzzlePlayer,
+}
+
+fn default_deck_definition_path() -> String {
+    GameConfig::default().deck_definition_path
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+pub enum PuzzleGoal {
+    // Earn at least this many Rupees from tokens within the given number of your own turns
+    ScoreRupees { rupees: usize, turns: usize },
+    // Finish the game with more Rupees than the computer, including the camel bonus
+    WinGame,
+}
+
+impl PuzzleGoal {
+    pub fn localize(&self, localization: &Localization) -> String {
+        match self {
+            PuzzleGoal::ScoreRupees { rupees, turns: 1 } => {
+                localization.format("puzzle_goal.score_rupees", &[("rupees", rupees)])
+            }
+            PuzzleGoal::ScoreRupees { rupees, turns } => localization.format(
+                "puzzle_goal.score_rupees_in_turns",
+                &[("rupees", rupees), ("turns", turns)],
+            ),
+            PuzzleGoal::WinGame => localization.get("puzzle_goal.win_game"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub enum PuzzleCard {
+    Camel,
+    Good(String),
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PuzzlePlayer {
+    pub goods: Vec<String>,
+    pub camels: usize,
+    // The number of tokens of each good already sold. Taken from the top of each stack, the player's before the opponent's.
+    #[serde(default)]
+    pub sold: HashMap<String, usize>,
+    // The number of bonus tokens of each type already won
+    #[serde(default)]
+    pub bonus: HashMap<BonusType, usize>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum PuzzleDefinitionError {
+    NoTurns,
+    UnknownGood(String),
+    TooManyCards {
+        card: PuzzleCard,
+        required: usize,
+        available: usize,
+    },
+    WrongMarketSize {
+        required: usize,
+        found: usize,
+    },
+    HandTooLarge {
+        is_opponent: bool,
+        max_goods_in_hand: usize,
+    },
+    TooManyTokensSold(String),
+    TooManyBonusTokensWon(BonusType),
+}
+
+impl fmt::Display for PuzzleDefinitionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PuzzleDefinitionError::NoTurns => {
+                write!(f, "The goal must allow at least one turn")
+            }
+            PuzzleDefinitionError::UnknownGood(name) => {
+                write!(f, "{} is not a good in the deck", name)
+            }
+            PuzzleDefinitionError::TooManyCards {
+                card,
+                required,
+                available,
+            } => write!(
+                f,
+                "The puzzle places {} {} cards, but the deck only has {}",
+                required,
+                match card {
+                    PuzzleCard::Camel => "Camel",
+                    PuzzleCard::Good(name) => name.as_str(),
+                },
+                available
+            ),
+            PuzzleDefinitionError::WrongMarketSize { required, found } => write!(
+                f,
+                "The market must have {} cards, but the puzzle places {}",
+                required, found
+            ),
+            PuzzleDefinitionError::HandTooLarge {
+                is_opponent,
+                max_goods_in_hand,
+            } => write!(
+                f,
+                "The {}'s hand has more than {} goods",
+                if *is_opponent { "opponent" } else { "player" },
+                max_goods_in_hand
+            ),
+            PuzzleDefinitionError::TooManyTokensSold(name) => {
+                write!(f, "More {} tokens are sold than the stack has", name)
+            }
+            PuzzleDefinitionError::TooManyBonusTokensWon(bonus_type) => {
+                write!(
+                    f,
+                    "More {:?} bonus tokens are won than the pool has",
+                    bonus_type
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for PuzzleDefinitionError {}
+
+impl PuzzleDefinitionError {
+    // For the main menu. The Display text, in English, is for the log.
+    pub fn localize(&self, localization: &Localization) -> String {
+        match self {
+            PuzzleDefinitionError::NoTurns => localization.get("puzzle_error.no_turns"),
+            PuzzleDefinitionError::UnknownGood(name) => localization.format(
+                "puzzle_error.unknown_good",
+                &[("good", &localization.get_good_name(name))],
+            ),
+            PuzzleDefinitionError::TooManyCards {
+                card,
+                required,
+                available,
+            } => localization.format(
+                "puzzle_error.too_many_cards",
+                &[
+                    (
+                        "card",
+                        &match card {
+                            PuzzleCard::Camel => localization.get("puzzle_error.camel"),
+                            PuzzleCard::Good(name) => localization.get_good_name(name),
+                        },
+                    ),
+                    ("required", required),
+                    ("available", available),
+                ],
+            ),
+            PuzzleDefinitionError::WrongMarketSize { required, found } => localization.format(
+                "puzzle_error.wrong_market_size",
+                &[("required", required), ("found", found)],
+            ),
+            PuzzleDefinitionError::HandTooLarge {
+                is_opponent,
+                max_goods_in_hand,
+            } => localization.format(
+                if *is_opponent {
+                    "puzzle_error.opponent_hand_too_large"
+                } else {
+                    "puzzle_error.player_hand_too_large"
+                },
+                &[("max", max_goods_in_hand)],
+            ),
+            PuzzleDefinitionError::TooManyTokensSold(name) => localization.format(
+                "puzzle_error.too_many_tokens_sold",
+                &[("good", &localization.get_good_name(name))],
+            ),
+            PuzzleDefinitionError::TooManyBonusTokensWon(bonus_type) => localization.format(
+                "puzzle_error.too_many_bonus_tokens_won",
+                &[("bonus", &bonus_type.localize(localization))],
+            ),
+        }
+    }
+}
+
+// The game resources for a puzzle, in place of those dealt for a normal game
+pub struct PuzzlePosition {
+    pub deck: Deck,
+    pub market: Market,
+    pub discard_pile: DiscardPile,
+    pub tokens: Tokens,
+    pub player: PuzzlePlayerPosition,
+    pub opponent: PuzzlePlayerPosition,
+}
+
+pub struct PuzzlePlayerPosition {
+    pub goods: Vec<GoodType>,
+    pub camels: usize,
+    pub tokens: Tokens,
+}
+
+impl PuzzleDefinition {
+    // Builds the position with the given cards and tokens. Every card of the deck definition that the puzzle does not place
+    // is in the discard pile, and the bonus token pools are not shuffled, so the last token listed is taken first.
+    pub fn create_position(
+        &self,
+        deck_definition: &DeckDefinition,
+        config: &GameConfig,
+    ) -> Result<PuzzlePosition, PuzzleDefinitionError> {
+        if matches!(self.goal, PuzzleGoal::ScoreRupees { turns: 0, .. }) {
+            return Err(PuzzleDefinitionError::NoTurns);
+        }
+
+        let market = self.get_cards(&self.market, deck_definition)?;
+        let mut deck = self.get_cards(&self.deck, deck_definition)?;
+        deck.reverse();
+        let player_goods = self.get_goods(&self.player.goods, deck_definition)?;
+        let opponent_goods = self.get_goods(&self.opponent.goods, deck_definition)?;
+
+        if market.len() != config.market_size
+            && !(deck.is_empty() && market.len() < config.market_size)
+        {
+            return Err(PuzzleDefinitionError::WrongMarketSize {
+                required: config.market_size,
+                found: market.len(),
+            });
+        }
+
+        for (is_opponent, goods) in [(false, &player_goods), (true, &opponent_goods)] {
+            if goods.len() > config.max_goods_in_hand {
+                return Err(PuzzleDefinitionError::HandTooLarge {
+                    is_opponent,
+                    max_goods_in_hand: config.max_goods_in_hand,
+                });
+            }
+        }
+
+        // Whatever is left after placing the puzzle's cards was discarded earlier in the game
+        let mut discarded = Deck::create_all_cards(deck_definition);
+        let placed_cards = market
+            .iter()
+            .chain(deck.iter())
+            .copied()
+            .chain(player_goods.iter().map(|g| CardType::Good(*g)))
+            .chain(opponent_goods.iter().map(|g| CardType::Good(*g)))
+            .chain(iter::repeat(CardType::Camel).take(self.player.camels + self.opponent.camels));
+
+        for (card, required) in placed_cards.counts() {
+            let available = discarded.iter().filter(|c| **c == card).count();
+            if required > available {
+                return Err(PuzzleDefinitionError::TooManyCards {
+                    card: match card {
+                        CardType::Camel => PuzzleCard::Camel,
+                        CardType::Good(good) => {
+                            PuzzleCard::Good(deck_definition.goods[good].name.clone())
+                        }
+                    },
+                    required,
+                    available,
+                });
+            }
+
+            for _ in 0..required {
+                let idx = discarded.iter().position(|c| *c == card).unwrap();
+                discarded.remove(idx);
+            }
+        }
+
+        let mut tokens = Tokens::create_unshuffled_game_tokens(deck_definition);
+        let player_tokens = self.take_tokens(&self.player, &mut tokens, deck_definition)?;
+        let opponent_tokens = self.take_tokens(&self.opponent, &mut tokens, deck_definition)?;
+
+        Ok(PuzzlePosition {
+            deck: Deck { cards: deck },
+            market: Market { cards: market },
+            discard_pile: DiscardPile { cards: discarded },
+            tokens,
+            player: PuzzlePlayerPosition {
+                goods: player_goods,
+                camels: self.player.camels,
+                tokens: player_tokens,
+            },
+            opponent: PuzzlePlayerPosition {
+                goods: opponent_goods,
+                camels: self.opponent.camels,
+                tokens: opponent_tokens,
+            },
+        })
+    }
+
+    fn get_good(
+        &self,
+        name: &str,
+        deck_definition: &DeckDefinition,
+    ) -> Result<GoodType, PuzzleDefinitionError> {
+        deck_definition
+            .find_good(name)
+            .ok_or_else(|| PuzzleDefinitionError::UnknownGood(name.to_string()))
+    }
+
+    fn get_goods(
+        &self,
+        names: &[String],
+        deck_definition: &DeckDefinition,
+    ) -> Result<Vec<GoodType>, PuzzleDefinitionError> {
+        names
+            .iter()
+            .map(|name| self.get_good(name, deck_definition))
+            .collect()
+    }
+
+    fn get_cards(
+        &self,
+        cards: &[PuzzleCard],
+        deck_definition: &DeckDefinition,
+    ) -> Result<Vec<CardType>, PuzzleDefinitionError> {
+        cards
+            .iter()
+            .map(|card| match card {
+                PuzzleCard::Camel => Ok(CardType::Camel),
+                PuzzleCard::Good(name) => Ok(CardType::Good(self.get_good(name, deck_definition)?)),
+            })
+            .collect()
+    }
+
+    // Moves the tokens the player has already won from the top of the game's stacks and pools
+    fn take_tokens(
+        &self,
+        puzzle_player: &PuzzlePlayer,
+        game_tokens: &mut Tokens,
+        deck_definition: &DeckDefinition,
+    ) -> Result<Tokens, PuzzleDefinitionError> {
+        let mut tokens = Tokens::create_empty(deck_definition.goods.len());
+
+        for (name, num_sold) in puzzle_player.sold.iter() {
+            let good = self.get_good(name, deck_definition)?;
+            for _ in 0..*num_sold {
+                let token = game_tokens.goods[good]
+                    .pop()
+                    .ok_or_else(|| PuzzleDefinitionError::TooManyTokensSold(name.clone()))?;
+                tokens.goods[good].push(token);
+            }
+        }
+
+        for (bonus_type, num_won) in puzzle_player.bonus.iter() {
+            for _ in 0..*num_won {
+                let token = game_tokens.bonus[*bonus_type]
+                    .pop()
+                    .ok_or(PuzzleDefinitionError::TooManyBonusTokensWon(*bonus_type))?;
+                tokens.bonus[*bonus_type].push(token);
+            }
+        }
+
+        Ok(tokens)
+    }
+}
+
+#[derive(Default)]
+pub struct PuzzleDefinitionLoader;
+
+impl AssetLoader for PuzzleDefinitionLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let puzzle_definition = ron::de::from_bytes::<PuzzleDefinition>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(puzzle_definition));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["puzzle.ron"]
+    }
+}
+
+// The puzzle being loaded for the game being set up
+pub struct PuzzleDefinitionHandle(pub Handle<PuzzleDefinition>);
+
+pub struct PuzzleDefinitionPlugin;
+
+impl Plugin for PuzzleDefinitionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<PuzzleDefinition>()
+            .init_asset_loader::<PuzzleDefinitionLoader>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{official_deck_definition, CLOTH, DIAMOND, GOLD, LEATHER, SPICE};
+
+    fn good(name: &str) -> PuzzleCard {
+        PuzzleCard::Good(name.to_string())
+    }
+
+    fn puzzle_player(goods: &[&str], sold: &[(&str, usize)]) -> PuzzlePlayer {
+        PuzzlePlayer {
+            goods: goods.iter().map(|name| name.to_string()).collect(),
+            camels: 1,
+            sold: sold
+                .iter()
+                .map(|(name, num_sold)| (name.to_string(), *num_sold))
+                .collect(),
+            bonus: HashMap::new(),
+        }
+    }
+
+    fn create_puzzle(market: Vec<PuzzleCard>, deck: Vec<PuzzleCard>) -> PuzzleDefinition {
+        PuzzleDefinition {
+            name: "Test".to_string(),
+            goal: PuzzleGoal::WinGame,
+            deck_definition: default_deck_definition_path(),
+            market,
+            deck,
+            player: puzzle_player(&["Diamond", "Cloth"], &[]),
+            opponent: puzzle_player(&["Gold"], &[]),
+        }
+    }
+
+    fn create_market() -> Vec<PuzzleCard> {
+        vec![
+            good("Diamond"),
+            PuzzleCard::Camel,
+            PuzzleCard::Camel,
+            good("Cloth"),
+            good("Leather"),
+        ]
+    }
+
+    fn create_position(puzzle: &PuzzleDefinition) -> Result<PuzzlePosition, PuzzleDefinitionError> {
+        puzzle.create_position(&official_deck_definition(), &GameConfig::default())
+    }
+
+    #[test]
+    fn deck_is_listed_from_the_top_down() {
+        let puzzle = create_puzzle(
+            create_market(),
+            vec![good("Leather"), good("Spice"), good("Gold")],
+        );
+
+        let position = create_position(&puzzle).unwrap();
+
+        // Cards are drawn from the end of the deck
+        assert_eq!(
+            position.deck.cards,
+            vec![
+                CardType::Good(GOLD),
+                CardType::Good(SPICE),
+                CardType::Good(LEATHER)
+            ]
+        );
+    }
+
+    #[test]
+    fn cards_not_placed_are_discarded() {
+        let deck_definition = official_deck_definition();
+        let puzzle = create_puzzle(create_market(), vec![good("Leather")]);
+
+        let position = create_position(&puzzle).unwrap();
+
+        // The market, the deck, both hands and both players' camels
+        assert_eq!(
+            position.discard_pile.cards.len(),
+            deck_definition.get_total_num_cards() - 5 - 1 - 3 - 2
+        );
+        assert_eq!(position.player.goods, vec![DIAMOND, CLOTH]);
+    }
+
+    #[test]
+    fn market_must_be_full_while_the_deck_has_cards() {
+        let mut market = create_market();
+        market.pop();
+        let puzzle = create_puzzle(market, vec![good("Leather")]);
+
+        assert_eq!(
+            create_position(&puzzle).err(),
+            Some(PuzzleDefinitionError::WrongMarketSize {
+                required: 5,
+                found: 4
+            })
+        );
+    }
+
+    #[test]
+    fn market_can_be_short_once_the_deck_is_empty() {
+        let mut market = create_market();
+        market.pop();
+        let puzzle = create_puzzle(market, vec![]);
+
+        assert!(create_position(&puzzle).is_ok());
+    }
+
+    #[test]
+    fn cards_cannot_be_placed_more_times_than_the_deck_has_them() {
+        // The official deck has 6 diamonds, and the player holds one
+        let puzzle = create_puzzle(
+            vec![good("Diamond"); 5],
+            vec![good("Diamond"), good("Leather")],
+        );
+
+        assert_eq!(
+            create_position(&puzzle).err(),
+            Some(PuzzleDefinitionError::TooManyCards {
+                card: good("Diamond"),
+                required: 7,
+                available: 6,
+            })
+        );
+    }
+
+    #[test]
+    fn hand_cannot_be_larger_than_the_rules_allow() {
+        let mut puzzle = create_puzzle(create_market(), vec![]);
+        puzzle.opponent = puzzle_player(&["Leather"; 8], &[]);
+
+        assert_eq!(
+            create_position(&puzzle).err(),
+            Some(PuzzleDefinitionError::HandTooLarge {
+                is_opponent: true,
+                max_goods_in_hand: 7
+            })
+        );
+    }
+
+    #[test]
+    fn sold_tokens_are_taken_from_the_top_of_the_stack_player_first() {
+        let mut puzzle = create_puzzle(create_market(), vec![]);
+        puzzle.player = puzzle_player(&[], &[("Diamond", 2)]);
+        puzzle.opponent = puzzle_player(&[], &[("Diamond", 1)]);
+
+        let position = create_position(&puzzle).unwrap();
+
+        assert_eq!(position.player.tokens.goods[DIAMOND], vec![7, 7]);
+        assert_eq!(position.opponent.tokens.goods[DIAMOND], vec![5]);
+        assert_eq!(position.tokens.goods[DIAMOND], vec![5, 5]);
+    }
+
+    #[test]
+    fn cannot_sell_more_tokens_than_the_stack_holds() {
+        // The official deck has 5 diamond tokens
+        let mut puzzle = create_puzzle(create_market(), vec![]);
+        puzzle.player = puzzle_player(&[], &[("Diamond", 3)]);
+        puzzle.opponent = puzzle_player(&[], &[("Diamond", 3)]);
+
+        assert_eq!(
+            create_position(&puzzle).err(),
+            Some(PuzzleDefinitionError::TooManyTokensSold(
+                "Diamond".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn goal_must_allow_a_turn() {
+        let mut puzzle = create_puzzle(create_market(), vec![]);
+        puzzle.goal = PuzzleGoal::ScoreRupees {
+            rupees: 10,
+            turns: 0,
+        };
+
+        assert_eq!(
+            create_position(&puzzle).err(),
+            Some(PuzzleDefinitionError::NoTurns)
+        );
+    }
+}
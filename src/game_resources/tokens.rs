@@ -1,60 +1,201 @@
 use enum_map::{enum_map, Enum, EnumMap};
-use rand::{seq::SliceRandom, thread_rng};
+use rand::{seq::SliceRandom, Rng};
+use serde::Deserialize;
 
-use super::card::GoodType;
+use super::{
+    card::{GoodType, GoodsMap},
+    deck_definition::DeckDefinition,
+};
+use crate::localization::Localization;
 
 #[derive(Clone, Debug)]
 pub struct Tokens {
-    pub goods: EnumMap<GoodType, Vec<usize>>,
+    pub goods: GoodsMap<Vec<usize>>,
     pub bonus: EnumMap<BonusType, Vec<usize>>,
 }
 
-#[derive(Clone, Copy, Debug, Enum)]
+#[derive(Clone, Copy, Debug, Deserialize, Enum, Eq, PartialEq, Hash)]
 pub enum BonusType {
     Three,
     Four,
     Five,
 }
 
+impl BonusType {
+    // The bonus token awarded for selling this many goods at once, if any
+    pub fn from_num_sold(num_sold: usize) -> Option<Self> {
+        match num_sold {
+            3 => Some(BonusType::Three),
+            4 => Some(BonusType::Four),
+            d if 5 <= d => Some(BonusType::Five),
+            _ => None,
+        }
+    }
+
+    pub fn localize(&self, localization: &Localization) -> String {
+        localization.get(match self {
+            BonusType::Three => "bonus_type.three",
+            BonusType::Four => "bonus_type.four",
+            BonusType::Five => "bonus_type.five",
+        })
+    }
+}
+
+// What a sale would earn if it were confirmed now
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SalePreview {
+    // In the order they would be taken from the top of the stack
+    pub goods_tokens: Vec<usize>,
+    pub bonus_type: Option<BonusType>,
+    // The lowest and highest values left in the bonus pile, since the token on top is not known.
+    // None if the pile is empty, when no bonus token is awarded.
+    pub bonus_value_range: Option<(usize, usize)>,
+    pub is_game_ending: bool,
+}
+
+impl SalePreview {
+    pub fn get_goods_tokens_value(&self) -> usize {
+        self.goods_tokens.iter().sum()
+    }
+}
+
 impl Tokens {
-    pub fn create_game_tokens() -> Self {
-        let goods = enum_map! {
-          GoodType::Diamond => vec![5,5,5,7,7,],
-          GoodType::Gold => vec![5,5,5,6,6,],
-          GoodType::Silver => vec![5,5,5,5,5,],
-          GoodType::Cloth => vec![1,1,2,2,3,3,5,],
-          GoodType::Spice => vec![1,1,2,2,3,3,5,],
-          GoodType::Leather => vec![1,1,1,1,1,1,2,3,4,],
-        };
+    pub fn preview_sale(
+        &self,
+        good_type: GoodType,
+        num_sold: usize,
+        num_empty_token_stacks_to_end: usize,
+    ) -> SalePreview {
+        let stack = &self.goods[good_type];
+        let goods_tokens = stack.iter().rev().take(num_sold).copied().collect();
+
+        let bonus_type = BonusType::from_num_sold(num_sold);
+        let bonus_value_range = bonus_type.and_then(|bonus_type| {
+            let pile = &self.bonus[bonus_type];
+            Some((*pile.iter().min()?, *pile.iter().max()?))
+        });
 
-        let mut rng = thread_rng();
+        let num_empty_stacks = self
+            .goods
+            .iter()
+            .filter(|(other_good_type, token_values)| {
+                token_values.is_empty()
+                    || (*other_good_type == good_type && token_values.len() <= num_sold)
+            })
+            .count();
 
-        let mut three_bonuses = vec![3, 3, 2, 2, 2, 1, 1];
-        three_bonuses.shuffle(&mut rng);
+        SalePreview {
+            goods_tokens,
+            bonus_type,
+            bonus_value_range,
+            is_game_ending: num_empty_stacks >= num_empty_token_stacks_to_end,
+        }
+    }
 
-        let mut four_bonuses = vec![6, 6, 5, 5, 4, 4];
-        four_bonuses.shuffle(&mut rng);
+    // The definition must have been validated, so that every bonus type is present
+    pub fn create_game_tokens(deck_definition: &DeckDefinition, rng: &mut impl Rng) -> Self {
+        let mut tokens = Tokens::create_unshuffled_game_tokens(deck_definition);
 
-        let mut five_bonuses = vec![10, 10, 9, 8, 8];
-        five_bonuses.shuffle(&mut rng);
+        for (_, bonuses) in tokens.bonus.iter_mut() {
+            bonuses.shuffle(rng);
+        }
+
+        tokens
+    }
+
+    // The bonus token pools in the order they are defined, so the last token listed is on top
+    pub fn create_unshuffled_game_tokens(deck_definition: &DeckDefinition) -> Self {
+        let goods = deck_definition
+            .goods
+            .map(|goods_definition| goods_definition.tokens.clone());
 
         let bonus = enum_map! {
-          BonusType::Three => three_bonuses.clone(),
-          BonusType::Four => four_bonuses.clone(),
-          BonusType::Five => five_bonuses.clone(),
+          bonus_type => deck_definition.bonus_tokens[&bonus_type].clone(),
         };
 
         Self { goods, bonus }
     }
 
-    pub fn create_empty() -> Self {
+    pub fn create_empty(num_goods: usize) -> Self {
         Self {
-            goods: enum_map! {
-              _ => vec![]
-            },
+            goods: GoodsMap::new(vec![vec![]; num_goods]),
             bonus: enum_map! {
               _ => vec![]
             },
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{CLOTH, DIAMOND, LEATHER};
+
+    fn create_tokens() -> Tokens {
+        let mut tokens = Tokens::create_empty(6);
+        for good_idx in 0..6 {
+            tokens.goods[GoodType(good_idx)] = vec![1, 2];
+        }
+        tokens.goods[LEATHER] = vec![1, 1, 2, 3, 4];
+        tokens.goods[CLOTH] = vec![2, 3];
+        tokens.bonus[BonusType::Three] = vec![2, 1, 3];
+        tokens
+    }
+
+    #[test]
+    fn bonus_type_depends_on_the_number_of_goods_sold() {
+        assert_eq!(BonusType::from_num_sold(2), None);
+        assert_eq!(BonusType::from_num_sold(3), Some(BonusType::Three));
+        assert_eq!(BonusType::from_num_sold(4), Some(BonusType::Four));
+        assert_eq!(BonusType::from_num_sold(5), Some(BonusType::Five));
+        assert_eq!(BonusType::from_num_sold(6), Some(BonusType::Five));
+    }
+
+    #[test]
+    fn preview_takes_goods_tokens_from_the_top_of_the_stack() {
+        let preview = create_tokens().preview_sale(LEATHER, 2, 3);
+
+        assert_eq!(preview.goods_tokens, vec![4, 3]);
+        assert_eq!(preview.get_goods_tokens_value(), 7);
+        assert_eq!(preview.bonus_type, None);
+        assert_eq!(preview.bonus_value_range, None);
+        assert!(!preview.is_game_ending);
+    }
+
+    #[test]
+    fn preview_shows_the_range_of_the_bonus_pile() {
+        let preview = create_tokens().preview_sale(LEATHER, 3, 3);
+
+        assert_eq!(preview.goods_tokens, vec![4, 3, 2]);
+        assert_eq!(preview.bonus_type, Some(BonusType::Three));
+        assert_eq!(preview.bonus_value_range, Some((1, 3)));
+    }
+
+    #[test]
+    fn preview_has_no_bonus_range_when_the_bonus_pile_is_empty() {
+        let preview = create_tokens().preview_sale(LEATHER, 4, 3);
+
+        assert_eq!(preview.bonus_type, Some(BonusType::Four));
+        assert_eq!(preview.bonus_value_range, None);
+    }
+
+    #[test]
+    fn preview_stops_when_the_stack_runs_out_partway_through_a_sale() {
+        let preview = create_tokens().preview_sale(CLOTH, 3, 3);
+
+        assert_eq!(preview.goods_tokens, vec![3, 2]);
+        assert_eq!(preview.get_goods_tokens_value(), 5);
+        assert_eq!(preview.bonus_type, Some(BonusType::Three));
+        assert!(!preview.is_game_ending);
+    }
+
+    #[test]
+    fn preview_ends_the_game_when_the_sale_empties_the_last_stack_needed() {
+        let mut tokens = create_tokens();
+        tokens.goods[DIAMOND].clear();
+        tokens.goods[GoodType(1)].clear();
+
+        assert!(tokens.preview_sale(CLOTH, 2, 3).is_game_ending);
+        assert!(!tokens.preview_sale(CLOTH, 1, 3).is_game_ending);
+    }
+}
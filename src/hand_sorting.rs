@@ -0,0 +1,248 @@
+use bevy::prelude::*;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+
+use crate::animation::animate_transform;
+use crate::game::{ActivePlayer, GoodsHandOwner, HumanPlayer};
+use crate::game_resources::card::{ActivePlayerGoodsCard, GoodType};
+use crate::game_resources::tokens::Tokens;
+use crate::localization::Localization;
+use crate::move_execution::TweenState;
+use crate::positioning::Layout;
+use crate::settings::Settings;
+use crate::states::AppState;
+
+// How the goods in a player's hand are laid out, chosen in the settings menu. Goods that sort equally stay in the
+// order they were taken.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum HandSortOrder {
+    // In the order the goods were taken, closing up the gaps left by sales
+    #[default]
+    Unsorted,
+    // In the order of the goods in the deck definition - diamonds first by the official rules
+    GoodType,
+    // The goods whose token stacks are worth the most right now first
+    Value,
+    // The goods held the most of first
+    Count,
+}
+
+impl HandSortOrder {
+    pub fn next(&self) -> Self {
+        match self {
+            HandSortOrder::Unsorted => HandSortOrder::GoodType,
+            HandSortOrder::GoodType => HandSortOrder::Value,
+            HandSortOrder::Value => HandSortOrder::Count,
+            HandSortOrder::Count => HandSortOrder::Unsorted,
+        }
+    }
+
+    // The index in the hand that each good moves to, or None if the hand is left as it is
+    pub fn get_sorted_indices(&self, hand: &[GoodType], tokens: &Tokens) -> Option<Vec<usize>> {
+        let get_top_token_value =
+            |good_type: GoodType| tokens.goods[good_type].last().copied().unwrap_or_default();
+        let get_count = |good_type: GoodType| hand.iter().filter(|g| **g == good_type).count();
+
+        let order = match self {
+            HandSortOrder::Unsorted => return None,
+            HandSortOrder::GoodType => (0..hand.len())
+                .sorted_by_key(|idx| hand[*idx])
+                .collect::<Vec<_>>(),
+            HandSortOrder::Value => (0..hand.len())
+                .sorted_by_key(|idx| (Reverse(get_top_token_value(hand[*idx])), hand[*idx]))
+                .collect(),
+            HandSortOrder::Count => (0..hand.len())
+                .sorted_by_key(|idx| (Reverse(get_count(hand[*idx])), hand[*idx]))
+                .collect(),
+        };
+
+        let mut sorted_indices = vec![0; hand.len()];
+        for (sorted_idx, idx) in order.into_iter().enumerate() {
+            sorted_indices[idx] = sorted_idx;
+        }
+
+        Some(sorted_indices)
+    }
+
+    pub fn localize(&self, localization: &Localization) -> String {
+        localization.get(match self {
+            HandSortOrder::Unsorted => "hand_sort_order.unsorted",
+            HandSortOrder::GoodType => "hand_sort_order.good_type",
+            HandSortOrder::Value => "hand_sort_order.value",
+            HandSortOrder::Count => "hand_sort_order.count",
+        })
+    }
+}
+
+// Reorders both the GoodsHandOwner and the ActivePlayerGoodsCard indices, so that each card's index still matches
+// its good's position in the hand, and moves the cards to their new positions. Only a human player's hand is
+// sorted, since the computer's cards are face down.
+#[allow(clippy::too_many_arguments)]
+fn sort_active_player_goods_hand(
+    In(should_sort): In<bool>,
+    mut commands: Commands,
+    mut tween_state: ResMut<TweenState>,
+    layout: Res<Layout>,
+    settings: Res<Settings>,
+    app_state: Res<State<AppState>>,
+    tokens: Option<Res<Tokens>>,
+    mut active_player_query: Query<&mut GoodsHandOwner, (With<ActivePlayer>, With<HumanPlayer>)>,
+    goods_cards_query: Query<(Entity, &ActivePlayerGoodsCard, &Transform)>,
+) {
+    let (tokens, mut goods_hand_owner) = match (tokens, active_player_query.get_single_mut()) {
+        (Some(tokens), Ok(goods_hand_owner)) if should_sort => (tokens, goods_hand_owner),
+        _ => return,
+    };
+
+    let sorted_indices = match settings
+        .hand_sort_order
+        .get_sorted_indices(&goods_hand_owner.0, &tokens)
+    {
+        Some(sorted_indices) => sorted_indices,
+        None => return,
+    };
+
+    if sorted_indices
+        .iter()
+        .enumerate()
+        .all(|(idx, sorted_idx)| idx == *sorted_idx)
+    {
+        return;
+    }
+
+    let mut sorted_hand = goods_hand_owner.0.clone();
+    for (idx, good_type) in goods_hand_owner.0.iter().enumerate() {
+        sorted_hand[sorted_indices[idx]] = *good_type;
+    }
+    goods_hand_owner.0 = sorted_hand;
+
+    // Only the cards sorted after a move hold up the next turn
+    let mut untracked_tween_state = TweenState::default();
+    let tween_state = if *app_state.current() == AppState::WaitForTweensToFinish {
+        &mut *tween_state
+    } else {
+        &mut untracked_tween_state
+    };
+
+    for (e, goods_card, transform) in goods_cards_query.iter() {
+        let sorted_idx = sorted_indices[goods_card.0];
+
+        if sorted_idx != goods_card.0 {
+            animate_transform(
+                &mut commands,
+                tween_state,
+                settings.animation_speed,
+                e,
+                transform,
+                Transform {
+                    translation: layout.get_active_player_goods_card_translation(sorted_idx),
+                    ..*transform
+                },
+                e.to_bits(),
+            );
+
+            commands
+                .entity(e)
+                .remove::<ActivePlayerGoodsCard>()
+                .insert(ActivePlayerGoodsCard(sorted_idx));
+        }
+    }
+}
+
+fn pass_through() -> bool {
+    true
+}
+
+// The sort order may have been changed in the pause menu
+fn has_settings_changed(settings: Res<Settings>) -> bool {
+    settings.is_changed()
+}
+
+pub struct HandSortingPlugin;
+
+impl Plugin for HandSortingPlugin {
+    fn build(&self, app: &mut App) {
+        // The hand is sorted at the start of each turn, after each move, and when the settings change
+        app.add_system_set(
+            SystemSet::on_enter(AppState::InGame)
+                .with_system(pass_through.chain(sort_active_player_goods_hand)),
+        )
+        .add_system_set(
+            SystemSet::on_enter(AppState::WaitForTweensToFinish)
+                .with_system(pass_through.chain(sort_active_player_goods_hand)),
+        )
+        .add_system_set(
+            SystemSet::on_update(AppState::InGame)
+                .with_system(has_settings_changed.chain(sort_active_player_goods_hand)),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{CLOTH, DIAMOND, LEATHER};
+
+    const HAND: [GoodType; 5] = [LEATHER, DIAMOND, CLOTH, LEATHER, CLOTH];
+
+    fn create_tokens(diamond_value: usize, cloth_value: usize, leather_value: usize) -> Tokens {
+        let mut tokens = Tokens::create_empty(6);
+        for (good_type, value) in [
+            (DIAMOND, diamond_value),
+            (CLOTH, cloth_value),
+            (LEATHER, leather_value),
+        ] {
+            tokens.goods[good_type] = (1..=value).collect();
+        }
+        tokens
+    }
+
+    #[test]
+    fn unsorted_hand_is_left_as_it_is() {
+        assert_eq!(
+            HandSortOrder::Unsorted.get_sorted_indices(&HAND, &create_tokens(5, 3, 4)),
+            None
+        );
+    }
+
+    #[test]
+    fn good_type_order_keeps_equal_goods_in_the_order_they_were_taken() {
+        assert_eq!(
+            HandSortOrder::GoodType.get_sorted_indices(&HAND, &create_tokens(5, 3, 4)),
+            Some(vec![3, 0, 1, 4, 2])
+        );
+    }
+
+    #[test]
+    fn value_order_puts_the_most_valuable_top_token_first() {
+        assert_eq!(
+            HandSortOrder::Value.get_sorted_indices(&HAND, &create_tokens(5, 3, 4)),
+            Some(vec![1, 0, 3, 2, 4])
+        );
+    }
+
+    #[test]
+    fn value_order_breaks_ties_by_good_type_and_puts_empty_stacks_last() {
+        assert_eq!(
+            HandSortOrder::Value.get_sorted_indices(&HAND, &create_tokens(0, 4, 4)),
+            Some(vec![2, 4, 0, 3, 1])
+        );
+    }
+
+    #[test]
+    fn count_order_puts_the_goods_held_the_most_first_and_breaks_ties_by_good_type() {
+        assert_eq!(
+            HandSortOrder::Count.get_sorted_indices(&HAND, &create_tokens(5, 3, 4)),
+            Some(vec![2, 4, 0, 3, 1])
+        );
+    }
+
+    #[test]
+    fn empty_hand_has_no_indices() {
+        assert_eq!(
+            HandSortOrder::Value.get_sorted_indices(&[], &create_tokens(5, 3, 4)),
+            Some(vec![])
+        );
+    }
+}
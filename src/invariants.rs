@@ -0,0 +1,277 @@
+use bevy::prelude::*;
+use itertools::Itertools;
+use std::collections::HashMap;
+
+use crate::game::{
+    ActivePlayer, CamelsHandOwner, DeckCard, GoodsHandOwner, Player, PlayerName, TokensOwner,
+};
+use crate::game_resources::card::*;
+use crate::game_resources::deck::Deck;
+use crate::game_resources::deck_definition::DeckDefinition;
+use crate::game_resources::discard_pile::DiscardPile;
+use crate::game_resources::game_config::GameConfig;
+use crate::game_resources::market::Market;
+use crate::game_resources::tokens::Tokens;
+use crate::states::AppState;
+
+// Checks that no card or token has been lost or duplicated by move execution, and that the card entities agree with the game resources.
+// Only added in debug builds - see GamePlugin.
+
+#[derive(Default)]
+struct InvariantCheckState {
+    is_check_pending: bool,
+}
+
+fn request_invariant_check(mut invariant_check_state: ResMut<InvariantCheckState>) {
+    invariant_check_state.is_check_pending = true;
+}
+
+type PlayerQueryItem<'a> = (
+    &'a PlayerName,
+    &'a GoodsHandOwner,
+    &'a CamelsHandOwner,
+    &'a TokensOwner,
+    Option<&'a ActivePlayer>,
+);
+
+#[allow(clippy::too_many_arguments)]
+fn check_game_invariants(
+    mut invariant_check_state: ResMut<InvariantCheckState>,
+    app_state: Res<State<AppState>>,
+    game_config: Res<GameConfig>,
+    deck_definition: Option<Res<DeckDefinition>>,
+    deck: Option<Res<Deck>>,
+    market: Option<Res<Market>>,
+    discard_pile: Option<Res<DiscardPile>>,
+    game_tokens: Option<Res<Tokens>>,
+    players_query: Query<PlayerQueryItem, With<Player>>,
+    deck_cards_query: Query<(&Card, &DeckCard)>,
+    market_cards_query: Query<(&Card, &MarketCard)>,
+    active_goods_cards_query: Query<(&Card, &ActivePlayerGoodsCard)>,
+    inactive_goods_cards_query: Query<(&Card, &InactivePlayerGoodsCard)>,
+    active_camel_cards_query: Query<&Card, With<ActivePlayerCamelCard>>,
+    inactive_camel_cards_query: Query<&Card, With<InactivePlayerCamelCard>>,
+) {
+    // Card entities are only guaranteed to be in place once a turn has started
+    if !invariant_check_state.is_check_pending
+        || !matches!(
+            app_state.current(),
+            AppState::InGame | AppState::AiTurn | AppState::GameOver
+        )
+    {
+        return;
+    }
+
+    invariant_check_state.is_check_pending = false;
+
+    // The game resources only exist while a game is being played - see teardown_game
+    let (deck_definition, deck, market, discard_pile, game_tokens) =
+        match (deck_definition, deck, market, discard_pile, game_tokens) {
+            (
+                Some(deck_definition),
+                Some(deck),
+                Some(market),
+                Some(discard_pile),
+                Some(game_tokens),
+            ) => (deck_definition, deck, market, discard_pile, game_tokens),
+            _ => return,
+        };
+
+    let players = players_query.iter().collect::<Vec<_>>();
+    let mut violations: Vec<String> = vec![];
+
+    // Every card is accounted for exactly once
+    let mut all_cards = deck.cards.clone();
+    all_cards.extend(market.cards.iter());
+    all_cards.extend(discard_pile.cards.iter());
+    for (_, goods_hand, camels_hand, _, _) in players.iter() {
+        all_cards.extend(goods_hand.0.iter().map(|g| CardType::Good(*g)));
+        all_cards.extend(std::iter::repeat(CardType::Camel).take(camels_hand.0));
+    }
+
+    violations.extend(get_count_differences(
+        "cards",
+        &Deck::create_all_cards(&deck_definition),
+        &all_cards,
+    ));
+
+    // Every token is accounted for exactly once
+    let full_tokens = Tokens::create_unshuffled_game_tokens(&deck_definition);
+    let all_tokens = players
+        .iter()
+        .map(|(_, _, _, tokens_owner, _)| &tokens_owner.0)
+        .chain(std::iter::once(game_tokens.as_ref()))
+        .collect::<Vec<_>>();
+
+    for (good_type, expected) in full_tokens.goods.iter() {
+        let actual = all_tokens
+            .iter()
+            .flat_map(|tokens| tokens.goods[good_type].iter().copied())
+            .collect::<Vec<_>>();
+        violations.extend(get_count_differences(
+            &format!("{} tokens", deck_definition.goods[good_type].name),
+            expected,
+            &actual,
+        ));
+    }
+
+    for (bonus_type, expected) in full_tokens.bonus.iter() {
+        let actual = all_tokens
+            .iter()
+            .flat_map(|tokens| tokens.bonus[bonus_type].iter().copied())
+            .collect::<Vec<_>>();
+        violations.extend(get_count_differences(
+            &format!("{:?} bonus tokens", bonus_type),
+            expected,
+            &actual,
+        ));
+    }
+
+    // The market is always refilled, unless the deck ran out
+    if market.cards.len() != game_config.market_size
+        && !(deck.cards.is_empty() && market.cards.len() < game_config.market_size)
+    {
+        violations.push(format!(
+            "market has {} cards with {} cards left in the deck",
+            market.cards.len(),
+            deck.cards.len()
+        ));
+    }
+
+    // Card entities agree with the resources
+    violations.extend(get_indexed_card_differences(
+        "deck",
+        &deck.cards,
+        deck_cards_query.iter().map(|(card, dc)| (dc.0, card.0)),
+    ));
+
+    violations.extend(get_indexed_card_differences(
+        "market",
+        &market.cards,
+        market_cards_query.iter().map(|(card, mc)| (mc.0, card.0)),
+    ));
+
+    for (name, goods_hand, camels_hand, _, active_player) in players.iter() {
+        let hand_cards = goods_hand
+            .0
+            .iter()
+            .map(|g| CardType::Good(*g))
+            .collect::<Vec<_>>();
+
+        let (goods_differences, num_camel_entities) = if active_player.is_some() {
+            (
+                get_indexed_card_differences(
+                    &format!("{} goods hand", name.0),
+                    &hand_cards,
+                    active_goods_cards_query
+                        .iter()
+                        .map(|(card, gc)| (gc.0, card.0)),
+                ),
+                active_camel_cards_query.iter().count(),
+            )
+        } else {
+            (
+                get_indexed_card_differences(
+                    &format!("{} goods hand", name.0),
+                    &hand_cards,
+                    inactive_goods_cards_query
+                        .iter()
+                        .map(|(card, gc)| (gc.0, card.0)),
+                ),
+                inactive_camel_cards_query.iter().count(),
+            )
+        };
+
+        violations.extend(goods_differences);
+
+        if num_camel_entities != camels_hand.0 {
+            violations.push(format!(
+                "{} has {} camels but {} camel card entities",
+                name.0, camels_hand.0, num_camel_entities
+            ));
+        }
+    }
+
+    if !violations.is_empty() {
+        panic!(
+            "Game invariants violated:\n{}",
+            violations.iter().map(|v| format!("  - {}", v)).join("\n")
+        );
+    }
+}
+
+fn get_count_differences<T: std::hash::Hash + Eq + std::fmt::Debug>(
+    name: &str,
+    expected: &[T],
+    actual: &[T],
+) -> Vec<String> {
+    let expected_counts: HashMap<&T, usize> = expected.iter().counts();
+    let actual_counts: HashMap<&T, usize> = actual.iter().counts();
+
+    expected_counts
+        .keys()
+        .chain(actual_counts.keys())
+        .unique()
+        .filter_map(|item| {
+            let expected_count = *expected_counts.get(item).unwrap_or(&0);
+            let actual_count = *actual_counts.get(item).unwrap_or(&0);
+            (expected_count != actual_count).then(|| {
+                format!(
+                    "{}: expected {} of {:?}, found {}",
+                    name, expected_count, item, actual_count
+                )
+            })
+        })
+        .collect()
+}
+
+fn get_indexed_card_differences(
+    name: &str,
+    expected: &[CardType],
+    entity_cards: impl Iterator<Item = (usize, CardType)>,
+) -> Vec<String> {
+    let entity_cards = entity_cards
+        .sorted_by_key(|(idx, _)| *idx)
+        .collect::<Vec<_>>();
+    let mut differences = vec![];
+
+    if entity_cards.len() != expected.len() {
+        differences.push(format!(
+            "{}: resource has {} cards but there are {} card entities",
+            name,
+            expected.len(),
+            entity_cards.len()
+        ));
+    }
+
+    for (idx, card_type) in entity_cards {
+        match expected.get(idx) {
+            Some(expected_card_type) if *expected_card_type == card_type => {}
+            expected_card_type => differences.push(format!(
+                "{}: card entity at index {} is {:?}, resource has {:?}",
+                name, idx, card_type, expected_card_type
+            )),
+        }
+    }
+
+    differences
+}
+
+pub struct InvariantCheckPlugin;
+
+impl Plugin for InvariantCheckPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InvariantCheckState>()
+            .add_system_set(
+                SystemSet::on_enter(AppState::InGame).with_system(request_invariant_check),
+            )
+            .add_system_set(
+                SystemSet::on_enter(AppState::AiTurn).with_system(request_invariant_check),
+            )
+            .add_system_set(
+                SystemSet::on_enter(AppState::GameOver).with_system(request_invariant_check),
+            )
+            // Runs in the last stage so that all of the turn's entity changes have been applied
+            .add_system_to_stage(CoreStage::Last, check_game_invariants);
+    }
+}
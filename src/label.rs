@@ -4,4 +4,6 @@ use bevy::prelude::SystemLabel;
 pub enum Label {
     ConfirmTurnEventWriter,
     ConfirmTurnEventReader,
+    MoveValidityWriter,
+    DailyChallengeResultWriter,
 }
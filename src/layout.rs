@@ -0,0 +1,162 @@
+use bevy::prelude::*;
+
+use crate::game::{is_bottom_player, ActivePlayer, DeckCard, HumanPlayer};
+use crate::game_resources::card::{
+    ActivePlayerCamelCard, ActivePlayerGoodsCard, Card, DiscardPileCard, InactivePlayerCamelCard,
+    InactivePlayerGoodsCard, MarketCard,
+};
+use crate::game_resources::game_config::GameConfig;
+use crate::positioning::{Layout, Orientation};
+use crate::resources::GameState;
+use crate::states::AppState;
+
+// The space kept clear for the game UI's buttons: a column on the right of a landscape window, or a row along the
+// bottom of a portrait one. See game_ui.rs.
+pub const GAME_UI_PANEL_WIDTH: f32 = 170.0;
+pub const GAME_UI_PANEL_HEIGHT: f32 = 200.0;
+
+// Picks the layout for the window's orientation and the game's hand and market sizes, and zooms the camera so that
+// the whole layout fits in the window beside the game UI. Runs again whenever the window is resized or a game with
+// different rules is set up.
+fn update_layout(
+    windows: Res<Windows>,
+    game_config: Res<GameConfig>,
+    mut layout: ResMut<Layout>,
+    mut last_window_size: Local<Option<Vec2>>,
+    mut camera_query: Query<(&mut OrthographicProjection, &mut Transform), With<Camera2d>>,
+) {
+    let window = match windows.get_primary() {
+        Some(window) => window,
+        None => return,
+    };
+    let window_size = Vec2::new(window.width(), window.height());
+
+    if (*last_window_size == Some(window_size) && !game_config.is_changed())
+        || window_size.min_element() <= 0.0
+    {
+        return;
+    }
+
+    let (mut projection, mut camera_transform) = match camera_query.get_single_mut() {
+        Ok(camera) => camera,
+        Err(_) => return,
+    };
+    *last_window_size = Some(window_size);
+
+    let orientation = Orientation::from_window_size(window_size.x, window_size.y);
+
+    // Only replaced when it changes, since the cards and tokens are moved whenever the layout changes
+    let new_layout = Layout::new(orientation, &game_config);
+    if *layout != new_layout {
+        *layout = new_layout;
+    }
+
+    let ui_panel_size = match orientation {
+        Orientation::Landscape => Vec2::new(GAME_UI_PANEL_WIDTH, 0.0),
+        Orientation::Portrait => Vec2::new(0.0, GAME_UI_PANEL_HEIGHT),
+    };
+    let available_size = (window_size - ui_panel_size).max(Vec2::ONE);
+    let bounds_size = layout.bounds_max - layout.bounds_min;
+    let scale = (bounds_size / available_size).max_element();
+
+    // The layout is centred in the space left beside the UI panel
+    let bounds_center = 0.5 * (layout.bounds_min + layout.bounds_max);
+    let ui_panel_offset = 0.5 * scale * Vec2::new(ui_panel_size.x, -ui_panel_size.y);
+
+    projection.scale = scale;
+    camera_transform.translation =
+        (bounds_center + ui_panel_offset).extend(camera_transform.translation.z);
+}
+
+// Moves every card to where the new layout puts it. Cards that are part way through a tween carry on to where
+// they were headed, and are moved when the turn after it starts.
+#[allow(clippy::type_complexity)]
+fn relayout_cards(
+    layout: Res<Layout>,
+    game_state: Res<GameState>,
+    game_config: Res<GameConfig>,
+    active_player_query: Query<Option<&HumanPlayer>, With<ActivePlayer>>,
+    mut card_query: Query<
+        (
+            &mut Transform,
+            Option<&DeckCard>,
+            Option<&MarketCard>,
+            Option<&DiscardPileCard>,
+            Option<&ActivePlayerGoodsCard>,
+            Option<&ActivePlayerCamelCard>,
+            Option<&InactivePlayerGoodsCard>,
+            Option<&InactivePlayerCamelCard>,
+        ),
+        Or<(With<Card>, With<DiscardPileCard>)>,
+    >,
+) {
+    if !layout.is_changed() {
+        return;
+    }
+
+    let is_active_player_at_bottom = match active_player_query.get_single() {
+        Ok(human_player) => is_bottom_player(&game_state, true, human_player.is_some()),
+        Err(_) => return,
+    };
+
+    // Face down hands at the top of the screen are laid out like the computer's
+    let get_goods_card_translation = |idx: usize, is_at_bottom: bool| {
+        if is_at_bottom {
+            layout.get_active_player_goods_card_translation(idx)
+        } else {
+            layout.get_ai_player_goods_card_translation(idx)
+        }
+    };
+    let get_camel_card_translation = |idx: usize, is_at_bottom: bool| {
+        if is_at_bottom {
+            layout.get_active_player_camel_card_translation(idx)
+        } else {
+            layout.get_opponent_camel_hand_translation()
+        }
+    };
+
+    for (
+        mut transform,
+        deck_card,
+        market_card,
+        discard_pile_card,
+        active_goods_card,
+        active_camel_card,
+        inactive_goods_card,
+        inactive_camel_card,
+    ) in card_query.iter_mut()
+    {
+        let translation = if let Some(deck_card) = deck_card {
+            layout.get_deck_card_translation(deck_card.0)
+        } else if let Some(market_card) = market_card {
+            layout.get_market_card_translation(market_card.0, game_config.market_size)
+        } else if discard_pile_card.is_some() {
+            layout.get_discard_pile_translation()
+        } else if let Some(goods_card) = active_goods_card {
+            get_goods_card_translation(goods_card.0, is_active_player_at_bottom)
+        } else if let Some(camel_card) = active_camel_card {
+            get_camel_card_translation(camel_card.0, is_active_player_at_bottom)
+        } else if let Some(goods_card) = inactive_goods_card {
+            get_goods_card_translation(goods_card.0, !is_active_player_at_bottom)
+        } else if let Some(camel_card) = inactive_camel_card {
+            get_camel_card_translation(camel_card.0, !is_active_player_at_bottom)
+        } else {
+            continue;
+        };
+
+        // Cards keep their depth, so the ones sold onto the discard pile stay in order
+        transform.translation = translation.truncate().extend(transform.translation.z);
+    }
+}
+
+pub struct LayoutPlugin;
+
+impl Plugin for LayoutPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Layout>().add_system(update_layout);
+
+        for state in [AppState::InGame, AppState::AiTurn] {
+            app.add_system_set(SystemSet::on_update(state).with_system(relayout_cards));
+        }
+    }
+}
@@ -0,0 +1,295 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+
+use crate::settings::Settings;
+
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+// English is built in, so there is always text to show while the chosen language loads, and for any text a
+// translation leaves out
+const DEFAULT_LOCALE: &str = include_str!("../assets/locales/en.locale.ron");
+
+const LANGUAGE_LIST_PATH: &str = "locales/languages.list.ron";
+
+// The text for each key in one language, loaded from a `.locale.ron` file in assets/locales. Placeholders in
+// braces, like {name}, are filled in when the text is shown.
+#[derive(Clone, Debug, Deserialize, TypeUuid)]
+#[uuid = "3d5b8a61-7c0e-4f29-b1a4-92e6d0c4f7a3"]
+pub struct Locale {
+    // The language's own name for itself, shown in the settings menu
+    pub language_name: String,
+    pub strings: HashMap<String, String>,
+}
+
+// The codes of the languages offered in the settings menu, in order. Each has a `<code>.locale.ron` file.
+#[derive(Clone, Debug, Deserialize, TypeUuid)]
+#[uuid = "a8f2c4e7-15b9-4d63-8e0f-6c7a3b91d2e5"]
+pub struct LanguageList(pub Vec<String>);
+
+#[derive(Default)]
+pub struct LocaleLoader;
+
+impl AssetLoader for LocaleLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let locale = ron::de::from_bytes::<Locale>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(locale));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["locale.ron"]
+    }
+}
+
+#[derive(Default)]
+pub struct LanguageListLoader;
+
+impl AssetLoader for LanguageListLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let language_list = ron::de::from_bytes::<LanguageList>(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(language_list));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["list.ron"]
+    }
+}
+
+// The text to show in the language chosen in the settings
+pub struct Localization {
+    default_locale: Locale,
+    // None until the chosen language has loaded, or if it is the default
+    locale: Option<Locale>,
+    language: String,
+    // The code and name of each language that has loaded, in the order of the language list
+    languages: Vec<(String, String)>,
+}
+
+impl Default for Localization {
+    fn default() -> Self {
+        let default_locale: Locale =
+            ron::from_str(DEFAULT_LOCALE).expect("The built in English locale should be valid");
+
+        Self {
+            languages: vec![(
+                DEFAULT_LANGUAGE.to_string(),
+                default_locale.language_name.clone(),
+            )],
+            default_locale,
+            locale: None,
+            language: DEFAULT_LANGUAGE.to_string(),
+        }
+    }
+}
+
+impl Localization {
+    pub fn try_get(&self, key: &str) -> Option<&str> {
+        self.locale
+            .as_ref()
+            .and_then(|locale| locale.strings.get(key))
+            .or_else(|| self.default_locale.strings.get(key))
+            .map(|text| text.as_str())
+    }
+
+    // A missing key is shown as it is, so it can be spotted and added to the locale files
+    pub fn get(&self, key: &str) -> String {
+        self.try_get(key).unwrap_or(key).to_string()
+    }
+
+    pub fn format(&self, key: &str, args: &[(&str, &dyn fmt::Display)]) -> String {
+        args.iter().fold(self.get(key), |text, (name, value)| {
+            text.replace(&format!("{{{}}}", name), &value.to_string())
+        })
+    }
+
+    // Goods are named in the deck definitions, which may define goods the locale files don't know about
+    pub fn get_good_name(&self, name: &str) -> String {
+        self.try_get(&format!("good.{}", name))
+            .unwrap_or(name)
+            .to_string()
+    }
+
+    pub fn get_language_name(&self, language: &str) -> String {
+        self.languages
+            .iter()
+            .find(|(code, _)| code == language)
+            .map_or_else(|| language.to_string(), |(_, name)| name.clone())
+    }
+
+    // The language after the given one in the settings menu
+    pub fn get_next_language(&self, language: &str) -> String {
+        let index = self
+            .languages
+            .iter()
+            .position(|(code, _)| code == language)
+            .map_or(0, |index| (index + 1) % self.languages.len());
+
+        self.languages[index].0.clone()
+    }
+}
+
+// Marks text that is the same whenever it is shown, so it can be translated again if the language changes. Goes on
+// the Text entity, or on a button whose child shows the text.
+#[derive(Component)]
+pub struct LocalizedText(pub &'static str);
+
+struct LocaleHandles {
+    language_list: Handle<LanguageList>,
+    locales: Vec<(String, Handle<Locale>)>,
+}
+
+fn load_language_list(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(LocaleHandles {
+        language_list: asset_server.load(LANGUAGE_LIST_PATH),
+        locales: vec![],
+    });
+}
+
+fn load_locales(
+    asset_server: Res<AssetServer>,
+    language_lists: Res<Assets<LanguageList>>,
+    mut ev_language_list: EventReader<AssetEvent<LanguageList>>,
+    mut locale_handles: ResMut<LocaleHandles>,
+) {
+    for ev in ev_language_list.iter() {
+        let handle = match ev {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => handle,
+            AssetEvent::Removed { .. } => continue,
+        };
+
+        if *handle != locale_handles.language_list {
+            continue;
+        }
+
+        if let Some(language_list) = language_lists.get(handle) {
+            locale_handles.locales = language_list
+                .0
+                .iter()
+                .map(|code| {
+                    (
+                        code.clone(),
+                        asset_server.load(&format!("locales/{}.locale.ron", code)),
+                    )
+                })
+                .collect();
+        }
+    }
+}
+
+// Switches to the chosen language once it has loaded, and again when a locale file changes
+fn update_localization(
+    settings: Res<Settings>,
+    locales: Res<Assets<Locale>>,
+    locale_handles: Res<LocaleHandles>,
+    mut ev_locale: EventReader<AssetEvent<Locale>>,
+    mut localization: ResMut<Localization>,
+) {
+    let has_locale_changed = ev_locale.iter().count() > 0;
+
+    if !has_locale_changed
+        && !locale_handles.is_changed()
+        && settings.language == localization.language
+    {
+        return;
+    }
+
+    let loaded_locales = locale_handles
+        .locales
+        .iter()
+        .filter_map(|(code, handle)| locales.get(handle).map(|locale| (code, locale)))
+        .collect::<Vec<_>>();
+
+    let mut languages = loaded_locales
+        .iter()
+        .map(|(code, locale)| (code.to_string(), locale.language_name.clone()))
+        .collect::<Vec<_>>();
+
+    if !languages.iter().any(|(code, _)| code == DEFAULT_LANGUAGE) {
+        languages.insert(
+            0,
+            (
+                DEFAULT_LANGUAGE.to_string(),
+                localization.default_locale.language_name.clone(),
+            ),
+        );
+    }
+
+    let locale = loaded_locales
+        .iter()
+        .find(|(code, _)| **code == settings.language)
+        .map(|(_, locale)| (*locale).clone());
+
+    // Other languages keep showing the default until they load
+    if settings.language != DEFAULT_LANGUAGE
+        && locale.is_none()
+        && languages == localization.languages
+    {
+        return;
+    }
+
+    localization.language = settings.language.clone();
+    localization.locale = locale;
+    localization.languages = languages;
+}
+
+fn update_localized_text(
+    localization: Res<Localization>,
+    localized_text_query: Query<(Entity, &LocalizedText, Option<&Children>)>,
+    mut text_query: Query<&mut Text>,
+) {
+    if !localization.is_changed() {
+        return;
+    }
+
+    for (entity, localized_text, children) in localized_text_query.iter() {
+        let value = localization.get(localized_text.0);
+
+        let mut text_entities = vec![entity];
+        if let Some(children) = children {
+            text_entities.extend(children.iter().copied());
+        }
+
+        for text_entity in text_entities {
+            if let Ok(mut text) = text_query.get_mut(text_entity) {
+                text.sections[0].value = value.clone();
+            }
+        }
+    }
+}
+
+pub struct LocalizationPlugin;
+
+impl Plugin for LocalizationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Localization>()
+            .add_asset::<Locale>()
+            .add_asset::<LanguageList>()
+            .init_asset_loader::<LocaleLoader>()
+            .init_asset_loader::<LanguageListLoader>()
+            .add_startup_system(load_language_list)
+            .add_system(load_locales)
+            .add_system(update_localization.after(load_locales))
+            .add_system(update_localized_text.after(update_localization));
+    }
+}
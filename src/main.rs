@@ -1,25 +1,46 @@
 mod ai;
+mod animation;
+mod card_focus;
 mod card_selection;
 mod common_systems;
+mod daily_challenge;
 mod event;
 mod game;
 mod game_resources;
+mod hand_sorting;
+#[cfg(debug_assertions)]
+mod invariants;
 mod label;
+mod layout;
+mod localization;
 mod move_execution;
+mod move_history;
 mod move_validation;
 mod positioning;
+mod puzzle;
 mod resources;
+mod score_breakdown;
+mod settings;
 mod states;
+mod storage;
+#[cfg(test)]
+mod test_fixtures;
+mod token_stacks;
+mod tutorial;
 mod ui;
 
 use bevy::{prelude::*, render::camera::ScalingMode};
 use bevy_interact_2d::{Group, InteractionSource};
 use event::EventsPlugin;
 use game::*;
+use layout::LayoutPlugin;
+use localization::LocalizationPlugin;
 use resources::GameState;
+use settings::Settings;
 use states::{AppState, TurnState};
 use ui::game_ui::GameUiPlugin;
 use ui::main_menu::MainMenuPlugin;
+use ui::settings_menu::SettingsMenuPlugin;
 
 #[allow(clippy::type_complexity)]
 
@@ -31,12 +52,16 @@ fn main() {
             ..default()
         })
         .init_resource::<GameState>()
+        .insert_resource(Settings::load())
         .add_plugins(DefaultPlugins)
         .add_plugin(EventsPlugin)
         .add_state(AppState::MainMenu)
         .add_state(TurnState::None)
         .add_startup_system(setup_app)
+        .add_plugin(LocalizationPlugin)
+        .add_plugin(LayoutPlugin)
         .add_plugin(MainMenuPlugin)
+        .add_plugin(SettingsMenuPlugin)
         .add_plugin(GamePlugin)
         .add_plugin(GameUiPlugin)
         .run();
@@ -45,12 +70,9 @@ fn main() {
 fn setup_app(mut commands: Commands) {
     commands
         .spawn_bundle(Camera2dBundle {
+            // Zoomed to fit the table in the window by the layout
             projection: OrthographicProjection {
-                scaling_mode: ScalingMode::Auto {
-                    min_width: 800.,
-                    min_height: 600.,
-                },
-                scale: 2.0,
+                scaling_mode: ScalingMode::WindowSize,
                 ..default()
             },
             ..default()
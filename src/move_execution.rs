@@ -1,29 +1,25 @@
 use bevy::ecs::schedule::ShouldRun;
 use bevy::prelude::*;
-use bevy_tweening::lens::{TransformPositionLens, TransformRotationLens};
-use bevy_tweening::{
-    Animator, EaseFunction, Tracks, Tween, TweenCompleted, TweeningPlugin, TweeningType,
-};
+use bevy_tweening::{Animator, TweenCompleted, TweeningPlugin};
 use itertools::Itertools;
 use std::cmp::Reverse;
-use std::time::Duration;
 
+use crate::animation::{animate_transform, AnimationSpeed};
 use crate::card_selection::SelectedCard;
-use crate::event::ConfirmTurnEvent;
+use crate::event::{ConfirmTurnEvent, TokensAwardedEvent};
 use crate::game::*;
 use crate::game_resources::card::*;
 use crate::game_resources::deck::Deck;
+use crate::game_resources::deck_definition::DeckDefinition;
 use crate::game_resources::discard_pile::DiscardPile;
+use crate::game_resources::game_config::GameConfig;
 use crate::game_resources::market::Market;
 use crate::game_resources::tokens::{BonusType, Tokens};
 use crate::label::Label;
 use crate::move_validation::MoveType;
-use crate::positioning::{
-    get_active_player_camel_card_translation, get_active_player_goods_card_translation,
-    get_ai_player_goods_card_translation, get_market_card_translation,
-    get_opponent_camel_hand_translation, DISCARD_PILE_POS,
-};
+use crate::positioning::Layout;
 use crate::resources::GameState;
+use crate::settings::Settings;
 use crate::states::AppState;
 
 #[allow(clippy::too_many_arguments)]
@@ -33,6 +29,8 @@ fn handle_take_single_good_move_confirmed(
     mut ev_confirm_turn: EventReader<ConfirmTurnEvent>,
     mut deck: ResMut<Deck>,
     mut market: ResMut<Market>,
+    game_config: Res<GameConfig>,
+    deck_definition: Res<DeckDefinition>,
     selected_market_cards_query: Query<
         (Entity, &Card, &MarketCard, &Transform),
         With<SelectedCard>,
@@ -41,6 +39,8 @@ fn handle_take_single_good_move_confirmed(
     mut deck_cards_query: Query<(Entity, &DeckCard, &Card, &Transform, &mut Handle<Image>)>,
     mut tween_state: ResMut<TweenState>,
     mut game_state: ResMut<GameState>,
+    settings: Res<Settings>,
+    layout: Res<Layout>,
     app_state: Res<State<AppState>>,
 ) {
     for _ev in ev_confirm_turn
@@ -63,27 +63,26 @@ fn handle_take_single_good_move_confirmed(
 
         // TODO: implement these as methods on a Player component?
         let end = if is_ai_turn {
-            get_ai_player_goods_card_translation(active_player_goods_hand.0.len() - 1)
+            layout.get_ai_player_goods_card_translation(active_player_goods_hand.0.len() - 1)
         } else {
-            get_active_player_goods_card_translation(active_player_goods_hand.0.len() - 1)
+            layout.get_active_player_goods_card_translation(active_player_goods_hand.0.len() - 1)
         };
 
-        let tween = Tween::new(
-            EaseFunction::QuadraticInOut,
-            TweeningType::Once,
-            Duration::from_secs(2),
-            TransformPositionLens {
-                start: transform.translation,
-                end,
+        animate_transform(
+            &mut commands,
+            &mut tween_state,
+            settings.animation_speed,
+            card_entity,
+            transform,
+            Transform {
+                translation: end,
+                ..*transform
             },
-        )
-        .with_completed_event(1);
-
-        tween_state.tweening_entities.push(card_entity);
+            1,
+        );
 
         commands
             .entity(card_entity)
-            .insert(Animator::new(tween))
             .remove::<MarketCard>()
             .insert(ActivePlayerGoodsCard(active_player_goods_hand.0.len() - 1));
 
@@ -98,25 +97,26 @@ fn handle_take_single_good_move_confirmed(
                     .unwrap();
 
             // Update the sprite to show the face
-            *top_deck_card_texture = asset_server.load(&card.0.get_card_texture());
+            *top_deck_card_texture =
+                asset_server.load(&card.0.get_card_texture(&deck_definition.goods));
 
             // Tween to the market card position
-            let second_tween = Tween::new(
-                EaseFunction::QuadraticInOut,
-                TweeningType::Once,
-                Duration::from_secs(2),
-                TransformPositionLens {
-                    start: deck_card_transform.translation,
-                    end: get_market_card_translation(market_card.0),
+            animate_transform(
+                &mut commands,
+                &mut tween_state,
+                settings.animation_speed,
+                deck_card_entity,
+                deck_card_transform,
+                Transform {
+                    translation: layout
+                        .get_market_card_translation(market_card.0, game_config.market_size),
+                    ..*deck_card_transform
                 },
-            )
-            .with_completed_event(2);
-
-            tween_state.tweening_entities.push(deck_card_entity);
+                2,
+            );
 
             commands
                 .entity(deck_card_entity)
-                .insert(Animator::new(second_tween))
                 .remove::<DeckCard>()
                 .insert(MarketCard(market_card.0));
         } else {
@@ -132,11 +132,15 @@ fn handle_take_all_camels_move_confirmed(
     mut ev_confirm_turn: EventReader<ConfirmTurnEvent>,
     mut deck: ResMut<Deck>,
     mut market: ResMut<Market>,
+    game_config: Res<GameConfig>,
+    deck_definition: Res<DeckDefinition>,
     selected_camel_market_cards_query: Query<(Entity, &MarketCard, &Transform), With<SelectedCard>>,
     mut active_player_query: Query<&mut CamelsHandOwner, With<ActivePlayer>>,
     mut deck_cards_query: Query<(Entity, &DeckCard, &Card, &Transform, &mut Handle<Image>)>,
     mut tween_state: ResMut<TweenState>,
     mut game_state: ResMut<GameState>,
+    settings: Res<Settings>,
+    layout: Res<Layout>,
     app_state: Res<State<AppState>>,
 ) {
     for _ev in ev_confirm_turn
@@ -158,47 +162,34 @@ fn handle_take_all_camels_move_confirmed(
 
             // TODO: implement these as methods on a Player component?
             let end = if is_ai_turn {
-                get_opponent_camel_hand_translation()
+                layout.get_opponent_camel_hand_translation()
             } else {
-                get_active_player_camel_card_translation(active_player_camel_hand.0 - 1)
+                layout.get_active_player_camel_card_translation(active_player_camel_hand.0 - 1)
             };
 
-            let tween = Tween::new(
-                EaseFunction::QuadraticInOut,
-                TweeningType::Once,
-                Duration::from_secs(2),
-                TransformPositionLens {
-                    start: transform.translation,
-                    end,
-                },
-            )
-            .with_completed_event(1);
-
-            let mut tracks_vec = vec![tween];
-
-            if is_ai_turn {
-                let rotation_tween = Tween::new(
-                    EaseFunction::QuadraticInOut,
-                    TweeningType::Once,
-                    Duration::from_secs(2),
-                    TransformRotationLens {
-                        start: transform.rotation,
-                        end: Quat::from_rotation_z((180.0_f32).to_radians()),
-                    },
-                )
-                .with_completed_event(1);
-                tracks_vec.push(rotation_tween);
-
-                tween_state.tweening_entities.push(card_entity);
-            }
-
-            let tracks = Tracks::new(tracks_vec);
+            // The computer's camels are turned upside down, like the rest of its hand
+            let rotation = if is_ai_turn {
+                Quat::from_rotation_z((180.0_f32).to_radians())
+            } else {
+                transform.rotation
+            };
 
-            tween_state.tweening_entities.push(card_entity);
+            animate_transform(
+                &mut commands,
+                &mut tween_state,
+                settings.animation_speed,
+                card_entity,
+                transform,
+                Transform {
+                    translation: end,
+                    rotation,
+                    ..*transform
+                },
+                1,
+            );
 
             commands
                 .entity(card_entity)
-                .insert(Animator::new(tracks))
                 .remove::<MarketCard>()
                 .insert(ActivePlayerCamelCard(active_player_camel_hand.0 - 1));
 
@@ -216,25 +207,26 @@ fn handle_take_all_camels_move_confirmed(
                         .unwrap();
 
                 // Update the sprite to show the face
-                *top_deck_card_texture = asset_server.load(&card.0.get_card_texture());
+                *top_deck_card_texture =
+                    asset_server.load(&card.0.get_card_texture(&deck_definition.goods));
 
                 // Tween to the market card position
-                let second_tween = Tween::new(
-                    EaseFunction::QuadraticInOut,
-                    TweeningType::Once,
-                    Duration::from_secs(2),
-                    TransformPositionLens {
-                        start: deck_card_transform.translation,
-                        end: get_market_card_translation(market_card.0),
+                animate_transform(
+                    &mut commands,
+                    &mut tween_state,
+                    settings.animation_speed,
+                    deck_card_entity,
+                    deck_card_transform,
+                    Transform {
+                        translation: layout
+                            .get_market_card_translation(market_card.0, game_config.market_size),
+                        ..*deck_card_transform
                     },
-                )
-                .with_completed_event(2);
-
-                tween_state.tweening_entities.push(deck_card_entity);
+                    2,
+                );
 
                 commands
                     .entity(deck_card_entity)
-                    .insert(Animator::new(second_tween))
                     .remove::<DeckCard>()
                     .insert(MarketCard(market_card.0));
             } else {
@@ -249,6 +241,7 @@ fn handle_exchange_goods_move_confirmed(
     mut commands: Commands,
     mut ev_confirm_turn: EventReader<ConfirmTurnEvent>,
     mut market: ResMut<Market>,
+    game_config: Res<GameConfig>,
     mut active_player_query: Query<(&mut GoodsHandOwner, &mut CamelsHandOwner), With<ActivePlayer>>,
     active_player_selected_camel_cards: Query<
         (Entity, &Transform),
@@ -261,6 +254,8 @@ fn handle_exchange_goods_move_confirmed(
     >,
     selected_market_goods_cards_query: Query<(Entity, &MarketCard, &Transform), With<SelectedCard>>,
     mut tween_state: ResMut<TweenState>,
+    settings: Res<Settings>,
+    layout: Res<Layout>,
     app_state: Res<State<AppState>>,
 ) {
     let is_ai_turn = *app_state.current() == AppState::AiTurn;
@@ -288,47 +283,46 @@ fn handle_exchange_goods_move_confirmed(
                 CardType::Good(good_type_removed_from_hand),
             );
 
-            let tween_hand_to_market = Tween::new(
-                EaseFunction::QuadraticInOut,
-                TweeningType::Once,
-                Duration::from_secs(2),
-                TransformPositionLens {
-                    start: player_good.2.translation,
-                    end: get_market_card_translation(market_good.1 .0),
+            animate_transform(
+                &mut commands,
+                &mut tween_state,
+                settings.animation_speed,
+                player_good.0,
+                player_good.2,
+                Transform {
+                    translation: layout
+                        .get_market_card_translation(market_good.1 .0, game_config.market_size),
+                    ..*player_good.2
                 },
-            )
-            .with_completed_event(1);
-
-            tween_state.tweening_entities.push(player_good.0);
+                1,
+            );
 
             commands
                 .entity(player_good.0)
-                .insert(Animator::new(tween_hand_to_market))
                 .remove::<ActivePlayerGoodsCard>()
                 .insert(MarketCard(market_good.1 .0));
 
             let end = if is_ai_turn {
-                get_ai_player_goods_card_translation(player_good.1 .0)
+                layout.get_ai_player_goods_card_translation(player_good.1 .0)
             } else {
-                get_active_player_goods_card_translation(player_good.1 .0)
+                layout.get_active_player_goods_card_translation(player_good.1 .0)
             };
 
-            let tween_market_to_hand = Tween::new(
-                EaseFunction::QuadraticInOut,
-                TweeningType::Once,
-                Duration::from_secs(2),
-                TransformPositionLens {
-                    start: market_good.2.translation,
-                    end,
+            animate_transform(
+                &mut commands,
+                &mut tween_state,
+                settings.animation_speed,
+                market_good.0,
+                market_good.2,
+                Transform {
+                    translation: end,
+                    ..*market_good.2
                 },
-            )
-            .with_completed_event(2);
-
-            tween_state.tweening_entities.push(market_good.0);
+                2,
+            );
 
             commands
                 .entity(market_good.0)
-                .insert(Animator::new(tween_market_to_hand))
                 .remove::<MarketCard>()
                 .insert(ActivePlayerGoodsCard(player_good.1 .0));
         }
@@ -346,47 +340,46 @@ fn handle_exchange_goods_move_confirmed(
                 .push(good_type_removed_from_market.into_good_type());
             market.cards.insert(market_good.1 .0, CardType::Camel);
 
-            let tween_camel_to_market = Tween::new(
-                EaseFunction::QuadraticInOut,
-                TweeningType::Once,
-                Duration::from_secs(2),
-                TransformPositionLens {
-                    start: camel.1.translation,
-                    end: get_market_card_translation(market_good.1 .0),
+            animate_transform(
+                &mut commands,
+                &mut tween_state,
+                settings.animation_speed,
+                camel.0,
+                camel.1,
+                Transform {
+                    translation: layout
+                        .get_market_card_translation(market_good.1 .0, game_config.market_size),
+                    ..*camel.1
                 },
-            )
-            .with_completed_event(3);
-
-            tween_state.tweening_entities.push(camel.0);
+                3,
+            );
 
             commands
                 .entity(camel.0)
-                .insert(Animator::new(tween_camel_to_market))
                 .remove::<ActivePlayerCamelCard>()
                 .insert(MarketCard(market_good.1 .0));
 
             let end = if is_ai_turn {
-                get_ai_player_goods_card_translation(goods_hand_owner.0.len() - 1)
+                layout.get_ai_player_goods_card_translation(goods_hand_owner.0.len() - 1)
             } else {
-                get_active_player_goods_card_translation(goods_hand_owner.0.len() - 1)
+                layout.get_active_player_goods_card_translation(goods_hand_owner.0.len() - 1)
             };
 
-            let tween_market_to_hand = Tween::new(
-                EaseFunction::QuadraticInOut,
-                TweeningType::Once,
-                Duration::from_secs(2),
-                TransformPositionLens {
-                    start: market_good.2.translation,
-                    end,
+            animate_transform(
+                &mut commands,
+                &mut tween_state,
+                settings.animation_speed,
+                market_good.0,
+                market_good.2,
+                Transform {
+                    translation: end,
+                    ..*market_good.2
                 },
-            )
-            .with_completed_event(4);
-
-            tween_state.tweening_entities.push(market_good.0);
+                4,
+            );
 
             commands
                 .entity(market_good.0)
-                .insert(Animator::new(tween_market_to_hand))
                 .remove::<MarketCard>()
                 .insert(ActivePlayerGoodsCard(goods_hand_owner.0.len() - 1));
         }
@@ -416,27 +409,26 @@ fn handle_exchange_goods_move_confirmed(
 
             if index_in_hand != correct_index {
                 let end = if is_ai_turn {
-                    get_opponent_camel_hand_translation()
+                    layout.get_opponent_camel_hand_translation()
                 } else {
-                    get_active_player_camel_card_translation(correct_index)
+                    layout.get_active_player_camel_card_translation(correct_index)
                 };
 
-                let tween_shift_in_hand = Tween::new(
-                    EaseFunction::QuadraticInOut,
-                    TweeningType::Once,
-                    Duration::from_secs(2),
-                    TransformPositionLens {
-                        start: transform.translation,
-                        end,
+                animate_transform(
+                    &mut commands,
+                    &mut tween_state,
+                    settings.animation_speed,
+                    e,
+                    transform,
+                    Transform {
+                        translation: end,
+                        ..*transform
                     },
-                )
-                .with_completed_event(e.to_bits());
-
-                tween_state.tweening_entities.push(e);
+                    e.to_bits(),
+                );
 
                 commands
                     .entity(e)
-                    .insert(Animator::new(tween_shift_in_hand))
                     .remove::<ActivePlayerCamelCard>()
                     .insert(ActivePlayerCamelCard(correct_index));
             }
@@ -448,9 +440,12 @@ fn handle_sell_goods_move_confirmed(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut ev_confirm_turn: EventReader<ConfirmTurnEvent>,
+    mut ev_tokens_awarded: EventWriter<TokensAwardedEvent>,
     mut discard_pile: ResMut<DiscardPile>,
     mut tween_state: ResMut<TweenState>,
     mut game_tokens: ResMut<Tokens>,
+    game_config: Res<GameConfig>,
+    deck_definition: Res<DeckDefinition>,
     mut active_player_selected_goods_card: Query<
         (
             Entity,
@@ -464,6 +459,8 @@ fn handle_sell_goods_move_confirmed(
     all_active_player_goods_cards: Query<(Entity, &ActivePlayerGoodsCard, &Transform)>,
     mut active_player_query: Query<(&mut GoodsHandOwner, &mut TokensOwner), With<ActivePlayer>>,
     mut game_state: ResMut<GameState>,
+    settings: Res<Settings>,
+    layout: Res<Layout>,
     app_state: Res<State<AppState>>,
 ) {
     for _ev in ev_confirm_turn
@@ -472,6 +469,7 @@ fn handle_sell_goods_move_confirmed(
     {
         let (mut goods_hand_owner, mut tokens_owner) = active_player_query.single_mut();
         let is_ai_turn = *app_state.current() == AppState::AiTurn;
+        let mut goods_tokens = vec![];
 
         for (e, active_player_goods_card, transform, card, mut image) in
             active_player_selected_goods_card
@@ -487,31 +485,32 @@ fn handle_sell_goods_move_confirmed(
 
             if is_ai_turn {
                 // Show face
-                *image = asset_server.load(&card.0.get_card_texture());
+                *image = asset_server.load(&card.0.get_card_texture(&deck_definition.goods));
             }
 
-            let tween_goods_hand_to_discard_pile = Tween::new(
-                EaseFunction::QuadraticInOut,
-                TweeningType::Once,
-                Duration::from_secs(2),
-                TransformPositionLens {
-                    start: transform.translation,
-                    end: DISCARD_PILE_POS,
+            animate_transform(
+                &mut commands,
+                &mut tween_state,
+                settings.animation_speed,
+                e,
+                transform,
+                Transform {
+                    translation: layout.get_discard_pile_translation(),
+                    ..*transform
                 },
-            )
-            .with_completed_event(4);
-
-            tween_state.tweening_entities.push(e);
+                4,
+            );
 
             commands
                 .entity(e)
-                .insert(Animator::new(tween_goods_hand_to_discard_pile))
-                .remove::<ActivePlayerGoodsCard>();
+                .remove::<ActivePlayerGoodsCard>()
+                .insert(DiscardPileCard);
 
             let next_goods_token = game_tokens.goods[sold_card].pop();
 
             if let Some(val) = next_goods_token {
                 tokens_owner.0.goods[sold_card].push(val);
+                goods_tokens.push((sold_card, val));
             }
         }
 
@@ -542,52 +541,54 @@ fn handle_sell_goods_move_confirmed(
             if index_in_hand != correct_index {
                 // TODO: implement these as methods on a Player component?
                 let end = if is_ai_turn {
-                    get_ai_player_goods_card_translation(correct_index)
+                    layout.get_ai_player_goods_card_translation(correct_index)
                 } else {
-                    get_active_player_goods_card_translation(correct_index)
+                    layout.get_active_player_goods_card_translation(correct_index)
                 };
 
-                let tween_shift_in_hand = Tween::new(
-                    EaseFunction::QuadraticInOut,
-                    TweeningType::Once,
-                    Duration::from_secs(2),
-                    TransformPositionLens {
-                        start: transform.translation,
-                        end,
+                animate_transform(
+                    &mut commands,
+                    &mut tween_state,
+                    settings.animation_speed,
+                    e,
+                    transform,
+                    Transform {
+                        translation: end,
+                        ..*transform
                     },
-                )
-                .with_completed_event(e.to_bits());
-
-                tween_state.tweening_entities.push(e);
+                    e.to_bits(),
+                );
 
                 commands
                     .entity(e)
-                    .insert(Animator::new(tween_shift_in_hand))
                     .remove::<ActivePlayerGoodsCard>()
                     .insert(ActivePlayerGoodsCard(correct_index));
             }
         }
 
         let num_cards_sold = active_player_selected_goods_card.iter().count();
-        let bonus_type = match num_cards_sold {
-            d if d == 3 => Some(BonusType::Three),
-            d if d == 4 => Some(BonusType::Four),
-            d if 5 <= d => Some(BonusType::Five),
-            _ => None,
-        };
+        let bonus_type = BonusType::from_num_sold(num_cards_sold);
+
+        let mut awarded_bonus_type = None;
 
         if let Some(bt) = bonus_type {
             if let Some(val) = game_tokens.bonus[bt].pop() {
                 tokens_owner.0.bonus[bt].push(val);
+                awarded_bonus_type = Some(bt);
             }
         }
 
+        ev_tokens_awarded.send(TokensAwardedEvent {
+            goods_tokens,
+            bonus_type: awarded_bonus_type,
+        });
+
         if game_tokens
             .goods
             .iter()
             .filter(|(_, token_values)| token_values.is_empty())
             .count()
-            >= 3
+            >= game_config.num_empty_token_stacks_to_end
         {
             game_state.is_game_over = true;
         }
@@ -612,6 +613,39 @@ pub struct TweenState {
 
 pub struct ScreenTransitionDelayTimer(Timer);
 
+fn setup_screen_transition_delay_timer(
+    settings: Res<Settings>,
+    mut timer: ResMut<ScreenTransitionDelayTimer>,
+) {
+    let delay = settings.animation_speed.get_screen_transition_delay();
+
+    if !delay.is_zero() {
+        timer.0.set_duration(delay);
+    }
+    timer.0.reset();
+}
+
+// Speeds up every tween in progress so the current animation finishes almost immediately
+const SKIPPED_ANIMATION_SPEED: f32 = 100.0;
+
+// Space skips the rest of the turn's animations and the delay before the next turn
+fn skip_animations(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut timer: ResMut<ScreenTransitionDelayTimer>,
+    mut animator_query: Query<&mut Animator<Transform>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::Space) {
+        return;
+    }
+
+    for mut animator in animator_query.iter_mut() {
+        animator.set_speed(SKIPPED_ANIMATION_SPEED);
+    }
+
+    let duration = timer.0.duration();
+    timer.0.set_elapsed(duration);
+}
+
 fn wait_for_tweens_to_finish(
     mut commands: Commands,
     mut ev_tween_completed: EventReader<TweenCompleted>,
@@ -620,6 +654,7 @@ fn wait_for_tweens_to_finish(
     time: Res<Time>,
     mut timer: ResMut<ScreenTransitionDelayTimer>,
     game_state: Res<GameState>,
+    settings: Res<Settings>,
     active_player_query: Query<(Entity, Option<&AiPlayer>), With<ActivePlayer>>,
     inactive_player_query: Query<Entity, (With<Player>, Without<ActivePlayer>)>,
     mut active_player_goods_cards: Query<
@@ -631,21 +666,32 @@ fn wait_for_tweens_to_finish(
     inactive_player_camel_cards: Query<(Entity, &InactivePlayerCamelCard)>,
     mut market_cards_query: Query<(&Card, &mut Handle<Image>), With<MarketCard>>,
     asset_server: Res<AssetServer>,
+    deck_definition: Res<DeckDefinition>,
 ) {
     for ev in ev_tween_completed.iter() {
-        let index = tween_state
+        // Tweens from an earlier turn may still complete after the turn was skipped through
+        if let Some(index) = tween_state
             .tweening_entities
             .iter()
             .position(|e| *e == ev.entity)
-            .unwrap();
-        tween_state.tweening_entities.remove(index);
+        {
+            tween_state.tweening_entities.remove(index);
 
-        if tween_state.tweening_entities.is_empty() {
-            tween_state.did_all_tweens_complete = true;
+            if tween_state.tweening_entities.is_empty() {
+                tween_state.did_all_tweens_complete = true;
+            }
         }
     }
 
-    if tween_state.did_all_tweens_complete && timer.0.tick(time.delta()).just_finished() {
+    // Instant animations don't wait for tweens or the delay timer
+    let is_instant = settings.animation_speed == AnimationSpeed::Instant;
+    let is_ready_to_transition = if is_instant {
+        tween_state.tweening_entities.is_empty()
+    } else {
+        tween_state.did_all_tweens_complete && timer.0.tick(time.delta()).just_finished()
+    };
+
+    if is_ready_to_transition {
         tween_state.did_all_tweens_complete = false;
 
         let (active_player_entity, ai_player_option) = active_player_query.single();
@@ -693,7 +739,7 @@ fn wait_for_tweens_to_finish(
         if is_current_player_ai {
             // Show card faces for all market cards in case AI exchanged
             for (card, mut card_image) in market_cards_query.iter_mut() {
-                *card_image = asset_server.load(&card.0.get_card_texture());
+                *card_image = asset_server.load(&card.0.get_card_texture(&deck_definition.goods));
             }
         }
 
@@ -745,8 +791,13 @@ impl Plugin for MoveExecutionPlugin {
                     .after(Label::ConfirmTurnEventWriter)
                     .with_system(handle_confirm_turn_event),
             )
+            .add_system_set(
+                SystemSet::on_enter(AppState::WaitForTweensToFinish)
+                    .with_system(setup_screen_transition_delay_timer),
+            )
             .add_system_set(
                 SystemSet::on_update(AppState::WaitForTweensToFinish)
+                    .with_system(skip_animations.before(wait_for_tweens_to_finish))
                     .with_system(wait_for_tweens_to_finish),
             );
     }
@@ -0,0 +1,661 @@
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use bevy::prelude::*;
+use itertools::Itertools;
+use std::collections::HashMap;
+
+use crate::card_selection::SelectedCard;
+use crate::common_systems::despawn_entity_with_component;
+use crate::event::ConfirmTurnEvent;
+use crate::game::{ActivePlayer, HumanPlayer, PlayerName};
+use crate::game_resources::card::{
+    ActivePlayerCamelCard, ActivePlayerGoodsCard, Card, CardType, GoodType, GoodsMap, MarketCard,
+};
+use crate::game_resources::deck_definition::{DeckDefinition, GoodsDefinition};
+use crate::game_resources::game_config::GameConfig;
+use crate::game_resources::tokens::{BonusType, Tokens};
+use crate::label::Label;
+use crate::localization::{Localization, LocalizedText};
+use crate::move_validation::{get_card_selection, MoveType};
+use crate::resources::GameState;
+use crate::states::AppState;
+
+const PANEL_WIDTH: f32 = 180.0;
+const PANEL_HEIGHT: f32 = 280.0;
+const SCROLL_LINE_HEIGHT: f32 = 20.0;
+
+#[derive(Debug, Clone)]
+pub enum RecordedMove {
+    TakeSingleGood(GoodType),
+    TakeAllCamels(usize),
+    Exchange {
+        taken: Vec<GoodType>,
+        given_goods: Vec<GoodType>,
+        num_camels_given: usize,
+    },
+    Sell {
+        good_type: GoodType,
+        num_sold: usize,
+        goods_tokens_value: usize,
+        // The value is only shown to the player who won it
+        bonus_value: Option<usize>,
+    },
+}
+
+impl RecordedMove {
+    // The rupees from the tokens won by the move, bonus included
+    pub fn get_rupees_won(&self) -> usize {
+        match self {
+            RecordedMove::Sell {
+                goods_tokens_value,
+                bonus_value,
+                ..
+            } => goods_tokens_value + bonus_value.unwrap_or_default(),
+            _ => 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MoveHistoryEntry {
+    pub player: Entity,
+    pub player_name: String,
+    pub recorded_move: RecordedMove,
+}
+
+// Every turn played so far in this game, oldest first
+#[derive(Default)]
+pub struct MoveHistory {
+    pub entries: Vec<MoveHistoryEntry>,
+}
+
+impl MoveHistory {
+    // The goods that everyone has seen the player take into their hand and not yet give back or sell. The goods
+    // they were dealt stay hidden, so giving or selling more of a good than they were seen to take doesn't go below
+    // zero.
+    pub fn get_known_goods_in_hand(&self, player: Entity) -> HashMap<GoodType, usize> {
+        let mut known_goods = HashMap::new();
+        let remove_goods = |known_goods: &mut HashMap<GoodType, usize>, good_type, count| {
+            if let Some(known_count) = known_goods.get_mut(&good_type) {
+                *known_count = usize::saturating_sub(*known_count, count);
+            }
+        };
+
+        for entry in self.entries.iter().filter(|entry| entry.player == player) {
+            match &entry.recorded_move {
+                RecordedMove::TakeSingleGood(good_type) => {
+                    *known_goods.entry(*good_type).or_default() += 1;
+                }
+                RecordedMove::TakeAllCamels(_) => {}
+                RecordedMove::Exchange {
+                    taken, given_goods, ..
+                } => {
+                    for good_type in given_goods {
+                        remove_goods(&mut known_goods, *good_type, 1);
+                    }
+                    for good_type in taken {
+                        *known_goods.entry(*good_type).or_default() += 1;
+                    }
+                }
+                RecordedMove::Sell {
+                    good_type,
+                    num_sold,
+                    ..
+                } => {
+                    remove_goods(&mut known_goods, *good_type, *num_sold);
+                }
+            }
+        }
+
+        known_goods.retain(|_, count| *count > 0);
+        known_goods
+    }
+}
+
+fn get_good_names(
+    goods: &GoodsMap<GoodsDefinition>,
+    good_types: &[GoodType],
+    localization: &Localization,
+) -> String {
+    good_types
+        .iter()
+        .map(|good_type| localization.get_good_name(&goods[*good_type].name))
+        .join(", ")
+}
+
+fn get_camels_text(num_camels: usize, localization: &Localization) -> String {
+    if num_camels == 1 {
+        localization.get("history.one_camel")
+    } else {
+        localization.format("history.camels", &[("count", &num_camels)])
+    }
+}
+
+impl MoveHistoryEntry {
+    // Bonus token values are kept secret from everyone but the player who won them
+    pub fn describe(
+        &self,
+        goods: &GoodsMap<GoodsDefinition>,
+        can_see_bonus_value: bool,
+        localization: &Localization,
+    ) -> String {
+        let player = &self.player_name;
+
+        match &self.recorded_move {
+            RecordedMove::TakeSingleGood(good_type) => localization.format(
+                "history.took",
+                &[
+                    ("player", player),
+                    (
+                        "cards",
+                        &localization.get_good_name(&goods[*good_type].name),
+                    ),
+                ],
+            ),
+            RecordedMove::TakeAllCamels(num_camels) => localization.format(
+                "history.took",
+                &[
+                    ("player", player),
+                    ("cards", &get_camels_text(*num_camels, localization)),
+                ],
+            ),
+            RecordedMove::Exchange {
+                taken,
+                given_goods,
+                num_camels_given,
+            } => {
+                let given = match (given_goods.is_empty(), *num_camels_given) {
+                    (false, 0) => get_good_names(goods, given_goods, localization),
+                    (true, num_camels) => get_camels_text(num_camels, localization),
+                    (false, num_camels) => localization.format(
+                        "history.goods_and_camels",
+                        &[
+                            ("goods", &get_good_names(goods, given_goods, localization)),
+                            ("camels", &get_camels_text(num_camels, localization)),
+                        ],
+                    ),
+                };
+
+                localization.format(
+                    "history.exchanged",
+                    &[
+                        ("player", player),
+                        ("given", &given),
+                        ("taken", &get_good_names(goods, taken, localization)),
+                    ],
+                )
+            }
+            RecordedMove::Sell {
+                good_type,
+                num_sold,
+                goods_tokens_value,
+                bonus_value,
+            } => {
+                let bonus_text = match bonus_value {
+                    Some(value) if can_see_bonus_value => {
+                        localization.format("history.bonus_value", &[("value", value)])
+                    }
+                    Some(_) => localization.get("history.bonus"),
+                    None => "".to_string(),
+                };
+
+                localization.format(
+                    "history.sold",
+                    &[
+                        ("player", player),
+                        ("count", num_sold),
+                        ("good", &localization.get_good_name(&goods[*good_type].name)),
+                        ("value", goods_tokens_value),
+                        ("bonus", &bonus_text),
+                    ],
+                )
+            }
+        }
+    }
+}
+
+fn reset_move_history(mut commands: Commands) {
+    commands.insert_resource(MoveHistory::default());
+}
+
+// Runs before the move is executed, while the selected cards and the tokens to be won are still in place
+#[allow(clippy::too_many_arguments)]
+fn record_move(
+    mut ev_confirm_turn: EventReader<ConfirmTurnEvent>,
+    mut move_history: ResMut<MoveHistory>,
+    tokens: Option<Res<Tokens>>,
+    game_config: Res<GameConfig>,
+    active_player_query: Query<(Entity, &PlayerName), With<ActivePlayer>>,
+    market_selected_card_query: Query<&Card, (With<MarketCard>, With<SelectedCard>)>,
+    goods_hand_selected_card_query: Query<&Card, (With<ActivePlayerGoodsCard>, With<SelectedCard>)>,
+    camel_hand_selected_card_query: Query<&Card, (With<ActivePlayerCamelCard>, With<SelectedCard>)>,
+) {
+    for ev in ev_confirm_turn.iter() {
+        let tokens = match &tokens {
+            Some(tokens) => tokens,
+            None => return,
+        };
+        let (player, player_name) = active_player_query.single();
+        let selection = get_card_selection(
+            &market_selected_card_query,
+            &goods_hand_selected_card_query,
+            &camel_hand_selected_card_query,
+        );
+
+        let market_goods = selection
+            .market
+            .iter()
+            .filter_map(|card_type| match card_type {
+                CardType::Camel => None,
+                CardType::Good(good_type) => Some(*good_type),
+            })
+            .collect::<Vec<_>>();
+
+        let recorded_move = match ev.0 {
+            MoveType::TakeSingleGood => RecordedMove::TakeSingleGood(market_goods[0]),
+            MoveType::TakeAllCamels => RecordedMove::TakeAllCamels(selection.market.len()),
+            MoveType::ExchangeForGoodsFromMarket => RecordedMove::Exchange {
+                taken: market_goods,
+                given_goods: selection.goods_hand.clone(),
+                num_camels_given: selection.num_camels_from_hand,
+            },
+            MoveType::SellGoods => {
+                let good_type = selection.goods_hand[0];
+                let num_sold = selection.goods_hand.len();
+                let sale_preview = tokens.preview_sale(
+                    good_type,
+                    num_sold,
+                    game_config.num_empty_token_stacks_to_end,
+                );
+
+                RecordedMove::Sell {
+                    good_type,
+                    num_sold,
+                    goods_tokens_value: sale_preview.get_goods_tokens_value(),
+                    bonus_value: BonusType::from_num_sold(num_sold)
+                        .and_then(|bonus_type| tokens.bonus[bonus_type].last().copied()),
+                }
+            }
+        };
+
+        move_history.entries.push(MoveHistoryEntry {
+            player,
+            player_name: player_name.0.clone(),
+            recorded_move,
+        });
+    }
+}
+
+#[derive(Component)]
+struct MoveHistoryPanel;
+
+// The list inside the panel, moved up and down by the mouse wheel
+#[derive(Component, Default)]
+struct MoveHistoryList {
+    position: f32,
+}
+
+fn create_entry_text(asset_server: &AssetServer, value: String) -> TextBundle {
+    TextBundle::from_section(
+        value,
+        TextStyle {
+            font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+            font_size: 14.0,
+            color: Color::rgb(0.9, 0.9, 0.9),
+        },
+    )
+    .with_style(Style {
+        margin: UiRect::all(Val::Px(5.0)),
+        max_size: Size::new(Val::Px(PANEL_WIDTH - 10.0), Val::Undefined),
+        ..default()
+    })
+}
+
+// The panel stays up from one turn to the next against the computer, and is set up again after each turn
+// transition screen in local multiplayer
+fn setup_move_history_panel(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    localization: Res<Localization>,
+    panel_query: Query<(), With<MoveHistoryPanel>>,
+) {
+    if !panel_query.is_empty() {
+        return;
+    }
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect::new(Val::Px(0.), Val::Auto, Val::Auto, Val::Px(0.)),
+                size: Size::new(Val::Px(PANEL_WIDTH), Val::Px(PANEL_HEIGHT)),
+                flex_direction: FlexDirection::ColumnReverse,
+                ..default()
+            },
+            color: Color::rgba(0.1, 0.1, 0.1, 0.8).into(),
+            ..default()
+        })
+        .insert(MoveHistoryPanel)
+        // Tracks whether the cursor is over the panel, so it only scrolls while hovered
+        .insert(Interaction::default())
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(create_entry_text(
+                    &asset_server,
+                    localization.get("history.title"),
+                ))
+                .insert(LocalizedText("history.title"));
+
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::ColumnReverse,
+                        flex_grow: 1.0,
+                        overflow: Overflow::Hidden,
+                        ..default()
+                    },
+                    color: Color::NONE.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent
+                        .spawn_bundle(NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::ColumnReverse,
+                                flex_shrink: 0.0,
+                                ..default()
+                            },
+                            color: Color::NONE.into(),
+                            ..default()
+                        })
+                        .insert(MoveHistoryList::default());
+                });
+        });
+}
+
+// Lists the most recent turn first
+#[allow(clippy::too_many_arguments)]
+fn update_move_history_list(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    move_history: Res<MoveHistory>,
+    game_state: Res<GameState>,
+    deck_definition: Option<Res<DeckDefinition>>,
+    localization: Res<Localization>,
+    human_player_query: Query<(), With<HumanPlayer>>,
+    added_list_query: Query<(), Added<MoveHistoryList>>,
+    mut list_query: Query<(Entity, &mut MoveHistoryList, &mut Style)>,
+) {
+    if !move_history.is_changed() && !localization.is_changed() && added_list_query.is_empty() {
+        return;
+    }
+
+    let deck_definition = match deck_definition {
+        Some(deck_definition) => deck_definition,
+        None => return,
+    };
+
+    for (list_entity, mut list, mut style) in list_query.iter_mut() {
+        list.position = 0.0;
+        style.position.top = Val::Px(0.0);
+
+        commands.entity(list_entity).despawn_descendants();
+
+        let entry_entities = move_history
+            .entries
+            .iter()
+            .rev()
+            .map(|entry| {
+                // Against the computer, the player can see their own bonus values. Players share the screen in
+                // local multiplayer, so neither can.
+                let can_see_bonus_value =
+                    game_state.is_playing_ai && human_player_query.contains(entry.player);
+                let description =
+                    entry.describe(&deck_definition.goods, can_see_bonus_value, &localization);
+
+                commands
+                    .spawn_bundle(create_entry_text(&asset_server, description))
+                    .id()
+            })
+            .collect::<Vec<_>>();
+
+        commands.entity(list_entity).push_children(&entry_entities);
+    }
+}
+
+fn scroll_move_history_list(
+    mut ev_mouse_wheel: EventReader<MouseWheel>,
+    mut list_query: Query<(&mut MoveHistoryList, &mut Style, &Children, &Parent)>,
+    node_query: Query<&Node>,
+    panel_query: Query<&Interaction, With<MoveHistoryPanel>>,
+) {
+    let is_panel_hovered = panel_query
+        .iter()
+        .any(|interaction| *interaction != Interaction::None);
+
+    // Events are still read while the panel isn't hovered, so they don't build up until it is
+    for ev in ev_mouse_wheel.iter().filter(|_| is_panel_hovered) {
+        for (mut list, mut style, children, parent) in list_query.iter_mut() {
+            let entries_height: f32 = children
+                .iter()
+                .filter_map(|entity| node_query.get(*entity).ok())
+                .map(|node| node.size.y)
+                .sum();
+            let visible_height = node_query
+                .get(parent.get())
+                .map(|node| node.size.y)
+                .unwrap_or_default();
+            let max_scroll = (entries_height - visible_height).max(0.0);
+
+            let dy = match ev.unit {
+                MouseScrollUnit::Line => ev.y * SCROLL_LINE_HEIGHT,
+                MouseScrollUnit::Pixel => ev.y,
+            };
+
+            list.position = (list.position + dy).clamp(-max_scroll, 0.0);
+            style.position.top = Val::Px(list.position);
+        }
+    }
+}
+
+pub struct MoveHistoryPlugin;
+
+impl Plugin for MoveHistoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MoveHistory>()
+            .add_system_set(SystemSet::on_enter(AppState::InitGame).with_system(reset_move_history))
+            .add_system(
+                record_move
+                    .after(Label::ConfirmTurnEventWriter)
+                    .before(Label::ConfirmTurnEventReader),
+            )
+            .add_system(update_move_history_list.after(record_move))
+            .add_system(scroll_move_history_list);
+
+        for state in [AppState::InGame, AppState::AiTurn] {
+            app.add_system_set(SystemSet::on_enter(state).with_system(setup_move_history_panel));
+        }
+
+        for state in [
+            AppState::TurnTransition,
+            AppState::GameOver,
+            AppState::MainMenu,
+            AppState::InitGame,
+        ] {
+            app.add_system_set(
+                SystemSet::on_enter(state)
+                    .with_system(despawn_entity_with_component::<MoveHistoryPanel>),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{history_entry, official_goods, CLOTH, DIAMOND, LEATHER};
+
+    fn describe_sale(bonus_value: Option<usize>, can_see_bonus_value: bool) -> String {
+        let sale = history_entry(
+            Entity::from_raw(0),
+            RecordedMove::Sell {
+                good_type: LEATHER,
+                num_sold: 3,
+                goods_tokens_value: 6,
+                bonus_value,
+            },
+        );
+
+        sale.describe(
+            &official_goods(),
+            can_see_bonus_value,
+            &Localization::default(),
+        )
+    }
+
+    // Spawns a panel whose list of entries is twice as tall as the space it is shown in, returning the list
+    fn spawn_panel(app: &mut App, interaction: Interaction) -> Entity {
+        let entry = app
+            .world
+            .spawn()
+            .insert(Node {
+                size: Vec2::new(PANEL_WIDTH, 200.0),
+            })
+            .id();
+        let list = app
+            .world
+            .spawn()
+            .insert(MoveHistoryList::default())
+            .insert(Style::default())
+            .push_children(&[entry])
+            .id();
+        let viewport = app
+            .world
+            .spawn()
+            .insert(Node {
+                size: Vec2::new(PANEL_WIDTH, 100.0),
+            })
+            .push_children(&[list])
+            .id();
+        app.world
+            .spawn()
+            .insert(MoveHistoryPanel)
+            .insert(interaction)
+            .push_children(&[viewport]);
+
+        list
+    }
+
+    fn scroll_panel(interaction: Interaction) -> f32 {
+        let mut app = App::new();
+        app.add_event::<MouseWheel>()
+            .add_system(scroll_move_history_list);
+        let list = spawn_panel(&mut app, interaction);
+
+        app.world
+            .resource_mut::<Events<MouseWheel>>()
+            .send(MouseWheel {
+                unit: MouseScrollUnit::Line,
+                x: 0.0,
+                y: -1.0,
+            });
+        app.update();
+
+        app.world.get::<MoveHistoryList>(list).unwrap().position
+    }
+
+    #[test]
+    fn sale_shows_the_bonus_value_when_it_can_be_seen() {
+        assert_eq!(
+            describe_sale(Some(3), true),
+            "Player 0 sold 3 Leather for 6 rupees + bonus of 3"
+        );
+    }
+
+    #[test]
+    fn sale_hides_the_bonus_value_when_it_cannot_be_seen() {
+        assert_eq!(
+            describe_sale(Some(3), false),
+            "Player 0 sold 3 Leather for 6 rupees + bonus"
+        );
+    }
+
+    #[test]
+    fn sale_without_a_bonus_does_not_mention_one() {
+        assert_eq!(
+            describe_sale(None, true),
+            "Player 0 sold 3 Leather for 6 rupees"
+        );
+    }
+
+    #[test]
+    fn hovered_panel_scrolls() {
+        assert_eq!(scroll_panel(Interaction::Hovered), -SCROLL_LINE_HEIGHT);
+    }
+
+    #[test]
+    fn panel_does_not_scroll_when_not_hovered() {
+        assert_eq!(scroll_panel(Interaction::None), 0.0);
+    }
+
+    #[test]
+    fn known_goods_are_only_the_goods_taken_from_the_market() {
+        let player = Entity::from_raw(0);
+        let opponent = Entity::from_raw(1);
+        let move_history = MoveHistory {
+            entries: vec![
+                history_entry(player, RecordedMove::TakeSingleGood(LEATHER)),
+                history_entry(opponent, RecordedMove::TakeSingleGood(DIAMOND)),
+                history_entry(player, RecordedMove::TakeAllCamels(3)),
+                history_entry(
+                    player,
+                    RecordedMove::Exchange {
+                        taken: vec![LEATHER, CLOTH],
+                        given_goods: vec![],
+                        num_camels_given: 2,
+                    },
+                ),
+            ],
+        };
+
+        assert_eq!(
+            move_history.get_known_goods_in_hand(player),
+            HashMap::from([(LEATHER, 2), (CLOTH, 1)])
+        );
+    }
+
+    #[test]
+    fn known_goods_given_away_or_sold_are_forgotten() {
+        let player = Entity::from_raw(0);
+        let move_history = MoveHistory {
+            entries: vec![
+                history_entry(player, RecordedMove::TakeSingleGood(LEATHER)),
+                history_entry(player, RecordedMove::TakeSingleGood(LEATHER)),
+                history_entry(player, RecordedMove::TakeSingleGood(CLOTH)),
+                // Gives back a cloth and a diamond, which must have been dealt to them
+                history_entry(
+                    player,
+                    RecordedMove::Exchange {
+                        taken: vec![LEATHER, DIAMOND],
+                        given_goods: vec![CLOTH, DIAMOND],
+                        num_camels_given: 0,
+                    },
+                ),
+                // Sells more leather than they were seen to take
+                history_entry(
+                    player,
+                    RecordedMove::Sell {
+                        good_type: LEATHER,
+                        num_sold: 4,
+                        goods_tokens_value: 4,
+                        bonus_value: None,
+                    },
+                ),
+            ],
+        };
+
+        assert_eq!(
+            move_history.get_known_goods_in_hand(player),
+            HashMap::from([(DIAMOND, 1)])
+        );
+    }
+}
@@ -1,22 +1,42 @@
 use bevy::prelude::*;
-use itertools::Itertools;
 
 use crate::{
     card_selection::{SelectedCard, SelectedCardState},
-    game_resources::card::{
-        ActivePlayerCamelCard, ActivePlayerGoodsCard, Card, CardType, GoodType, MarketCard,
+    game_resources::{
+        card::{
+            ActivePlayerCamelCard, ActivePlayerGoodsCard, Card, CardType, GoodType, GoodsMap,
+            MarketCard,
+        },
+        deck_definition::{DeckDefinition, GoodsDefinition},
+        game_config::GameConfig,
     },
+    label::Label,
+    localization::Localization,
     states::TurnState,
 };
 
-#[derive(Default, Eq, PartialEq)]
+#[derive(Debug, Eq, PartialEq)]
 pub enum MoveValidity {
-    #[default]
-    Invalid,
+    Invalid(InvalidMoveReason),
     Valid(MoveType),
 }
 
-#[derive(Copy, Clone, Eq, PartialEq)]
+impl Default for MoveValidity {
+    fn default() -> Self {
+        MoveValidity::Invalid(InvalidMoveReason::NoMoveModeSelected)
+    }
+}
+
+impl MoveValidity {
+    pub fn localize(&self, localization: &Localization) -> String {
+        match self {
+            MoveValidity::Invalid(reason) => reason.localize(localization),
+            MoveValidity::Valid(move_type) => move_type.localize(localization),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum MoveType {
     TakeSingleGood,
     TakeAllCamels,
@@ -24,146 +44,270 @@ pub enum MoveType {
     SellGoods,
 }
 
-#[allow(clippy::too_many_arguments)]
-fn handle_selected_card_state_change_for_take(
-    turn_state: Res<State<TurnState>>,
-    selected_card_state: Res<SelectedCardState>,
-    mut move_validity_state: ResMut<MoveValidity>,
-    market_selected_card_query: Query<&Card, (With<MarketCard>, With<SelectedCard>)>,
-    all_market_card_query: Query<&Card, With<MarketCard>>,
-    camel_hand_selected_card_query: Query<&Card, (With<ActivePlayerCamelCard>, With<SelectedCard>)>,
-    goods_hand_selected_card_query: Query<&Card, (With<ActivePlayerGoodsCard>, With<SelectedCard>)>,
-    all_goods_hand_card_query: Query<&Card, With<ActivePlayerGoodsCard>>,
-) {
-    if *turn_state.current() != TurnState::Take
-        || (!selected_card_state.is_changed() && !turn_state.is_changed())
-    {
-        return;
+impl MoveType {
+    pub fn localize(&self, localization: &Localization) -> String {
+        localization.get(match self {
+            MoveType::TakeSingleGood => "move_type.take_single_good",
+            MoveType::TakeAllCamels => "move_type.take_all_camels",
+            MoveType::ExchangeForGoodsFromMarket => "move_type.exchange_goods",
+            MoveType::SellGoods => "move_type.sell_goods",
+        })
     }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum InvalidMoveReason {
+    NoMoveModeSelected,
+    NoCardsSelected,
+    NoMarketGoodsSelected,
+    HandWouldExceedLimit(usize),
+    CamelsTakenWithGoods,
+    CamelsExchangedForHandCards,
+    MustTakeAllCamels,
+    ExchangeTooFewGoods,
+    ExchangeCountMismatch,
+    ExchangeSameGoodType,
+    SellFromMarket,
+    SellCamels,
+    SellMixedGoods,
+    SellTooFewGoods(usize),
+    NotTutorialMove,
+}
+
+impl InvalidMoveReason {
+    pub fn localize(&self, localization: &Localization) -> String {
+        let key = match self {
+            InvalidMoveReason::NoMoveModeSelected => "invalid_move.no_move_mode_selected",
+            InvalidMoveReason::NoCardsSelected => "invalid_move.no_cards_selected",
+            InvalidMoveReason::NoMarketGoodsSelected => "invalid_move.no_market_goods_selected",
+            InvalidMoveReason::HandWouldExceedLimit(max_goods_in_hand) => {
+                return localization.format(
+                    "invalid_move.hand_would_exceed_limit",
+                    &[("max", max_goods_in_hand)],
+                );
+            }
+            InvalidMoveReason::CamelsTakenWithGoods => "invalid_move.camels_taken_with_goods",
+            InvalidMoveReason::CamelsExchangedForHandCards => {
+                "invalid_move.camels_exchanged_for_hand_cards"
+            }
+            InvalidMoveReason::MustTakeAllCamels => "invalid_move.must_take_all_camels",
+            InvalidMoveReason::ExchangeTooFewGoods => "invalid_move.exchange_too_few_goods",
+            InvalidMoveReason::ExchangeCountMismatch => "invalid_move.exchange_count_mismatch",
+            InvalidMoveReason::ExchangeSameGoodType => "invalid_move.exchange_same_good_type",
+            InvalidMoveReason::SellFromMarket => "invalid_move.sell_from_market",
+            InvalidMoveReason::SellCamels => "invalid_move.sell_camels",
+            InvalidMoveReason::SellMixedGoods => "invalid_move.sell_mixed_goods",
+            InvalidMoveReason::SellTooFewGoods(min_sale_size) => {
+                return localization
+                    .format("invalid_move.sell_too_few_goods", &[("min", min_sale_size)]);
+            }
+            InvalidMoveReason::NotTutorialMove => "invalid_move.not_tutorial_move",
+        };
+        localization.get(key)
+    }
+}
 
-    let num_selected_market_goods_cards = market_selected_card_query
+// The cards selected for a move, independent of the entities that represent them
+#[derive(Clone, Debug, Default)]
+pub struct CardSelection {
+    pub market: Vec<CardType>,
+    pub goods_hand: Vec<GoodType>,
+    pub num_camels_from_hand: usize,
+}
+
+// The parts of the board that the legality of a move depends on
+#[derive(Clone, Debug)]
+pub struct BoardState {
+    pub market: Vec<CardType>,
+    pub num_goods_in_hand: usize,
+    pub max_goods_in_hand: usize,
+}
+
+pub fn validate_take(selection: &CardSelection, board: &BoardState) -> MoveValidity {
+    let selected_market_goods = selection
+        .market
         .iter()
-        .filter(|c| matches!(c.0, CardType::Good(_)))
-        .count();
+        .filter_map(|c| match c {
+            CardType::Camel => None,
+            CardType::Good(g) => Some(*g),
+        })
+        .collect::<Vec<_>>();
 
-    let num_selected_camels_from_hand = camel_hand_selected_card_query.iter().count();
+    let num_selected_market_goods_cards = selected_market_goods.len();
+    let num_selected_camels_from_market = selection.market.len() - num_selected_market_goods_cards;
+    let num_selected_goods_from_hand = selection.goods_hand.len();
+    let num_selected_camels_from_hand = selection.num_camels_from_hand;
 
-    let num_total_selected_cards_in_market = market_selected_card_query.iter().count();
+    if selection.market.is_empty() && num_selected_goods_from_hand == 0 {
+        if num_selected_camels_from_hand == 0 {
+            return MoveValidity::Invalid(InvalidMoveReason::NoCardsSelected);
+        }
+        return MoveValidity::Invalid(InvalidMoveReason::NoMarketGoodsSelected);
+    }
 
-    let num_selected_goods_from_hand = goods_hand_selected_card_query.iter().count();
+    // Take all camels from market rule
+    if num_selected_camels_from_market > 0 {
+        let total_num_camels_in_market = board
+            .market
+            .iter()
+            .filter(|c| matches!(c, CardType::Camel))
+            .count();
 
-    let num_total_goods_in_hand = all_goods_hand_card_query.iter().count();
+        return if num_selected_market_goods_cards > 0 {
+            MoveValidity::Invalid(InvalidMoveReason::CamelsTakenWithGoods)
+        } else if num_selected_goods_from_hand > 0 || num_selected_camels_from_hand > 0 {
+            MoveValidity::Invalid(InvalidMoveReason::CamelsExchangedForHandCards)
+        } else if num_selected_camels_from_market != total_num_camels_in_market {
+            MoveValidity::Invalid(InvalidMoveReason::MustTakeAllCamels)
+        } else {
+            MoveValidity::Valid(MoveType::TakeAllCamels)
+        };
+    }
+
+    if num_selected_market_goods_cards == 0 {
+        return MoveValidity::Invalid(InvalidMoveReason::NoMarketGoodsSelected);
+    }
 
     // Take single good from market rule
-    if num_selected_market_goods_cards == 1
-        && num_total_selected_cards_in_market == 1
-        && num_selected_camels_from_hand == 0
-        && num_selected_goods_from_hand == 0
-        && num_total_goods_in_hand < 7
-    {
-        // println!("TAKE SINGLE GOOD");
-        *move_validity_state = MoveValidity::Valid(MoveType::TakeSingleGood);
-        return;
+    if num_selected_market_goods_cards == 1 {
+        return if num_selected_camels_from_hand > 0 || num_selected_goods_from_hand > 0 {
+            MoveValidity::Invalid(InvalidMoveReason::ExchangeTooFewGoods)
+        } else if board.num_goods_in_hand >= board.max_goods_in_hand {
+            MoveValidity::Invalid(InvalidMoveReason::HandWouldExceedLimit(
+                board.max_goods_in_hand,
+            ))
+        } else {
+            MoveValidity::Valid(MoveType::TakeSingleGood)
+        };
     }
 
-    // Take all camels from market rule
-    let total_num_camels_in_market = all_market_card_query
+    // Exchange at least two goods from the market with combination of camels and goods from player's hand
+    let do_market_goods_set_and_hand_goods_set_intersect = selected_market_goods
         .iter()
-        .filter(|c| matches!(c.0, CardType::Camel))
-        .count();
+        .any(|g| selection.goods_hand.contains(g));
 
-    if num_total_selected_cards_in_market > 0
-        && market_selected_card_query
-            .iter()
-            .all(|c| matches!(c.0, CardType::Camel))
-        && market_selected_card_query.iter().count() == total_num_camels_in_market
-        && goods_hand_selected_card_query.iter().count() == 0
+    if do_market_goods_set_and_hand_goods_set_intersect {
+        MoveValidity::Invalid(InvalidMoveReason::ExchangeSameGoodType)
+    } else if num_selected_market_goods_cards
+        != num_selected_camels_from_hand + num_selected_goods_from_hand
     {
-        // println!("TAKE ALL CAMELS");
-        *move_validity_state = MoveValidity::Valid(MoveType::TakeAllCamels);
-        return;
+        MoveValidity::Invalid(InvalidMoveReason::ExchangeCountMismatch)
+    } else if num_selected_market_goods_cards + board.num_goods_in_hand
+        - num_selected_goods_from_hand
+        > board.max_goods_in_hand
+    {
+        MoveValidity::Invalid(InvalidMoveReason::HandWouldExceedLimit(
+            board.max_goods_in_hand,
+        ))
+    } else {
+        MoveValidity::Valid(MoveType::ExchangeForGoodsFromMarket)
     }
+}
 
-    // Exchange at least two goods from the market with combination of camels and goods from player's hand
-    let do_market_goods_set_and_hand_goods_set_intersect = market_selected_card_query
-        .iter()
-        .filter_map(|c| match c.0 {
-            CardType::Camel => None,
-            CardType::Good(g) => Some(g),
-        })
-        .any(|g| {
-            goods_hand_selected_card_query
-                .iter()
-                .filter_map(|c| match c.0 {
-                    CardType::Camel => None,
-                    CardType::Good(g) => Some(g),
-                })
-                .contains(&g)
-        });
-
-    let num_selected_camels_from_market = market_selected_card_query
-        .iter()
-        .filter(|c| matches!(c.0, CardType::Camel))
-        .count();
-
-    if !do_market_goods_set_and_hand_goods_set_intersect
-        && num_selected_camels_from_market == 0
-        && num_selected_market_goods_cards > 1
-        && num_selected_market_goods_cards
-            == num_selected_camels_from_hand + num_selected_goods_from_hand
-        && num_selected_market_goods_cards + num_total_goods_in_hand - num_selected_goods_from_hand
-            <= 7
+pub fn validate_sell(selection: &CardSelection, goods: &GoodsMap<GoodsDefinition>) -> MoveValidity {
+    let are_all_goods_the_same = selection.goods_hand.windows(2).all(|w| w[0] == w[1]);
+
+    if !selection.market.is_empty() {
+        MoveValidity::Invalid(InvalidMoveReason::SellFromMarket)
+    } else if selection.num_camels_from_hand > 0 {
+        MoveValidity::Invalid(InvalidMoveReason::SellCamels)
+    } else if selection.goods_hand.is_empty() {
+        MoveValidity::Invalid(InvalidMoveReason::NoCardsSelected)
+    } else if !are_all_goods_the_same {
+        MoveValidity::Invalid(InvalidMoveReason::SellMixedGoods)
+    } else if selection.goods_hand.len() < goods[selection.goods_hand[0]].min_sale_size {
+        MoveValidity::Invalid(InvalidMoveReason::SellTooFewGoods(
+            goods[selection.goods_hand[0]].min_sale_size,
+        ))
+    } else {
+        MoveValidity::Valid(MoveType::SellGoods)
+    }
+}
+
+pub fn get_card_selection(
+    market_selected_card_query: &Query<&Card, (With<MarketCard>, With<SelectedCard>)>,
+    goods_hand_selected_card_query: &Query<
+        &Card,
+        (With<ActivePlayerGoodsCard>, With<SelectedCard>),
+    >,
+    camel_hand_selected_card_query: &Query<
+        &Card,
+        (With<ActivePlayerCamelCard>, With<SelectedCard>),
+    >,
+) -> CardSelection {
+    CardSelection {
+        market: market_selected_card_query.iter().map(|c| c.0).collect(),
+        goods_hand: goods_hand_selected_card_query
+            .iter()
+            .filter_map(|c| match c.0 {
+                CardType::Camel => None,
+                CardType::Good(g) => Some(g),
+            })
+            .collect(),
+        num_camels_from_hand: camel_hand_selected_card_query.iter().count(),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_selected_card_state_change_for_take(
+    turn_state: Res<State<TurnState>>,
+    selected_card_state: Res<SelectedCardState>,
+    game_config: Res<GameConfig>,
+    mut move_validity_state: ResMut<MoveValidity>,
+    market_selected_card_query: Query<&Card, (With<MarketCard>, With<SelectedCard>)>,
+    all_market_card_query: Query<&Card, With<MarketCard>>,
+    camel_hand_selected_card_query: Query<&Card, (With<ActivePlayerCamelCard>, With<SelectedCard>)>,
+    goods_hand_selected_card_query: Query<&Card, (With<ActivePlayerGoodsCard>, With<SelectedCard>)>,
+    all_goods_hand_card_query: Query<&Card, With<ActivePlayerGoodsCard>>,
+) {
+    if *turn_state.current() != TurnState::Take
+        || (!selected_card_state.is_changed() && !turn_state.is_changed())
     {
-        // println!("EXCHANGE");
-        *move_validity_state = MoveValidity::Valid(MoveType::ExchangeForGoodsFromMarket);
         return;
     }
 
-    *move_validity_state = MoveValidity::Invalid;
+    let selection = get_card_selection(
+        &market_selected_card_query,
+        &goods_hand_selected_card_query,
+        &camel_hand_selected_card_query,
+    );
+
+    let board = BoardState {
+        market: all_market_card_query.iter().map(|c| c.0).collect(),
+        num_goods_in_hand: all_goods_hand_card_query.iter().count(),
+        max_goods_in_hand: game_config.max_goods_in_hand,
+    };
+
+    *move_validity_state = validate_take(&selection, &board);
 }
 
 fn handle_selected_card_state_change_for_sell(
     turn_state: Res<State<TurnState>>,
+    deck_definition: Option<Res<DeckDefinition>>,
     selected_card_state: Res<SelectedCardState>,
     mut move_validity_state: ResMut<MoveValidity>,
     goods_hand_selected_card_query: Query<&Card, (With<ActivePlayerGoodsCard>, With<SelectedCard>)>,
     camel_hand_selected_card_query: Query<&Card, (With<ActivePlayerCamelCard>, With<SelectedCard>)>,
     market_selected_card_query: Query<&Card, (With<MarketCard>, With<SelectedCard>)>,
 ) {
+    let deck_definition = match deck_definition {
+        Some(deck_definition) => deck_definition,
+        None => return,
+    };
+
     if *turn_state.current() != TurnState::Sell
         || (!selected_card_state.is_changed() && !turn_state.is_changed())
     {
         return;
     }
 
-    let num_selected_goods_from_hand = goods_hand_selected_card_query.iter().count();
-    let num_selected_camels_from_hand = camel_hand_selected_card_query.iter().count();
-    let num_selected_cards_from_market = market_selected_card_query.iter().count();
-
-    if num_selected_goods_from_hand > 0
-        && num_selected_camels_from_hand == 0
-        && num_selected_cards_from_market == 0
-    {
-        let selected_goods_types: Vec<GoodType> = goods_hand_selected_card_query
-            .iter()
-            .filter_map(|c| match c.0 {
-                CardType::Camel => None,
-                CardType::Good(g) => Some(g),
-            })
-            .collect();
-        let are_all_goods_the_same = selected_goods_types.windows(2).all(|w| w[0] == w[1]);
-
-        if are_all_goods_the_same {
-            let good_type = selected_goods_types[0];
-
-            if !good_type.is_high_value() || num_selected_goods_from_hand > 1 {
-                *move_validity_state = MoveValidity::Valid(MoveType::SellGoods);
-                return;
-            }
-        }
-    }
+    let selection = get_card_selection(
+        &market_selected_card_query,
+        &goods_hand_selected_card_query,
+        &camel_hand_selected_card_query,
+    );
 
-    *move_validity_state = MoveValidity::Invalid;
+    *move_validity_state = validate_sell(&selection, &deck_definition.goods);
 }
 
 fn handle_no_turn_state_selected(
@@ -171,7 +315,7 @@ fn handle_no_turn_state_selected(
     mut move_validity_state: ResMut<MoveValidity>,
 ) {
     if turn_state.is_changed() && *turn_state.current() == TurnState::None {
-        *move_validity_state = MoveValidity::Invalid;
+        *move_validity_state = MoveValidity::Invalid(InvalidMoveReason::NoMoveModeSelected);
     }
 }
 
@@ -183,17 +327,440 @@ impl Plugin for MoveValidationPlugin {
             // component removal occurs at the end of the stage (i.e. update stage), so this system needs to go in PostUpdate
             .add_system_to_stage(
                 CoreStage::PostUpdate,
-                handle_selected_card_state_change_for_take,
+                handle_selected_card_state_change_for_take.label(Label::MoveValidityWriter),
             )
             .add_system_to_stage(
                 CoreStage::PostUpdate,
-                handle_selected_card_state_change_for_sell,
+                handle_selected_card_state_change_for_sell.label(Label::MoveValidityWriter),
             )
             .add_system_to_stage(
                 CoreStage::PostUpdate,
                 handle_no_turn_state_selected
+                    .label(Label::MoveValidityWriter)
                     .after(handle_selected_card_state_change_for_take)
                     .after(handle_selected_card_state_change_for_sell),
             );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::{official_goods, CLOTH, DIAMOND, GOLD, LEATHER, SILVER, SPICE};
+    use proptest::prelude::*;
+
+    use CardType::{Camel, Good};
+
+    fn board(market: Vec<CardType>, num_goods_in_hand: usize) -> BoardState {
+        BoardState {
+            market,
+            num_goods_in_hand,
+            max_goods_in_hand: 7,
+        }
+    }
+
+    fn selection(
+        market: Vec<CardType>,
+        goods_hand: Vec<GoodType>,
+        num_camels_from_hand: usize,
+    ) -> CardSelection {
+        CardSelection {
+            market,
+            goods_hand,
+            num_camels_from_hand,
+        }
+    }
+
+    fn default_market() -> Vec<CardType> {
+        vec![Camel, Camel, Good(DIAMOND), Good(CLOTH), Good(SPICE)]
+    }
+
+    #[test]
+    fn take_with_nothing_selected_is_invalid() {
+        assert_eq!(
+            validate_take(&CardSelection::default(), &board(default_market(), 3)),
+            MoveValidity::Invalid(InvalidMoveReason::NoCardsSelected)
+        );
+    }
+
+    #[test]
+    fn take_with_only_hand_cards_selected_is_invalid() {
+        assert_eq!(
+            validate_take(&selection(vec![], vec![], 2), &board(default_market(), 3)),
+            MoveValidity::Invalid(InvalidMoveReason::NoMarketGoodsSelected)
+        );
+        assert_eq!(
+            validate_take(
+                &selection(vec![], vec![LEATHER], 0),
+                &board(default_market(), 3)
+            ),
+            MoveValidity::Invalid(InvalidMoveReason::NoMarketGoodsSelected)
+        );
+    }
+
+    #[test]
+    fn take_single_good_below_hand_limit_is_valid() {
+        assert_eq!(
+            validate_take(
+                &selection(vec![Good(DIAMOND)], vec![], 0),
+                &board(default_market(), 6)
+            ),
+            MoveValidity::Valid(MoveType::TakeSingleGood)
+        );
+    }
+
+    #[test]
+    fn take_single_good_at_hand_limit_is_invalid() {
+        assert_eq!(
+            validate_take(
+                &selection(vec![Good(DIAMOND)], vec![], 0),
+                &board(default_market(), 7)
+            ),
+            MoveValidity::Invalid(InvalidMoveReason::HandWouldExceedLimit(7))
+        );
+    }
+
+    #[test]
+    fn take_single_good_with_hand_cards_is_too_few_for_exchange() {
+        assert_eq!(
+            validate_take(
+                &selection(vec![Good(DIAMOND)], vec![], 1),
+                &board(default_market(), 3)
+            ),
+            MoveValidity::Invalid(InvalidMoveReason::ExchangeTooFewGoods)
+        );
+    }
+
+    #[test]
+    fn take_all_camels_is_valid() {
+        assert_eq!(
+            validate_take(
+                &selection(vec![Camel, Camel], vec![], 0),
+                &board(default_market(), 7)
+            ),
+            MoveValidity::Valid(MoveType::TakeAllCamels)
+        );
+    }
+
+    #[test]
+    fn take_all_camels_with_some_unselected_is_invalid() {
+        assert_eq!(
+            validate_take(
+                &selection(vec![Camel], vec![], 0),
+                &board(default_market(), 3)
+            ),
+            MoveValidity::Invalid(InvalidMoveReason::MustTakeAllCamels)
+        );
+    }
+
+    #[test]
+    fn take_camels_with_goods_from_market_is_invalid() {
+        assert_eq!(
+            validate_take(
+                &selection(vec![Camel, Camel, Good(CLOTH)], vec![], 0),
+                &board(default_market(), 3)
+            ),
+            MoveValidity::Invalid(InvalidMoveReason::CamelsTakenWithGoods)
+        );
+    }
+
+    #[test]
+    fn take_camels_for_goods_from_hand_is_invalid() {
+        assert_eq!(
+            validate_take(
+                &selection(vec![Camel, Camel], vec![LEATHER, LEATHER], 0),
+                &board(default_market(), 3)
+            ),
+            MoveValidity::Invalid(InvalidMoveReason::CamelsExchangedForHandCards)
+        );
+    }
+
+    #[test]
+    fn take_camels_with_camels_from_hand_is_invalid() {
+        assert_eq!(
+            validate_take(
+                &selection(vec![Camel, Camel], vec![], 1),
+                &board(default_market(), 3)
+            ),
+            MoveValidity::Invalid(InvalidMoveReason::CamelsExchangedForHandCards)
+        );
+    }
+
+    #[test]
+    fn exchange_with_camels_only_is_valid() {
+        assert_eq!(
+            validate_take(
+                &selection(vec![Good(DIAMOND), Good(CLOTH)], vec![], 2),
+                &board(default_market(), 5)
+            ),
+            MoveValidity::Valid(MoveType::ExchangeForGoodsFromMarket)
+        );
+    }
+
+    #[test]
+    fn exchange_with_camels_only_exceeding_hand_limit_is_invalid() {
+        assert_eq!(
+            validate_take(
+                &selection(vec![Good(DIAMOND), Good(CLOTH)], vec![], 2),
+                &board(default_market(), 6)
+            ),
+            MoveValidity::Invalid(InvalidMoveReason::HandWouldExceedLimit(7))
+        );
+    }
+
+    #[test]
+    fn exchange_with_goods_at_hand_limit_is_valid() {
+        assert_eq!(
+            validate_take(
+                &selection(vec![Good(DIAMOND), Good(CLOTH)], vec![LEATHER, GOLD], 0),
+                &board(default_market(), 7)
+            ),
+            MoveValidity::Valid(MoveType::ExchangeForGoodsFromMarket)
+        );
+    }
+
+    #[test]
+    fn exchange_with_mixed_camels_and_goods_is_valid() {
+        assert_eq!(
+            validate_take(
+                &selection(
+                    vec![Good(DIAMOND), Good(CLOTH), Good(SPICE)],
+                    vec![LEATHER],
+                    2
+                ),
+                &board(default_market(), 4)
+            ),
+            MoveValidity::Valid(MoveType::ExchangeForGoodsFromMarket)
+        );
+    }
+
+    #[test]
+    fn exchange_for_same_good_type_is_invalid() {
+        assert_eq!(
+            validate_take(
+                &selection(vec![Good(DIAMOND), Good(CLOTH)], vec![CLOTH, LEATHER], 0),
+                &board(default_market(), 4)
+            ),
+            MoveValidity::Invalid(InvalidMoveReason::ExchangeSameGoodType)
+        );
+    }
+
+    #[test]
+    fn exchange_with_unequal_card_counts_is_invalid() {
+        assert_eq!(
+            validate_take(
+                &selection(vec![Good(DIAMOND), Good(CLOTH)], vec![LEATHER], 0),
+                &board(default_market(), 4)
+            ),
+            MoveValidity::Invalid(InvalidMoveReason::ExchangeCountMismatch)
+        );
+    }
+
+    #[test]
+    fn sell_with_nothing_selected_is_invalid() {
+        assert_eq!(
+            validate_sell(&CardSelection::default(), &official_goods()),
+            MoveValidity::Invalid(InvalidMoveReason::NoCardsSelected)
+        );
+    }
+
+    #[test]
+    fn sell_single_low_value_good_is_valid() {
+        assert_eq!(
+            validate_sell(&selection(vec![], vec![LEATHER], 0), &official_goods()),
+            MoveValidity::Valid(MoveType::SellGoods)
+        );
+    }
+
+    #[test]
+    fn sell_single_high_value_good_is_invalid() {
+        for good in [DIAMOND, GOLD, SILVER] {
+            assert_eq!(
+                validate_sell(&selection(vec![], vec![good], 0), &official_goods()),
+                MoveValidity::Invalid(InvalidMoveReason::SellTooFewGoods(2))
+            );
+        }
+    }
+
+    #[test]
+    fn sell_fewer_goods_than_min_sale_size_is_invalid() {
+        let mut goods = official_goods();
+        goods[LEATHER].min_sale_size = 3;
+
+        assert_eq!(
+            validate_sell(&selection(vec![], vec![LEATHER, LEATHER], 0), &goods),
+            MoveValidity::Invalid(InvalidMoveReason::SellTooFewGoods(3))
+        );
+        assert_eq!(
+            validate_sell(
+                &selection(vec![], vec![LEATHER, LEATHER, LEATHER], 0),
+                &goods
+            ),
+            MoveValidity::Valid(MoveType::SellGoods)
+        );
+    }
+
+    #[test]
+    fn sell_two_high_value_goods_is_valid() {
+        assert_eq!(
+            validate_sell(
+                &selection(vec![], vec![SILVER, SILVER], 0),
+                &official_goods()
+            ),
+            MoveValidity::Valid(MoveType::SellGoods)
+        );
+    }
+
+    #[test]
+    fn sell_mixed_goods_is_invalid() {
+        assert_eq!(
+            validate_sell(
+                &selection(vec![], vec![CLOTH, CLOTH, SPICE], 0),
+                &official_goods()
+            ),
+            MoveValidity::Invalid(InvalidMoveReason::SellMixedGoods)
+        );
+    }
+
+    #[test]
+    fn sell_camels_is_invalid() {
+        assert_eq!(
+            validate_sell(&selection(vec![], vec![CLOTH], 1), &official_goods()),
+            MoveValidity::Invalid(InvalidMoveReason::SellCamels)
+        );
+    }
+
+    #[test]
+    fn sell_from_market_is_invalid() {
+        assert_eq!(
+            validate_sell(
+                &selection(vec![Good(CLOTH)], vec![CLOTH], 0),
+                &official_goods()
+            ),
+            MoveValidity::Invalid(InvalidMoveReason::SellFromMarket)
+        );
+    }
+
+    fn good_type_strategy() -> impl Strategy<Value = GoodType> {
+        (0..official_goods().len()).prop_map(GoodType)
+    }
+
+    fn card_type_strategy() -> impl Strategy<Value = CardType> {
+        prop_oneof![Just(Camel), good_type_strategy().prop_map(Good)]
+    }
+
+    // A board with a full market and a legal hand, along with a selection of cards from it
+    fn board_and_selection_strategy() -> impl Strategy<Value = (BoardState, CardSelection)> {
+        (
+            prop::collection::vec(card_type_strategy(), 5),
+            prop::collection::vec(good_type_strategy(), 0..=7),
+            0..=5_usize,
+            prop::collection::vec(any::<bool>(), 5),
+            prop::collection::vec(any::<bool>(), 7),
+            0..=5_usize,
+            5..=9_usize,
+        )
+            .prop_map(
+                |(
+                    market,
+                    goods_hand,
+                    num_camels_in_hand,
+                    market_mask,
+                    hand_mask,
+                    num_camels,
+                    max_goods_in_hand,
+                )| {
+                    let goods_hand = goods_hand
+                        .into_iter()
+                        .take(max_goods_in_hand)
+                        .collect::<Vec<_>>();
+
+                    let selection = CardSelection {
+                        market: market
+                            .iter()
+                            .zip(market_mask.iter())
+                            .filter(|(_, selected)| **selected)
+                            .map(|(c, _)| *c)
+                            .collect(),
+                        goods_hand: goods_hand
+                            .iter()
+                            .zip(hand_mask.iter())
+                            .filter(|(_, selected)| **selected)
+                            .map(|(g, _)| *g)
+                            .collect(),
+                        num_camels_from_hand: num_camels.min(num_camels_in_hand),
+                    };
+
+                    (
+                        BoardState {
+                            market,
+                            num_goods_in_hand: goods_hand.len(),
+                            max_goods_in_hand,
+                        },
+                        selection,
+                    )
+                },
+            )
+    }
+
+    proptest! {
+        #[test]
+        fn valid_take_never_exceeds_hand_limit((board, selection) in board_and_selection_strategy()) {
+            if let MoveValidity::Valid(move_type) = validate_take(&selection, &board) {
+                let num_goods_after_take = match move_type {
+                    MoveType::TakeSingleGood => board.num_goods_in_hand + 1,
+                    MoveType::ExchangeForGoodsFromMarket => {
+                        board.num_goods_in_hand + selection.num_camels_from_hand
+                    }
+                    _ => board.num_goods_in_hand,
+                };
+                prop_assert!(num_goods_after_take <= board.max_goods_in_hand);
+            }
+        }
+
+        #[test]
+        fn valid_take_all_camels_takes_every_market_camel((board, selection) in board_and_selection_strategy()) {
+            if validate_take(&selection, &board) == MoveValidity::Valid(MoveType::TakeAllCamels) {
+                let num_market_camels = board.market.iter().filter(|c| **c == Camel).count();
+                prop_assert!(selection.market.iter().all(|c| *c == Camel));
+                prop_assert_eq!(selection.market.len(), num_market_camels);
+                prop_assert!(selection.goods_hand.is_empty());
+                prop_assert_eq!(selection.num_camels_from_hand, 0);
+            }
+        }
+
+        #[test]
+        fn valid_exchange_is_balanced_and_changes_good_types((board, selection) in board_and_selection_strategy()) {
+            if validate_take(&selection, &board) == MoveValidity::Valid(MoveType::ExchangeForGoodsFromMarket) {
+                prop_assert!(selection.market.len() >= 2);
+                prop_assert!(selection.market.iter().all(|c| *c != Camel));
+                prop_assert_eq!(
+                    selection.market.len(),
+                    selection.goods_hand.len() + selection.num_camels_from_hand
+                );
+                prop_assert!(selection
+                    .goods_hand
+                    .iter()
+                    .all(|g| !selection.market.contains(&Good(*g))));
+            }
+        }
+
+        #[test]
+        fn valid_sell_is_non_empty_and_of_one_good_type((_board, selection) in board_and_selection_strategy()) {
+            if validate_sell(&selection, &official_goods()) == MoveValidity::Valid(MoveType::SellGoods) {
+                prop_assert!(selection.market.is_empty());
+                prop_assert_eq!(selection.num_camels_from_hand, 0);
+                let good = selection.goods_hand[0];
+                prop_assert!(selection.goods_hand.iter().all(|g| *g == good));
+                prop_assert!(selection.goods_hand.len() >= official_goods()[good].min_sale_size);
+            }
+        }
+
+        #[test]
+        fn take_and_sell_never_agree_on_move_type((board, selection) in board_and_selection_strategy()) {
+            let is_take_valid = matches!(validate_take(&selection, &board), MoveValidity::Valid(_));
+            let is_sell_valid = matches!(validate_sell(&selection, &official_goods()), MoveValidity::Valid(_));
+            prop_assert!(!(is_take_valid && is_sell_valid));
+        }
+    }
+}
@@ -1,52 +1,330 @@
 use bevy::prelude::{Vec2, Vec3};
 
-pub const DECK_START_POS: Vec3 = Vec3::new(300.0, 0.0, 0.0);
-
-pub const DISCARD_PILE_POS: Vec3 = Vec3::new(
-    DECK_START_POS.x + 1.5 * CARD_DIMENSION.x + CARD_PADDING,
-    DECK_START_POS.y,
-    0.,
-);
+use crate::game_resources::game_config::GameConfig;
 
 pub const CARD_DIMENSION: Vec2 = Vec2::new(104.0, 150.0);
 pub const CARD_PADDING: f32 = 20.0;
 
-pub const GOODS_HAND_START_POS: Vec3 = Vec3::new(-5.0 * 0.5 * CARD_DIMENSION.x, -400.0, 0.0);
+pub const TOKEN_RADIUS: f32 = 30.0;
+pub const TOKEN_STACK_SPACING: f32 = 2.8 * TOKEN_RADIUS;
+// Each token further down a stack peeks out to the left of the one above it
+pub const TOKEN_STACK_OFFSET: f32 = 4.0;
+// Leaves room for the tokens peeking out of each stack, and its count
+const TOKEN_STACK_COLUMN_SPACING: f32 = 250.0;
 
-pub const CAMEL_HAND_START_POS: Vec3 = Vec3::new(
-    GOODS_HAND_START_POS.x,
-    GOODS_HAND_START_POS.y + CARD_DIMENSION.y + CARD_PADDING,
-    0.0,
-);
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub enum Orientation {
+    #[default]
+    Landscape,
+    Portrait,
+}
 
-pub const INACTIVE_PLAYER_GOODS_HAND_START_POS: Vec3 = Vec3::new(
-    GOODS_HAND_START_POS.x,
-    GOODS_HAND_START_POS.y * -1.0,
-    GOODS_HAND_START_POS.z,
-);
+impl Orientation {
+    pub fn from_window_size(width: f32, height: f32) -> Self {
+        if height > width {
+            Orientation::Portrait
+        } else {
+            Orientation::Landscape
+        }
+    }
+}
 
-pub fn get_active_player_goods_card_translation(idx: usize) -> Vec3 {
-    GOODS_HAND_START_POS + Vec3::X * idx as f32 * (CARD_DIMENSION.x + CARD_PADDING)
+// Where everything is placed in the world. Only the window's orientation and the sizes of the hands and market
+// change the layout - the camera is scaled so that the layout's bounds fill the window, whatever its size. See
+// layout.rs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layout {
+    pub orientation: Orientation,
+    pub deck_pos: Vec3,
+    pub goods_hand_start_pos: Vec3,
+    // Narrower than a card and its padding when a full hand wouldn't otherwise fit
+    pub goods_hand_card_spacing: f32,
+    // Each good's stack of game tokens is a row, with the bonus token piles underneath. The rows are split into
+    // columns of this many stacks.
+    pub game_token_stacks_start_pos: Vec3,
+    pub token_stacks_per_column: usize,
+    // The tokens won by the player whose hand is at the bottom of the screen, which are mirrored at the top of
+    // the screen for the other player
+    pub player_token_piles_start_pos: Vec3,
+    pub player_token_piles_columns: usize,
+    // The area of the world that must always be visible
+    pub bounds_min: Vec2,
+    pub bounds_max: Vec2,
 }
 
-pub fn get_ai_player_goods_card_translation(idx: usize) -> Vec3 {
-    INACTIVE_PLAYER_GOODS_HAND_START_POS + Vec3::X * idx as f32 * (CARD_DIMENSION.x + CARD_PADDING)
+impl Default for Layout {
+    fn default() -> Self {
+        Layout::new(Orientation::default(), &GameConfig::default())
+    }
 }
 
-pub fn get_market_card_translation(idx: usize) -> Vec3 {
-    DECK_START_POS
-        - (5 - idx) as f32 * CARD_DIMENSION.x * Vec3::X
-        - (5 - idx) as f32 * CARD_PADDING * Vec3::X
+// The x of the left edge of the market, which extends to the left of the deck
+fn get_market_left_edge(deck_pos: Vec3, market_size: usize) -> f32 {
+    deck_pos.x - market_size as f32 * (CARD_DIMENSION.x + CARD_PADDING) - 0.5 * CARD_DIMENSION.x
 }
 
-pub fn get_active_player_camel_card_translation(idx: usize) -> Vec3 {
-    CAMEL_HAND_START_POS + Vec3::X * idx as f32 * (CARD_DIMENSION.x + CARD_PADDING)
+// Where the first card of a full hand goes, and the distance between the cards, so that the hand fits between
+// min_x and max_x. The hand keeps its usual start and spacing if it fits, then moves left, and only then are the
+// cards moved closer together.
+fn get_goods_hand_placement(
+    usual_start_x: f32,
+    min_x: f32,
+    max_x: f32,
+    max_goods_in_hand: usize,
+) -> (f32, f32) {
+    let usual_spacing = CARD_DIMENSION.x + CARD_PADDING;
+    let num_gaps = max_goods_in_hand.saturating_sub(1) as f32;
+    let usual_width = num_gaps * usual_spacing + CARD_DIMENSION.x;
+    let min_start_x = min_x + 0.5 * CARD_DIMENSION.x;
+
+    let start_x = usual_start_x.min(max_x - usual_width + 0.5 * CARD_DIMENSION.x);
+    if start_x >= min_start_x || num_gaps == 0.0 {
+        return (start_x.max(min_start_x), usual_spacing);
+    }
+
+    let spacing = (max_x - min_x - CARD_DIMENSION.x) / num_gaps;
+    (min_start_x, spacing)
+}
+
+impl Layout {
+    pub fn new(orientation: Orientation, game_config: &GameConfig) -> Self {
+        match orientation {
+            // The market runs across the middle with a hand above and below it, the game tokens are stacked on
+            // the left and the tokens each player has won are piled on the right
+            Orientation::Landscape => {
+                let deck_pos = Vec3::new(300.0, 0.0, 0.0);
+                let game_token_stacks_start_pos = Vec3::new(-740.0, 480.0, 0.0);
+                let player_token_piles_start_pos = Vec3::new(580.0, -250.0, 0.0);
+
+                // The hand at the top of the screen is beside the first rows of game tokens
+                let (goods_hand_start_x, goods_hand_card_spacing) = get_goods_hand_placement(
+                    -5.0 * 0.5 * CARD_DIMENSION.x,
+                    game_token_stacks_start_pos.x + TOKEN_STACK_COLUMN_SPACING,
+                    player_token_piles_start_pos.x - TOKEN_RADIUS,
+                    game_config.max_goods_in_hand,
+                );
+
+                Layout {
+                    orientation,
+                    deck_pos,
+                    goods_hand_start_pos: Vec3::new(goods_hand_start_x, -400.0, 0.0),
+                    goods_hand_card_spacing,
+                    game_token_stacks_start_pos,
+                    token_stacks_per_column: usize::MAX,
+                    player_token_piles_start_pos,
+                    player_token_piles_columns: 3,
+                    bounds_min: Vec2::new(
+                        f32::min(
+                            -800.0,
+                            get_market_left_edge(deck_pos, game_config.market_size) - CARD_PADDING,
+                        ),
+                        -600.0,
+                    ),
+                    bounds_max: Vec2::new(800.0, 600.0),
+                }
+            }
+            // The game tokens are stacked above the cards, and the tokens each player has won are piled beyond
+            // their hand
+            Orientation::Portrait => {
+                let deck_pos = Vec3::new(220.0, 0.0, 0.0);
+                let game_token_stacks_start_pos = Vec3::new(-470.0, 880.0, 0.0);
+
+                // Wide enough on the left for the first column of game tokens, or the market if it's wider
+                let bounds_min = Vec2::new(
+                    f32::min(
+                        game_token_stacks_start_pos.x - TOKEN_RADIUS,
+                        get_market_left_edge(deck_pos, game_config.market_size),
+                    ) - CARD_PADDING,
+                    -720.0,
+                );
+                let bounds_max = Vec2::new(520.0, 940.0);
+
+                // Nothing else is level with the hands, so they can stretch across the whole width
+                let (goods_hand_start_x, goods_hand_card_spacing) = get_goods_hand_placement(
+                    -340.0,
+                    bounds_min.x + CARD_PADDING,
+                    bounds_max.x - CARD_PADDING,
+                    game_config.max_goods_in_hand,
+                );
+
+                Layout {
+                    orientation,
+                    deck_pos,
+                    goods_hand_start_pos: Vec3::new(goods_hand_start_x, -400.0, 0.0),
+                    goods_hand_card_spacing,
+                    game_token_stacks_start_pos,
+                    token_stacks_per_column: 3,
+                    player_token_piles_start_pos: Vec3::new(-250.0, -545.0, 0.0),
+                    player_token_piles_columns: 6,
+                    bounds_min,
+                    bounds_max,
+                }
+            }
+        }
+    }
+
+    // Each card further up the deck is raised a little, so the deck looks stacked
+    pub fn get_deck_card_translation(&self, idx: usize) -> Vec3 {
+        self.deck_pos + Vec3::new(idx as f32, idx as f32, idx as f32)
+    }
+
+    pub fn get_discard_pile_translation(&self) -> Vec3 {
+        Vec3::new(
+            self.deck_pos.x + 1.5 * CARD_DIMENSION.x + CARD_PADDING,
+            self.deck_pos.y,
+            0.,
+        )
+    }
+
+    pub fn get_camel_hand_start_translation(&self) -> Vec3 {
+        Vec3::new(
+            self.goods_hand_start_pos.x,
+            self.goods_hand_start_pos.y + CARD_DIMENSION.y + CARD_PADDING,
+            0.0,
+        )
+    }
+
+    pub fn get_active_player_goods_card_translation(&self, idx: usize) -> Vec3 {
+        self.goods_hand_start_pos + Vec3::X * idx as f32 * self.goods_hand_card_spacing
+    }
+
+    // The goods hand at the top of the screen - the computer's, or the inactive player's in local multiplayer
+    pub fn get_ai_player_goods_card_translation(&self, idx: usize) -> Vec3 {
+        let start = Vec3::new(
+            self.goods_hand_start_pos.x,
+            self.goods_hand_start_pos.y * -1.0,
+            self.goods_hand_start_pos.z,
+        );
+
+        start + Vec3::X * idx as f32 * self.goods_hand_card_spacing
+    }
+
+    // The market extends to the left of the deck
+    pub fn get_market_card_translation(&self, idx: usize, market_size: usize) -> Vec3 {
+        self.deck_pos
+            - (market_size - idx) as f32 * CARD_DIMENSION.x * Vec3::X
+            - (market_size - idx) as f32 * CARD_PADDING * Vec3::X
+    }
+
+    pub fn get_active_player_camel_card_translation(&self, idx: usize) -> Vec3 {
+        self.get_camel_hand_start_translation()
+            + Vec3::X * idx as f32 * (CARD_DIMENSION.x + CARD_PADDING)
+    }
+
+    pub fn get_opponent_camel_hand_translation(&self) -> Vec3 {
+        let camel_hand_start = self.get_camel_hand_start_translation();
+
+        Vec3::new(
+            camel_hand_start.x,
+            camel_hand_start.y * -1.0,
+            camel_hand_start.z,
+        )
+    }
+
+    pub fn get_game_token_translation(&self, stack_idx: usize, token_idx: usize) -> Vec3 {
+        let column = (stack_idx / self.token_stacks_per_column) as f32;
+        let row = (stack_idx % self.token_stacks_per_column) as f32;
+
+        self.game_token_stacks_start_pos
+            + Vec3::new(
+                column * TOKEN_STACK_COLUMN_SPACING + token_idx as f32 * TOKEN_STACK_OFFSET,
+                -row * TOKEN_STACK_SPACING,
+                token_idx as f32 * 0.1,
+            )
+    }
+
+    pub fn get_player_token_pile_translation(
+        &self,
+        pile_idx: usize,
+        is_bottom_player: bool,
+    ) -> Vec3 {
+        let column = (pile_idx % self.player_token_piles_columns) as f32;
+        let row = (pile_idx / self.player_token_piles_columns) as f32;
+        let translation = self.player_token_piles_start_pos
+            + Vec3::new(
+                column * TOKEN_STACK_SPACING,
+                -row * TOKEN_STACK_SPACING,
+                0.0,
+            );
+
+        if is_bottom_player {
+            translation
+        } else {
+            Vec3::new(translation.x, -translation.y, translation.z)
+        }
+    }
 }
 
-pub fn get_opponent_camel_hand_translation() -> Vec3 {
-    Vec3::new(
-        CAMEL_HAND_START_POS.x,
-        CAMEL_HAND_START_POS.y * -1.0,
-        CAMEL_HAND_START_POS.z,
-    )
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fixtures::all_game_configs;
+
+    fn get_goods_hand_edges(layout: &Layout, game_config: &GameConfig) -> (f32, f32) {
+        let last_card =
+            layout.get_active_player_goods_card_translation(game_config.max_goods_in_hand - 1);
+
+        (
+            layout.goods_hand_start_pos.x - 0.5 * CARD_DIMENSION.x,
+            last_card.x + 0.5 * CARD_DIMENSION.x,
+        )
+    }
+
+    #[test]
+    fn full_hands_fit_in_bounds_without_overlapping() {
+        for game_config in all_game_configs() {
+            for orientation in [Orientation::Landscape, Orientation::Portrait] {
+                let layout = Layout::new(orientation, &game_config);
+                let (left_edge, right_edge) = get_goods_hand_edges(&layout, &game_config);
+
+                assert!(
+                    left_edge >= layout.bounds_min.x,
+                    "{:?} {:?}",
+                    orientation,
+                    game_config
+                );
+                assert!(
+                    right_edge <= layout.bounds_max.x,
+                    "{:?} {:?}",
+                    orientation,
+                    game_config
+                );
+                assert!(layout.goods_hand_card_spacing > CARD_DIMENSION.x);
+            }
+        }
+    }
+
+    #[test]
+    fn full_hands_stay_clear_of_the_token_piles_in_landscape() {
+        for game_config in all_game_configs() {
+            let layout = Layout::new(Orientation::Landscape, &game_config);
+            let (_, right_edge) = get_goods_hand_edges(&layout, &game_config);
+
+            assert!(right_edge <= layout.player_token_piles_start_pos.x - TOKEN_RADIUS);
+        }
+    }
+
+    #[test]
+    fn markets_fit_in_bounds() {
+        for game_config in all_game_configs() {
+            for orientation in [Orientation::Landscape, Orientation::Portrait] {
+                let layout = Layout::new(orientation, &game_config);
+                let first_card = layout.get_market_card_translation(0, game_config.market_size);
+
+                assert!(first_card.x - 0.5 * CARD_DIMENSION.x >= layout.bounds_min.x);
+            }
+        }
+    }
+
+    #[test]
+    fn official_hands_keep_the_usual_spacing() {
+        for orientation in [Orientation::Landscape, Orientation::Portrait] {
+            let layout = Layout::new(orientation, &GameConfig::default());
+
+            assert_eq!(
+                layout.goods_hand_card_spacing,
+                CARD_DIMENSION.x + CARD_PADDING
+            );
+        }
+    }
 }
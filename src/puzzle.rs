@@ -0,0 +1,393 @@
+use bevy::asset::LoadState;
+use bevy::prelude::*;
+
+use crate::common_systems::despawn_entity_with_component;
+use crate::game::{
+    get_final_score, get_tokens_score, ActivePlayer, AiPlayer, CamelsHandOwner, HumanPlayer,
+    TokensOwner,
+};
+use crate::game_resources::deck_definition::DeckDefinitionHandle;
+use crate::game_resources::game_config::GameConfig;
+use crate::game_resources::puzzle_definition::{
+    PuzzleDefinition, PuzzleDefinitionHandle, PuzzleDefinitionPlugin, PuzzleGoal,
+};
+use crate::localization::Localization;
+use crate::resources::GameState;
+use crate::states::AppState;
+
+// The puzzles offered in the main menu, in the order they are cycled through
+pub const PUZZLES: [&str; 2] = [
+    "puzzles/two_turn_trader.puzzle.ron",
+    "puzzles/last_stand.puzzle.ron",
+];
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum PuzzleOutcome {
+    Solved,
+    Failed,
+}
+
+// Progress towards the goal of the puzzle being played. Only present during a puzzle.
+pub struct PuzzleState {
+    pub name: String,
+    pub goal: PuzzleGoal,
+    // Rupees from tokens the player had already won when the puzzle started
+    pub starting_rupees: usize,
+    pub num_turns_taken: usize,
+    pub outcome: Option<PuzzleOutcome>,
+}
+
+impl PuzzleState {
+    pub fn new(puzzle_definition: &PuzzleDefinition, starting_rupees: usize) -> Self {
+        Self {
+            name: puzzle_definition.name.clone(),
+            goal: puzzle_definition.goal,
+            starting_rupees,
+            num_turns_taken: 0,
+            outcome: None,
+        }
+    }
+
+    fn get_goal_text(&self, localization: &Localization) -> String {
+        let goal = self.goal.localize(localization);
+
+        match self.goal {
+            PuzzleGoal::ScoreRupees { turns, .. } if turns > 1 => localization.format(
+                "puzzle.goal_with_turn",
+                &[
+                    ("name", &self.name),
+                    ("goal", &goal),
+                    ("turn", &(self.num_turns_taken + 1).min(turns)),
+                    ("turns", &turns),
+                ],
+            ),
+            _ => localization.format("puzzle.goal", &[("name", &self.name), ("goal", &goal)]),
+        }
+    }
+}
+
+// Waits for the puzzle to load, then loads the deck definition it is played with - see setup_game
+#[allow(clippy::too_many_arguments)]
+fn load_puzzle_definition(
+    mut commands: Commands,
+    mut state: ResMut<State<AppState>>,
+    mut game_state: ResMut<GameState>,
+    mut game_config: ResMut<GameConfig>,
+    asset_server: Res<AssetServer>,
+    puzzle_definitions: Res<Assets<PuzzleDefinition>>,
+    puzzle_definition_handle: Option<Res<PuzzleDefinitionHandle>>,
+    localization: Res<Localization>,
+) {
+    let puzzle_definition_handle = match puzzle_definition_handle {
+        Some(handle) => handle,
+        None => return,
+    };
+
+    let puzzle_definition = match asset_server.get_load_state(&puzzle_definition_handle.0) {
+        LoadState::Loaded => puzzle_definitions.get(&puzzle_definition_handle.0).unwrap(),
+        LoadState::Failed => {
+            commands.remove_resource::<PuzzleDefinitionHandle>();
+            game_state.setup_error = Some(localization.format(
+                "menu.could_not_load",
+                &[("path", &PUZZLES[game_state.selected_puzzle])],
+            ));
+            state.set(AppState::MainMenu).unwrap();
+            return;
+        }
+        _ => return,
+    };
+
+    commands.remove_resource::<PuzzleDefinitionHandle>();
+
+    game_config.deck_definition_path = puzzle_definition.deck_definition.clone();
+    commands.insert_resource(DeckDefinitionHandle(
+        asset_server.load(&game_config.deck_definition_path),
+    ));
+    commands.insert_resource(puzzle_definition.clone());
+}
+
+// Runs as each turn ends, before the active player changes. Ends the game as soon as the puzzle is solved or failed.
+fn check_puzzle_goal(
+    puzzle_state: Option<ResMut<PuzzleState>>,
+    mut game_state: ResMut<GameState>,
+    game_config: Res<GameConfig>,
+    human_player_query: Query<
+        (&TokensOwner, &CamelsHandOwner, Option<&ActivePlayer>),
+        With<HumanPlayer>,
+    >,
+    ai_player_query: Query<(&TokensOwner, &CamelsHandOwner), With<AiPlayer>>,
+) {
+    let mut puzzle_state = match puzzle_state {
+        Some(puzzle_state) if puzzle_state.outcome.is_none() => puzzle_state,
+        _ => return,
+    };
+
+    let (tokens_owner, camels_hand, active_player) = human_player_query.single();
+
+    if active_player.is_some() {
+        puzzle_state.num_turns_taken += 1;
+    }
+
+    let outcome = match puzzle_state.goal {
+        PuzzleGoal::ScoreRupees { rupees, turns } => {
+            let rupees_earned = get_tokens_score(&tokens_owner.0) - puzzle_state.starting_rupees;
+
+            if rupees_earned >= rupees {
+                Some(PuzzleOutcome::Solved)
+            } else if puzzle_state.num_turns_taken >= turns || game_state.is_game_over {
+                Some(PuzzleOutcome::Failed)
+            } else {
+                None
+            }
+        }
+        PuzzleGoal::WinGame if game_state.is_game_over => {
+            let (opponent_tokens_owner, opponent_camels_hand) = ai_player_query.single();
+
+            let score = get_final_score(
+                &tokens_owner.0,
+                camels_hand.0,
+                opponent_camels_hand.0,
+                &game_config,
+            );
+            let opponent_score = get_final_score(
+                &opponent_tokens_owner.0,
+                opponent_camels_hand.0,
+                camels_hand.0,
+                &game_config,
+            );
+
+            if score > opponent_score {
+                Some(PuzzleOutcome::Solved)
+            } else {
+                Some(PuzzleOutcome::Failed)
+            }
+        }
+        PuzzleGoal::WinGame => None,
+    };
+
+    if outcome.is_some() {
+        puzzle_state.outcome = outcome;
+        game_state.is_game_over = true;
+    }
+}
+
+#[derive(Component)]
+struct PuzzleGoalUiRoot;
+
+fn setup_puzzle_goal_ui(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    localization: Res<Localization>,
+    puzzle_state: Option<Res<PuzzleState>>,
+) {
+    let puzzle_state = match puzzle_state {
+        Some(puzzle_state) => puzzle_state,
+        None => return,
+    };
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect::new(Val::Px(0.), Val::Px(0.), Val::Px(0.), Val::Auto),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .insert(PuzzleGoalUiRoot)
+        .with_children(|parent| {
+            parent.spawn_bundle(
+                TextBundle::from_section(
+                    puzzle_state.get_goal_text(&localization),
+                    TextStyle {
+                        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                        font_size: 30.0,
+                        color: Color::rgb(0.9, 0.9, 0.9),
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::all(Val::Px(10.)),
+                    ..default()
+                }),
+            );
+        });
+}
+
+fn update_puzzle_goal_text(
+    puzzle_state: Option<Res<PuzzleState>>,
+    localization: Res<Localization>,
+    ui_root_query: Query<&Children, With<PuzzleGoalUiRoot>>,
+    mut text_query: Query<&mut Text>,
+) {
+    let puzzle_state = match puzzle_state {
+        Some(puzzle_state) if puzzle_state.is_changed() || localization.is_changed() => {
+            puzzle_state
+        }
+        _ => return,
+    };
+
+    for children in ui_root_query.iter() {
+        for &child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(child) {
+                text.sections[0].value = puzzle_state.get_goal_text(&localization);
+            }
+        }
+    }
+}
+
+pub struct PuzzlePlugin;
+
+impl Plugin for PuzzlePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(PuzzleDefinitionPlugin)
+            .add_system_set(
+                SystemSet::on_update(AppState::InitGame).with_system(load_puzzle_definition),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::InitGame).with_system(setup_puzzle_goal_ui),
+            )
+            .add_system_set(
+                SystemSet::on_enter(AppState::WaitForTweensToFinish).with_system(check_puzzle_goal),
+            )
+            .add_system(update_puzzle_goal_text);
+
+        for state in [AppState::GameOver, AppState::MainMenu, AppState::InitGame] {
+            app.add_system_set(
+                SystemSet::on_enter(state)
+                    .with_system(despawn_entity_with_component::<PuzzleGoalUiRoot>),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_resources::tokens::Tokens;
+    use crate::test_fixtures::{CLOTH, LEATHER};
+
+    const STARTING_RUPEES: usize = 5;
+    const SCORE_10_IN_2_TURNS: PuzzleGoal = PuzzleGoal::ScoreRupees {
+        rupees: 10,
+        turns: 2,
+    };
+
+    // The human player has earned the given rupees since the puzzle started, and the computer has earned nothing
+    fn create_app(goal: PuzzleGoal, num_turns_taken: usize, rupees_earned: usize) -> App {
+        let mut tokens = Tokens::create_empty(6);
+        tokens.goods[CLOTH] = vec![STARTING_RUPEES];
+        tokens.goods[LEATHER] = vec![rupees_earned];
+
+        let mut app = App::new();
+        app.insert_resource(PuzzleState {
+            name: "Test".to_string(),
+            goal,
+            starting_rupees: STARTING_RUPEES,
+            num_turns_taken,
+            outcome: None,
+        })
+        .init_resource::<GameState>()
+        .insert_resource(GameConfig::default())
+        .add_system(check_puzzle_goal);
+
+        app.world
+            .spawn()
+            .insert(HumanPlayer)
+            .insert(ActivePlayer)
+            .insert(TokensOwner(tokens))
+            .insert(CamelsHandOwner(0));
+        app.world
+            .spawn()
+            .insert(AiPlayer)
+            .insert(TokensOwner(Tokens::create_empty(6)))
+            .insert(CamelsHandOwner(0));
+
+        app
+    }
+
+    fn get_outcome(app: &App) -> Option<PuzzleOutcome> {
+        app.world.resource::<PuzzleState>().outcome
+    }
+
+    fn is_game_over(app: &App) -> bool {
+        app.world.resource::<GameState>().is_game_over
+    }
+
+    #[test]
+    fn score_rupees_carries_on_until_the_last_turn() {
+        let mut app = create_app(SCORE_10_IN_2_TURNS, 0, 9);
+
+        app.update();
+
+        assert_eq!(get_outcome(&app), None);
+        assert_eq!(app.world.resource::<PuzzleState>().num_turns_taken, 1);
+        assert!(!is_game_over(&app));
+    }
+
+    #[test]
+    fn score_rupees_is_solved_on_the_last_turn() {
+        let mut app = create_app(SCORE_10_IN_2_TURNS, 1, 10);
+
+        app.update();
+
+        assert_eq!(get_outcome(&app), Some(PuzzleOutcome::Solved));
+        assert!(is_game_over(&app));
+    }
+
+    #[test]
+    fn score_rupees_is_failed_after_the_last_turn() {
+        let mut app = create_app(SCORE_10_IN_2_TURNS, 1, 9);
+
+        app.update();
+
+        assert_eq!(get_outcome(&app), Some(PuzzleOutcome::Failed));
+        assert!(is_game_over(&app));
+    }
+
+    #[test]
+    fn score_rupees_is_solved_when_the_game_ends_with_enough_rupees() {
+        let mut app = create_app(SCORE_10_IN_2_TURNS, 0, 10);
+        app.world.resource_mut::<GameState>().is_game_over = true;
+
+        app.update();
+
+        assert_eq!(get_outcome(&app), Some(PuzzleOutcome::Solved));
+    }
+
+    #[test]
+    fn score_rupees_is_failed_when_the_game_ends_early() {
+        let mut app = create_app(SCORE_10_IN_2_TURNS, 0, 9);
+        app.world.resource_mut::<GameState>().is_game_over = true;
+
+        app.update();
+
+        assert_eq!(get_outcome(&app), Some(PuzzleOutcome::Failed));
+    }
+
+    #[test]
+    fn opponent_turns_do_not_count() {
+        let mut app = create_app(SCORE_10_IN_2_TURNS, 1, 0);
+        let mut human_player_query = app.world.query_filtered::<Entity, With<HumanPlayer>>();
+        let human_player = human_player_query.single(&app.world);
+        app.world.entity_mut(human_player).remove::<ActivePlayer>();
+
+        app.update();
+
+        assert_eq!(get_outcome(&app), None);
+        assert_eq!(app.world.resource::<PuzzleState>().num_turns_taken, 1);
+    }
+
+    #[test]
+    fn win_game_is_only_decided_when_the_game_ends() {
+        let mut app = create_app(PuzzleGoal::WinGame, 0, 1);
+
+        app.update();
+        assert_eq!(get_outcome(&app), None);
+
+        app.world.resource_mut::<GameState>().is_game_over = true;
+        app.update();
+        assert_eq!(get_outcome(&app), Some(PuzzleOutcome::Solved));
+    }
+}
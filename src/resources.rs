@@ -1,5 +1,20 @@
+use crate::{ai::personality::AiPersonality, game_resources::game_config::RulesPreset};
+
 #[derive(Default)]
 pub struct GameState {
     pub is_game_over: bool,
     pub is_playing_ai: bool,
+    pub ai_personality: AiPersonality,
+    pub rules_preset: RulesPreset,
+    // Puzzles are played against the computer, from a position loaded from one of puzzle::PUZZLES
+    pub is_playing_puzzle: bool,
+    pub selected_puzzle: usize,
+    // The daily challenge is played against the computer, with the deal decided by the date - see daily_challenge.rs
+    pub is_playing_daily_challenge: bool,
+    // The tutorial is played against scripted moves from the computer - see tutorial.rs
+    pub is_playing_tutorial: bool,
+    // Set by each rematch, so that the players take turns to start. Player two is the computer against the computer.
+    pub is_second_player_starting: bool,
+    // Why the last game could not be set up, shown in the main menu
+    pub setup_error: Option<String>,
 }
@@ -0,0 +1,409 @@
+use bevy::prelude::*;
+use itertools::Itertools;
+
+use crate::game_resources::card::GoodType;
+use crate::game_resources::deck_definition::DeckDefinition;
+use crate::game_resources::tokens::{BonusType, Tokens};
+use crate::localization::Localization;
+use crate::move_history::MoveHistory;
+
+const HEADING_FONT_SIZE: f32 = 30.0;
+const BREAKDOWN_FONT_SIZE: f32 = 20.0;
+const CHART_WIDTH: f32 = 480.0;
+const CHART_HEIGHT: f32 = 80.0;
+
+// Each player's colour in the rupee chart, in the order the players are listed on the game over screen
+pub const PLAYER_CHART_COLORS: [Color; 2] = [Color::GOLD, Color::TURQUOISE];
+
+// Everything that one player's final score is made of, shown on the game over screen
+pub struct ScoreBreakdown<'a> {
+    pub heading: String,
+    pub tokens: &'a Tokens,
+    pub goods_hand: &'a [GoodType],
+    pub num_camels: usize,
+    // Zero unless the player was awarded the camel bonus
+    pub camel_bonus: usize,
+}
+
+fn create_text(
+    asset_server: &AssetServer,
+    value: String,
+    font_size: f32,
+    color: Color,
+) -> TextBundle {
+    TextBundle::from_section(
+        value,
+        TextStyle {
+            font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+            font_size,
+            color,
+        },
+    )
+    .with_style(Style {
+        margin: UiRect::new(Val::Px(8.0), Val::Px(8.0), Val::Px(2.0), Val::Px(2.0)),
+        ..default()
+    })
+}
+
+// "7 + 5 + 5 = 17", or just "5" for a single token
+fn get_tokens_sum_text(values: &[usize]) -> String {
+    match values {
+        [value] => value.to_string(),
+        _ => format!(
+            "{} = {}",
+            values.iter().join(" + "),
+            values.iter().sum::<usize>()
+        ),
+    }
+}
+
+impl ScoreBreakdown<'_> {
+    // One line for each good and bonus token size that earned the player anything, then their camels and the
+    // goods left in their hand
+    fn get_lines(
+        &self,
+        deck_definition: &DeckDefinition,
+        localization: &Localization,
+    ) -> Vec<String> {
+        let mut lines = vec![];
+
+        for (good_type, values) in self.tokens.goods.iter() {
+            if !values.is_empty() {
+                lines.push(localization.format(
+                    "score_breakdown.goods_tokens",
+                    &[
+                        (
+                            "good",
+                            &localization.get_good_name(&deck_definition.goods[good_type].name),
+                        ),
+                        ("tokens", &get_tokens_sum_text(values)),
+                    ],
+                ));
+            }
+        }
+
+        if self.tokens.goods.values().all(|values| values.is_empty()) {
+            lines.push(localization.get("score_breakdown.no_goods_tokens"));
+        }
+
+        for (bonus_type, values) in self.tokens.bonus.iter() {
+            if !values.is_empty() {
+                let bonus_key = match bonus_type {
+                    BonusType::Three => "score_breakdown.bonus_three",
+                    BonusType::Four => "score_breakdown.bonus_four",
+                    BonusType::Five => "score_breakdown.bonus_five",
+                };
+
+                lines.push(
+                    localization.format(bonus_key, &[("tokens", &get_tokens_sum_text(values))]),
+                );
+            }
+        }
+
+        lines.push(if self.camel_bonus > 0 {
+            localization.format(
+                "score_breakdown.camels_with_bonus",
+                &[("count", &self.num_camels), ("bonus", &self.camel_bonus)],
+            )
+        } else {
+            localization.format("score_breakdown.camels", &[("count", &self.num_camels)])
+        });
+
+        lines.push(if self.goods_hand.is_empty() {
+            localization.get("score_breakdown.empty_hand")
+        } else {
+            localization.format(
+                "score_breakdown.hand",
+                &[(
+                    "goods",
+                    &self
+                        .goods_hand
+                        .iter()
+                        .map(|good_type| {
+                            localization.get_good_name(&deck_definition.goods[*good_type].name)
+                        })
+                        .join(", "),
+                )],
+            )
+        });
+
+        lines
+    }
+}
+
+// A column with the player's score as its heading, over the breakdown of where it came from
+pub fn spawn_score_breakdown(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    localization: &Localization,
+    deck_definition: &DeckDefinition,
+    score_breakdown: &ScoreBreakdown,
+    color: Color,
+) -> Entity {
+    let lines = score_breakdown.get_lines(deck_definition, localization);
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::ColumnReverse,
+                align_items: AlignItems::FlexStart,
+                margin: UiRect::new(Val::Px(20.0), Val::Px(20.0), Val::Px(5.0), Val::Px(5.0)),
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn_bundle(create_text(
+                asset_server,
+                score_breakdown.heading.clone(),
+                HEADING_FONT_SIZE,
+                color,
+            ));
+
+            for line in lines {
+                parent.spawn_bundle(create_text(
+                    asset_server,
+                    line,
+                    BREAKDOWN_FONT_SIZE,
+                    Color::rgb(0.9, 0.9, 0.9),
+                ));
+            }
+        })
+        .id()
+}
+
+// Each player's rupees from tokens before the first turn and after every turn. Worked back from their final
+// tokens, since the players in a puzzle may start with tokens already won.
+pub fn get_rupee_totals_by_turn(
+    move_history: &MoveHistory,
+    players: &[(Entity, usize)],
+) -> Vec<Vec<usize>> {
+    players
+        .iter()
+        .map(|(player, final_tokens_score)| {
+            let rupees_won_by_turn = move_history
+                .entries
+                .iter()
+                .map(|entry| {
+                    if entry.player == *player {
+                        entry.recorded_move.get_rupees_won()
+                    } else {
+                        0
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            let rupees_won = rupees_won_by_turn.iter().sum::<usize>();
+            debug_assert!(
+                rupees_won <= *final_tokens_score,
+                "The move history has more rupees won than the player's tokens are worth"
+            );
+            let starting_total = final_tokens_score.saturating_sub(rupees_won);
+
+            std::iter::once(starting_total)
+                .chain(
+                    rupees_won_by_turn
+                        .into_iter()
+                        .scan(starting_total, |total, rupees_won| {
+                            *total += rupees_won;
+                            Some(*total)
+                        }),
+                )
+                .collect()
+        })
+        .collect()
+}
+
+// A bar for each player after every turn, as tall as their rupee total, over a legend of the players' colours
+pub fn spawn_rupee_chart(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    localization: &Localization,
+    player_names: &[String],
+    rupee_totals_by_turn: &[Vec<usize>],
+) -> Entity {
+    let num_points = rupee_totals_by_turn
+        .iter()
+        .map(|totals| totals.len())
+        .max()
+        .unwrap_or_default()
+        .max(1);
+    let max_total = rupee_totals_by_turn
+        .iter()
+        .flatten()
+        .copied()
+        .max()
+        .unwrap_or_default()
+        .max(1);
+    let point_width = CHART_WIDTH / num_points as f32;
+    let bar_width = point_width / rupee_totals_by_turn.len().max(1) as f32;
+
+    let chart_root_entity = commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::ColumnReverse,
+                align_items: AlignItems::Center,
+                margin: UiRect::all(Val::Px(5.0)),
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .id();
+
+    let title_entity = commands
+        .spawn_bundle(create_text(
+            asset_server,
+            localization.format("score_breakdown.chart_title", &[("max", &max_total)]),
+            BREAKDOWN_FONT_SIZE,
+            Color::rgb(0.9, 0.9, 0.9),
+        ))
+        .id();
+
+    let legend_entity = commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Row,
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .with_children(|parent| {
+            for (player_name, color) in player_names.iter().zip(PLAYER_CHART_COLORS) {
+                parent.spawn_bundle(create_text(
+                    asset_server,
+                    player_name.clone(),
+                    BREAKDOWN_FONT_SIZE,
+                    color,
+                ));
+            }
+        })
+        .id();
+
+    // The bars rise from the bottom of the chart, since the UI's y axis points up
+    let chart_entity = commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Px(CHART_WIDTH), Val::Px(CHART_HEIGHT)),
+                flex_direction: FlexDirection::Row,
+                align_items: AlignItems::FlexStart,
+                ..default()
+            },
+            color: Color::rgba(0.0, 0.0, 0.0, 0.3).into(),
+            ..default()
+        })
+        .with_children(|parent| {
+            for point_idx in 0..num_points {
+                parent
+                    .spawn_bundle(NodeBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(point_width), Val::Percent(100.0)),
+                            flex_direction: FlexDirection::Row,
+                            align_items: AlignItems::FlexStart,
+                            ..default()
+                        },
+                        color: Color::NONE.into(),
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        for (totals, color) in rupee_totals_by_turn.iter().zip(PLAYER_CHART_COLORS)
+                        {
+                            let total = totals.get(point_idx).copied().unwrap_or_default();
+
+                            parent.spawn_bundle(NodeBundle {
+                                style: Style {
+                                    size: Size::new(
+                                        Val::Px(bar_width),
+                                        Val::Percent(100.0 * total as f32 / max_total as f32),
+                                    ),
+                                    ..default()
+                                },
+                                color: color.into(),
+                                ..default()
+                            });
+                        }
+                    });
+            }
+        })
+        .id();
+
+    commands
+        .entity(chart_root_entity)
+        .push_children(&[title_entity, chart_entity, legend_entity]);
+
+    chart_root_entity
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::move_history::RecordedMove;
+    use crate::test_fixtures::{history_entry, LEATHER};
+
+    fn sale(goods_tokens_value: usize, bonus_value: Option<usize>) -> RecordedMove {
+        RecordedMove::Sell {
+            good_type: LEATHER,
+            num_sold: if bonus_value.is_some() { 3 } else { 1 },
+            goods_tokens_value,
+            bonus_value,
+        }
+    }
+
+    #[test]
+    fn tokens_sum_text_of_a_single_token_is_its_value() {
+        assert_eq!(get_tokens_sum_text(&[5]), "5");
+    }
+
+    #[test]
+    fn tokens_sum_text_adds_up_the_tokens() {
+        assert_eq!(get_tokens_sum_text(&[7, 5, 5]), "7 + 5 + 5 = 17");
+    }
+
+    #[test]
+    fn rupee_totals_of_an_empty_history_are_the_final_scores() {
+        let players = [(Entity::from_raw(0), 0), (Entity::from_raw(1), 12)];
+
+        assert_eq!(
+            get_rupee_totals_by_turn(&MoveHistory::default(), &players),
+            vec![vec![0], vec![12]]
+        );
+    }
+
+    #[test]
+    fn rupee_totals_include_the_bonus_tokens_won() {
+        let player = Entity::from_raw(0);
+        let opponent = Entity::from_raw(1);
+        let move_history = MoveHistory {
+            entries: vec![
+                history_entry(player, sale(4, None)),
+                history_entry(opponent, RecordedMove::TakeAllCamels(2)),
+                history_entry(player, sale(6, Some(3))),
+            ],
+        };
+
+        assert_eq!(
+            get_rupee_totals_by_turn(&move_history, &[(player, 13), (opponent, 0)]),
+            vec![vec![0, 4, 4, 13], vec![0, 0, 0, 0]]
+        );
+    }
+
+    #[test]
+    fn rupee_totals_start_from_the_tokens_a_puzzle_starts_with() {
+        let player = Entity::from_raw(0);
+        let opponent = Entity::from_raw(1);
+        let move_history = MoveHistory {
+            entries: vec![
+                history_entry(player, RecordedMove::TakeSingleGood(LEATHER)),
+                history_entry(opponent, sale(5, None)),
+                history_entry(player, sale(6, Some(2))),
+            ],
+        };
+
+        assert_eq!(
+            get_rupee_totals_by_turn(&move_history, &[(player, 18), (opponent, 15)]),
+            vec![vec![10, 10, 10, 18], vec![10, 10, 15, 15]]
+        );
+    }
+}
@@ -0,0 +1,82 @@
+use bevy::prelude::*;
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+
+use crate::ai::personality::AiDifficulty;
+use crate::animation::AnimationSpeed;
+use crate::hand_sorting::HandSortOrder;
+use crate::localization::{Localization, DEFAULT_LANGUAGE};
+use crate::storage;
+
+const SETTINGS_STORAGE_KEY: &str = "settings";
+
+pub const NUM_PLAYER_NAMES: usize = 2;
+pub const MAX_PLAYER_NAME_LENGTH: usize = 16;
+
+pub fn get_default_player_name(player_idx: usize, localization: &Localization) -> String {
+    localization.format(
+        "settings.default_player_name",
+        &[("number", &(player_idx + 1))],
+    )
+}
+
+// Options chosen in the settings menu, kept between runs of the game. Settings missing from an older save
+// take their default values.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub animation_speed: AnimationSpeed,
+    // The first player is the one playing against the computer. Empty until a name is typed in, so that the
+    // default name is shown in the chosen language - see get_player_name.
+    pub player_names: [String; NUM_PLAYER_NAMES],
+    pub ai_difficulty: AiDifficulty,
+    pub hand_sort_order: HandSortOrder,
+    // The official rules let players keep their number of camels secret
+    pub show_opponent_camel_count: bool,
+    // The code of a language in assets/locales
+    pub language: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            animation_speed: AnimationSpeed::default(),
+            player_names: Default::default(),
+            ai_difficulty: AiDifficulty::default(),
+            hand_sort_order: HandSortOrder::default(),
+            show_opponent_camel_count: false,
+            language: DEFAULT_LANGUAGE.to_string(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn load() -> Self {
+        let settings = match storage::load(SETTINGS_STORAGE_KEY) {
+            Some(settings) => settings,
+            None => return Self::default(),
+        };
+
+        ron::from_str(&settings).unwrap_or_else(|err| {
+            warn!("Could not read settings: {}", err);
+            Self::default()
+        })
+    }
+
+    pub fn get_player_name(&self, player_idx: usize, localization: &Localization) -> String {
+        match self.player_names[player_idx].as_str() {
+            "" => get_default_player_name(player_idx, localization),
+            name => name.to_string(),
+        }
+    }
+
+    pub fn save(&self) {
+        let saved = ron::ser::to_string_pretty(self, PrettyConfig::default())
+            .map_err(|err| err.to_string())
+            .and_then(|settings| storage::save(SETTINGS_STORAGE_KEY, &settings));
+
+        if let Err(err) = saved {
+            warn!("Could not save settings: {}", err);
+        }
+    }
+}
@@ -7,6 +7,8 @@ pub enum AppState {
     InGame,
     WaitForTweensToFinish,
     GameOver,
+    // Pushed on top of the main menu, or of the game to pause it
+    Settings,
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
@@ -0,0 +1,88 @@
+// Small pieces of data kept between runs of the game: a file per key in the game's directory in the user's
+// data directory natively, and an entry per key in the browser's localStorage on the Web.
+
+#[cfg(not(target_arch = "wasm32"))]
+mod platform {
+    use std::{env, fs, path::PathBuf};
+
+    const GAME_DIR_NAME: &str = "bevy_jaipur";
+    // Used instead, relative to the working directory, when the user's data directory can't be found
+    const FALLBACK_SAVES_DIR: &str = "saves";
+
+    #[cfg(target_os = "windows")]
+    fn get_user_data_dir() -> Option<PathBuf> {
+        env::var_os("APPDATA").map(PathBuf::from)
+    }
+
+    #[cfg(target_os = "macos")]
+    fn get_user_data_dir() -> Option<PathBuf> {
+        env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    fn get_user_data_dir() -> Option<PathBuf> {
+        env::var_os("XDG_DATA_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+    }
+
+    fn get_saves_dir() -> PathBuf {
+        get_user_data_dir()
+            .map(|dir| dir.join(GAME_DIR_NAME))
+            .unwrap_or_else(|| PathBuf::from(FALLBACK_SAVES_DIR))
+    }
+
+    fn get_path(key: &str) -> PathBuf {
+        get_saves_dir().join(format!("{}.ron", key))
+    }
+
+    pub fn load(key: &str) -> Option<String> {
+        fs::read_to_string(get_path(key)).ok()
+    }
+
+    pub fn save(key: &str, value: &str) -> Result<(), String> {
+        fs::create_dir_all(get_saves_dir()).map_err(|err| err.to_string())?;
+        fs::write(get_path(key), value).map_err(|err| err.to_string())
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod platform {
+    use web_sys::Storage;
+
+    const KEY_PREFIX: &str = "bevy_jaipur.";
+
+    fn get_local_storage() -> Option<Storage> {
+        web_sys::window()?.local_storage().ok()?
+    }
+
+    pub fn load(key: &str) -> Option<String> {
+        get_local_storage()?
+            .get_item(&format!("{}{}", KEY_PREFIX, key))
+            .ok()?
+    }
+
+    pub fn save(key: &str, value: &str) -> Result<(), String> {
+        get_local_storage()
+            .ok_or_else(|| "localStorage is not available".to_string())?
+            .set_item(&format!("{}{}", KEY_PREFIX, key), value)
+            .map_err(|err| format!("{:?}", err))
+    }
+}
+
+pub use platform::{load, save};
+
+// Seconds since the Unix epoch. std::time::SystemTime is not available in the browser.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn get_unix_time_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn get_unix_time_secs() -> u64 {
+    (js_sys::Date::now() / 1000.) as u64
+}
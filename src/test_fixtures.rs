@@ -0,0 +1,66 @@
+// Shared by the unit tests of several modules
+
+use bevy::prelude::Entity;
+
+use crate::game_resources::card::{GoodType, GoodsMap};
+use crate::game_resources::deck_definition::{DeckDefinition, GoodsDefinition};
+use crate::game_resources::game_config::{GameConfig, RulesPreset};
+use crate::move_history::{MoveHistoryEntry, RecordedMove};
+
+// The goods of the official deck, in the order they are defined
+pub const DIAMOND: GoodType = GoodType(0);
+pub const GOLD: GoodType = GoodType(1);
+pub const SILVER: GoodType = GoodType(2);
+pub const CLOTH: GoodType = GoodType(3);
+pub const SPICE: GoodType = GoodType(4);
+pub const LEATHER: GoodType = GoodType(5);
+
+pub fn history_entry(player: Entity, recorded_move: RecordedMove) -> MoveHistoryEntry {
+    MoveHistoryEntry {
+        player,
+        player_name: format!("Player {}", player.id()),
+        recorded_move,
+    }
+}
+
+// Only the names and minimum sale sizes are filled in
+pub fn official_goods() -> GoodsMap<GoodsDefinition> {
+    GoodsMap::new(
+        [
+            ("Diamond", 2),
+            ("Gold", 2),
+            ("Silver", 2),
+            ("Cloth", 1),
+            ("Spice", 1),
+            ("Leather", 1),
+        ]
+        .into_iter()
+        .map(|(name, min_sale_size)| GoodsDefinition {
+            name: name.to_string(),
+            texture: String::new(),
+            token_color: (0.0, 0.0, 0.0),
+            num_cards: 0,
+            tokens: vec![],
+            min_sale_size,
+        })
+        .collect(),
+    )
+}
+
+pub fn official_deck_definition() -> DeckDefinition {
+    ron::from_str(include_str!("../assets/decks/official.deck.ron"))
+        .expect("The official deck definition should be readable")
+}
+
+// The rules of every preset in the main menu
+pub fn all_game_configs() -> Vec<GameConfig> {
+    let mut game_configs = vec![];
+    let mut rules_preset = RulesPreset::Official;
+    loop {
+        game_configs.push(rules_preset.get_game_config());
+        rules_preset = rules_preset.next();
+        if rules_preset == RulesPreset::Official {
+            return game_configs;
+        }
+    }
+}
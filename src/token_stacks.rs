@@ -0,0 +1,451 @@
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::{DrawMode, FillMode, GeometryBuilder, StrokeMode};
+use bevy_prototype_lyon::shapes::Circle;
+use enum_map::Enum;
+
+use crate::animation::{animate_transform, AnimationSpeed};
+use crate::common_systems::despawn_entity_with_component;
+use crate::event::TokensAwardedEvent;
+use crate::game::{is_bottom_player, ActivePlayer, HumanPlayer, TokensOwner};
+use crate::game_resources::deck_definition::DeckDefinition;
+use crate::game_resources::tokens::{BonusType, Tokens};
+use crate::move_execution::TweenState;
+use crate::positioning::{Layout, TOKEN_RADIUS};
+use crate::resources::GameState;
+use crate::settings::Settings;
+use crate::states::AppState;
+
+const BONUS_TOKEN_COLOR: Color = Color::rgb(0.2, 0.2, 0.3);
+const EMPTY_STACK_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.3);
+
+// Drawn above the cards while moving
+const FLYING_TOKEN_Z: f32 = 200.0;
+
+#[derive(Component)]
+struct GameTokenStacks;
+
+#[derive(Component)]
+struct PlayerTokenPiles;
+
+#[derive(Component)]
+struct FlyingToken;
+
+// Bonus tokens are labelled with the number of goods sold to earn them, since their values are kept secret
+fn get_bonus_token_label(bonus_type: BonusType) -> &'static str {
+    match bonus_type {
+        BonusType::Three => "3",
+        BonusType::Four => "4",
+        BonusType::Five => "5",
+    }
+}
+
+fn get_bonus_stack_idx(num_goods: usize, bonus_type: BonusType) -> usize {
+    num_goods + bonus_type.into_usize()
+}
+
+fn create_text(asset_server: &AssetServer, value: String, font_size: f32) -> Text {
+    Text::from_section(
+        value,
+        TextStyle {
+            font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+            font_size,
+            color: Color::rgb(0.9, 0.9, 0.9),
+        },
+    )
+    .with_alignment(TextAlignment::CENTER)
+}
+
+fn spawn_token(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    color: Color,
+    label: Option<String>,
+    translation: Vec3,
+) -> Entity {
+    let circle = Circle {
+        radius: TOKEN_RADIUS,
+        center: Vec2::ZERO,
+    };
+
+    commands
+        .spawn_bundle(GeometryBuilder::build_as(
+            &circle,
+            DrawMode::Outlined {
+                fill_mode: FillMode::color(color),
+                outline_mode: StrokeMode::new(Color::BLACK, 2.0),
+            },
+            Transform::from_translation(translation),
+        ))
+        .with_children(|parent| {
+            if let Some(label) = label {
+                parent.spawn_bundle(Text2dBundle {
+                    text: create_text(asset_server, label, 28.0),
+                    transform: Transform::from_translation(Vec3::Z),
+                    ..default()
+                });
+            }
+        })
+        .id()
+}
+
+fn spawn_count_text(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    count: usize,
+    translation: Vec3,
+) -> Entity {
+    commands
+        .spawn_bundle(Text2dBundle {
+            text: create_text(asset_server, format!("x{}", count), 20.0),
+            transform: Transform::from_translation(translation),
+            ..default()
+        })
+        .id()
+}
+
+// A stack shows every token left in it, with the value of the one on top and how many remain
+fn spawn_stack(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    layout: &Layout,
+    stack_idx: usize,
+    color: Color,
+    labels: Vec<Option<String>>,
+) -> Vec<Entity> {
+    if labels.is_empty() {
+        return vec![spawn_token(
+            commands,
+            asset_server,
+            EMPTY_STACK_COLOR,
+            None,
+            layout.get_game_token_translation(stack_idx, 0),
+        )];
+    }
+
+    let num_tokens = labels.len();
+    let mut entities = labels
+        .into_iter()
+        .enumerate()
+        .map(|(token_idx, label)| {
+            // Only the token on top can be seen
+            let label = label.filter(|_| token_idx == num_tokens - 1);
+            let translation = layout.get_game_token_translation(stack_idx, token_idx);
+
+            spawn_token(commands, asset_server, color, label, translation)
+        })
+        .collect::<Vec<_>>();
+
+    let count_translation = layout.get_game_token_translation(stack_idx, num_tokens - 1)
+        + Vec3::new(1.8 * TOKEN_RADIUS, 0.0, 0.0);
+    entities.push(spawn_count_text(
+        commands,
+        asset_server,
+        num_tokens,
+        count_translation,
+    ));
+
+    entities
+}
+
+fn spawn_game_token_stacks(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    layout: &Layout,
+    deck_definition: &DeckDefinition,
+    tokens: &Tokens,
+) {
+    let mut children = vec![];
+
+    for (good_type, token_values) in tokens.goods.iter() {
+        let labels = token_values
+            .iter()
+            .map(|value| Some(value.to_string()))
+            .collect();
+
+        children.extend(spawn_stack(
+            commands,
+            asset_server,
+            layout,
+            good_type.0,
+            deck_definition.goods[good_type].get_token_color(),
+            labels,
+        ));
+    }
+
+    for (bonus_type, token_values) in tokens.bonus.iter() {
+        let labels = vec![Some(get_bonus_token_label(bonus_type).to_string()); token_values.len()];
+
+        children.extend(spawn_stack(
+            commands,
+            asset_server,
+            layout,
+            get_bonus_stack_idx(tokens.goods.len(), bonus_type),
+            BONUS_TOKEN_COLOR,
+            labels,
+        ));
+    }
+
+    commands
+        .spawn_bundle(SpatialBundle::default())
+        .insert(GameTokenStacks)
+        .push_children(&children);
+}
+
+// A pile for each good and bonus the player has won, showing the value of the last goods token won.
+// Empty piles are not shown.
+fn spawn_player_token_piles(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    layout: &Layout,
+    deck_definition: &DeckDefinition,
+    tokens: &Tokens,
+    is_bottom_player: bool,
+) {
+    let mut children = vec![];
+
+    let goods_piles = tokens
+        .goods
+        .iter()
+        .map(|(good_type, token_values)| {
+            (
+                deck_definition.goods[good_type].get_token_color(),
+                token_values.last().map(|value| value.to_string()),
+                token_values.len(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let bonus_piles = tokens.bonus.iter().map(|(bonus_type, token_values)| {
+        (
+            BONUS_TOKEN_COLOR,
+            Some(get_bonus_token_label(bonus_type).to_string()),
+            token_values.len(),
+        )
+    });
+
+    for (pile_idx, (color, label, count)) in goods_piles.into_iter().chain(bonus_piles).enumerate()
+    {
+        if count == 0 {
+            continue;
+        }
+
+        let translation = layout.get_player_token_pile_translation(pile_idx, is_bottom_player);
+
+        children.push(spawn_token(
+            commands,
+            asset_server,
+            color,
+            label,
+            translation,
+        ));
+        children.push(spawn_count_text(
+            commands,
+            asset_server,
+            count,
+            translation + Vec3::new(TOKEN_RADIUS, -TOKEN_RADIUS, 1.0),
+        ));
+    }
+
+    commands
+        .spawn_bundle(SpatialBundle::default())
+        .insert(PlayerTokenPiles)
+        .push_children(&children);
+}
+
+fn setup_token_stacks(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    layout: Res<Layout>,
+    game_state: Res<GameState>,
+    deck_definition: Res<DeckDefinition>,
+    tokens: Res<Tokens>,
+    players_query: Query<(&TokensOwner, Option<&ActivePlayer>, Option<&HumanPlayer>)>,
+) {
+    spawn_game_token_stacks(
+        &mut commands,
+        &asset_server,
+        &layout,
+        &deck_definition,
+        &tokens,
+    );
+
+    for (tokens_owner, active_player, human_player) in players_query.iter() {
+        spawn_player_token_piles(
+            &mut commands,
+            &asset_server,
+            &layout,
+            &deck_definition,
+            &tokens_owner.0,
+            is_bottom_player(&game_state, active_player.is_some(), human_player.is_some()),
+        );
+    }
+}
+
+// Moves each token won in a sale from its stack to the seller's piles. The piles are brought up to date
+// when the next turn starts.
+#[allow(clippy::too_many_arguments)]
+fn animate_awarded_tokens(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut ev_tokens_awarded: EventReader<TokensAwardedEvent>,
+    mut tween_state: ResMut<TweenState>,
+    layout: Res<Layout>,
+    game_state: Res<GameState>,
+    settings: Res<Settings>,
+    deck_definition: Res<DeckDefinition>,
+    tokens: Res<Tokens>,
+    game_token_stacks_query: Query<Entity, With<GameTokenStacks>>,
+    active_player_query: Query<Option<&HumanPlayer>, With<ActivePlayer>>,
+) {
+    for ev in ev_tokens_awarded.iter() {
+        // With instant animations the won tokens are shown straight away in the seller's piles
+        if settings.animation_speed == AnimationSpeed::Instant {
+            continue;
+        }
+
+        let is_seller_at_bottom =
+            is_bottom_player(&game_state, true, active_player_query.single().is_some());
+        let num_goods = tokens.goods.len();
+
+        // The stacks already have the sold tokens taken off them
+        for e in game_token_stacks_query.iter() {
+            commands.entity(e).despawn_recursive();
+        }
+        spawn_game_token_stacks(
+            &mut commands,
+            &asset_server,
+            &layout,
+            &deck_definition,
+            &tokens,
+        );
+
+        let mut num_taken_from_stack = vec![0; num_goods];
+        let goods_tokens = ev.goods_tokens.iter().map(|(good_type, value)| {
+            // Tokens are taken from the top, so the first token taken was highest up the stack
+            let num_taken = ev
+                .goods_tokens
+                .iter()
+                .filter(|(other_good_type, _)| other_good_type == good_type)
+                .count();
+            let token_idx =
+                tokens.goods[*good_type].len() + num_taken - num_taken_from_stack[good_type.0] - 1;
+            num_taken_from_stack[good_type.0] += 1;
+
+            (
+                deck_definition.goods[*good_type].get_token_color(),
+                value.to_string(),
+                layout.get_game_token_translation(good_type.0, token_idx),
+                good_type.0,
+            )
+        });
+
+        let bonus_token = ev.bonus_type.map(|bonus_type| {
+            let stack_idx = get_bonus_stack_idx(num_goods, bonus_type);
+
+            (
+                BONUS_TOKEN_COLOR,
+                get_bonus_token_label(bonus_type).to_string(),
+                layout.get_game_token_translation(stack_idx, tokens.bonus[bonus_type].len()),
+                stack_idx,
+            )
+        });
+
+        let flying_tokens = goods_tokens.chain(bonus_token).collect::<Vec<_>>();
+
+        for (color, label, start, pile_idx) in flying_tokens {
+            let start = start + Vec3::Z * FLYING_TOKEN_Z;
+            let end = layout.get_player_token_pile_translation(pile_idx, is_seller_at_bottom)
+                + Vec3::Z * FLYING_TOKEN_Z;
+
+            let e = spawn_token(&mut commands, &asset_server, color, Some(label), start);
+
+            animate_transform(
+                &mut commands,
+                &mut tween_state,
+                settings.animation_speed,
+                e,
+                &Transform::from_translation(start),
+                Transform::from_translation(end),
+                5,
+            );
+
+            commands.entity(e).insert(FlyingToken);
+        }
+    }
+}
+
+// Moves the stacks and piles to where the new layout puts them, once the window has been resized
+#[allow(clippy::too_many_arguments)]
+fn relayout_token_stacks(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    layout: Res<Layout>,
+    game_state: Res<GameState>,
+    deck_definition: Res<DeckDefinition>,
+    tokens: Res<Tokens>,
+    token_stacks_query: Query<Entity, Or<(With<GameTokenStacks>, With<PlayerTokenPiles>)>>,
+    players_query: Query<(&TokensOwner, Option<&ActivePlayer>, Option<&HumanPlayer>)>,
+) {
+    if !layout.is_changed() {
+        return;
+    }
+
+    for e in token_stacks_query.iter() {
+        commands.entity(e).despawn_recursive();
+    }
+
+    spawn_game_token_stacks(
+        &mut commands,
+        &asset_server,
+        &layout,
+        &deck_definition,
+        &tokens,
+    );
+
+    for (tokens_owner, active_player, human_player) in players_query.iter() {
+        spawn_player_token_piles(
+            &mut commands,
+            &asset_server,
+            &layout,
+            &deck_definition,
+            &tokens_owner.0,
+            is_bottom_player(&game_state, active_player.is_some(), human_player.is_some()),
+        );
+    }
+}
+
+pub struct TokenStacksPlugin;
+
+impl Plugin for TokenStacksPlugin {
+    fn build(&self, app: &mut App) {
+        for state in [AppState::InGame, AppState::AiTurn] {
+            app.add_system_set(
+                SystemSet::on_enter(state)
+                    .with_system(despawn_entity_with_component::<GameTokenStacks>)
+                    .with_system(despawn_entity_with_component::<PlayerTokenPiles>)
+                    .with_system(despawn_entity_with_component::<FlyingToken>)
+                    .with_system(setup_token_stacks),
+            );
+            app.add_system_set(SystemSet::on_update(state).with_system(relayout_token_stacks));
+        }
+
+        for state in [
+            AppState::TurnTransition,
+            AppState::GameOver,
+            AppState::MainMenu,
+            AppState::InitGame,
+        ] {
+            app.add_system_set(
+                SystemSet::on_enter(state)
+                    .with_system(despawn_entity_with_component::<GameTokenStacks>)
+                    .with_system(despawn_entity_with_component::<PlayerTokenPiles>)
+                    .with_system(despawn_entity_with_component::<FlyingToken>),
+            );
+        }
+
+        app.add_system_set(
+            SystemSet::on_enter(AppState::WaitForTweensToFinish)
+                .with_system(animate_awarded_tokens),
+        );
+    }
+}
@@ -0,0 +1,550 @@
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::{DrawMode, GeometryBuilder, StrokeMode};
+use bevy_prototype_lyon::shapes::{Rectangle, RectangleOrigin};
+use big_brain::BigBrainStage;
+use itertools::Itertools;
+
+use crate::card_selection::SelectedCard;
+use crate::common_systems::despawn_entity_with_component;
+use crate::event::ConfirmTurnEvent;
+use crate::game::{ActivePlayer, HumanPlayer};
+use crate::game_resources::card::{
+    ActivePlayerCamelCard, ActivePlayerGoodsCard, Card, CardType, MarketCard,
+};
+use crate::game_resources::deck_definition::DeckDefinition;
+use crate::game_resources::game_config::GameConfig;
+use crate::game_resources::puzzle_definition::{
+    PuzzleCard, PuzzleDefinition, PuzzleDefinitionError, PuzzleGoal, PuzzlePlayer,
+};
+use crate::label::Label;
+use crate::localization::Localization;
+use crate::move_validation::{
+    get_card_selection, CardSelection, InvalidMoveReason, MoveType, MoveValidity,
+};
+use crate::positioning::CARD_DIMENSION;
+use crate::resources::GameState;
+use crate::states::AppState;
+
+// A card named in the tutorial script, resolved against the deck definition when the tutorial starts
+#[derive(Clone, Copy)]
+enum TutorialCard {
+    Camel,
+    Good(&'static str),
+}
+
+// The cards for a move, as they are selected in the market and the hand of the player making it
+struct TutorialMove {
+    move_type: MoveType,
+    market: &'static [TutorialCard],
+    goods_hand: &'static [&'static str],
+    num_camels_from_hand: usize,
+}
+
+struct TutorialStep {
+    // The key of the step's instructions in the locale files
+    text: &'static str,
+    player_move: TutorialMove,
+    // The computer's reply, or None after the last step
+    opponent_move: Option<TutorialMove>,
+}
+
+// Each step is played from the position left by the steps before it - see create_tutorial_puzzle
+const STEPS: [TutorialStep; 4] = [
+    TutorialStep {
+        text: "tutorial.take_single_good",
+        player_move: TutorialMove {
+            move_type: MoveType::TakeSingleGood,
+            market: &[TutorialCard::Good("Spice")],
+            goods_hand: &[],
+            num_camels_from_hand: 0,
+        },
+        opponent_move: Some(TutorialMove {
+            move_type: MoveType::SellGoods,
+            market: &[],
+            goods_hand: &["Cloth", "Cloth"],
+            num_camels_from_hand: 0,
+        }),
+    },
+    TutorialStep {
+        text: "tutorial.exchange_goods",
+        player_move: TutorialMove {
+            move_type: MoveType::ExchangeForGoodsFromMarket,
+            market: &[TutorialCard::Good("Diamond"), TutorialCard::Good("Gold")],
+            goods_hand: &[],
+            num_camels_from_hand: 2,
+        },
+        opponent_move: Some(TutorialMove {
+            move_type: MoveType::TakeSingleGood,
+            market: &[TutorialCard::Good("Leather")],
+            goods_hand: &[],
+            num_camels_from_hand: 0,
+        }),
+    },
+    TutorialStep {
+        text: "tutorial.take_all_camels",
+        player_move: TutorialMove {
+            move_type: MoveType::TakeAllCamels,
+            market: &[
+                TutorialCard::Camel,
+                TutorialCard::Camel,
+                TutorialCard::Camel,
+            ],
+            goods_hand: &[],
+            num_camels_from_hand: 0,
+        },
+        opponent_move: Some(TutorialMove {
+            move_type: MoveType::SellGoods,
+            market: &[],
+            goods_hand: &["Leather", "Leather"],
+            num_camels_from_hand: 0,
+        }),
+    },
+    TutorialStep {
+        text: "tutorial.sell_goods",
+        player_move: TutorialMove {
+            move_type: MoveType::SellGoods,
+            market: &[],
+            goods_hand: &["Spice", "Spice", "Spice"],
+            num_camels_from_hand: 0,
+        },
+        opponent_move: None,
+    },
+];
+
+// The rigged position the tutorial starts from. The deck is ordered so that the computer's replies leave
+// the market ready for the next step.
+pub fn create_tutorial_puzzle() -> PuzzleDefinition {
+    let good = |name: &str| PuzzleCard::Good(name.to_string());
+    let goods =
+        |names: &[&str]| -> Vec<String> { names.iter().map(|name| name.to_string()).collect() };
+
+    PuzzleDefinition {
+        name: "Tutorial".to_string(),
+        // Never checked, since the tutorial ends after its last step
+        goal: PuzzleGoal::WinGame,
+        deck_definition: GameConfig::default().deck_definition_path,
+        market: vec![
+            good("Spice"),
+            good("Diamond"),
+            good("Gold"),
+            good("Cloth"),
+            good("Leather"),
+        ],
+        deck: vec![
+            good("Leather"),
+            PuzzleCard::Camel,
+            good("Gold"),
+            good("Spice"),
+            good("Diamond"),
+            good("Silver"),
+            good("Cloth"),
+            good("Leather"),
+        ],
+        player: PuzzlePlayer {
+            goods: goods(&["Spice", "Spice", "Silver", "Leather"]),
+            camels: 2,
+            sold: default(),
+            bonus: default(),
+        },
+        opponent: PuzzlePlayer {
+            goods: goods(&["Cloth", "Cloth", "Leather", "Silver", "Gold"]),
+            camels: 1,
+            sold: default(),
+            bonus: default(),
+        },
+    }
+}
+
+// Shown on the game over screen, where the final scores show the camel bonus being awarded
+pub fn get_end_of_tutorial_text(
+    game_config: &GameConfig,
+    localization: &Localization,
+) -> [String; 2] {
+    [
+        localization.format(
+            "tutorial.camel_bonus",
+            &[("bonus", &game_config.camel_bonus)],
+        ),
+        localization.format(
+            "tutorial.game_end",
+            &[("count", &game_config.num_empty_token_stacks_to_end)],
+        ),
+    ]
+}
+
+// Progress through the tutorial. Only present during the tutorial.
+pub struct TutorialState {
+    // The player's and the computer's move for each step, resolved from STEPS
+    moves: Vec<(CardSelection, Option<CardSelection>)>,
+    pub step: usize,
+    pub is_complete: bool,
+    is_opponent_move_sent: bool,
+}
+
+impl TutorialState {
+    pub fn new(deck_definition: &DeckDefinition) -> Result<Self, PuzzleDefinitionError> {
+        let moves = STEPS
+            .iter()
+            .map(|step| {
+                let player_move = resolve_move(&step.player_move, deck_definition)?;
+                let opponent_move = match &step.opponent_move {
+                    Some(opponent_move) => Some(resolve_move(opponent_move, deck_definition)?),
+                    None => None,
+                };
+
+                Ok((player_move, opponent_move))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            moves,
+            step: 0,
+            is_complete: false,
+            is_opponent_move_sent: false,
+        })
+    }
+
+    fn get_text(&self, localization: &Localization) -> String {
+        localization.format(
+            "tutorial.progress",
+            &[
+                ("step", &(self.step + 1)),
+                ("count", &STEPS.len()),
+                ("text", &localization.get(STEPS[self.step].text)),
+            ],
+        )
+    }
+}
+
+fn resolve_move(
+    tutorial_move: &TutorialMove,
+    deck_definition: &DeckDefinition,
+) -> Result<CardSelection, PuzzleDefinitionError> {
+    let get_good = |name: &str| {
+        deck_definition
+            .find_good(name)
+            .ok_or_else(|| PuzzleDefinitionError::UnknownGood(name.to_string()))
+    };
+
+    Ok(CardSelection {
+        market: tutorial_move
+            .market
+            .iter()
+            .map(|card| match card {
+                TutorialCard::Camel => Ok(CardType::Camel),
+                TutorialCard::Good(name) => Ok(CardType::Good(get_good(name)?)),
+            })
+            .collect::<Result<_, _>>()?,
+        goods_hand: tutorial_move
+            .goods_hand
+            .iter()
+            .map(|name| get_good(name))
+            .collect::<Result<_, _>>()?,
+        num_camels_from_hand: tutorial_move.num_camels_from_hand,
+    })
+}
+
+fn is_same_selection(selection: &CardSelection, other: &CardSelection) -> bool {
+    selection.market.iter().counts() == other.market.iter().counts()
+        && selection.goods_hand.iter().counts() == other.goods_hand.iter().counts()
+        && selection.num_camels_from_hand == other.num_camels_from_hand
+}
+
+// The entities of the active player's cards for the given selection, or None if they cannot all be found
+fn find_card_entities(
+    selection: &CardSelection,
+    market_card_query: &Query<(Entity, &Card), With<MarketCard>>,
+    goods_hand_card_query: &Query<(Entity, &Card), With<ActivePlayerGoodsCard>>,
+    camel_hand_card_query: &Query<Entity, With<ActivePlayerCamelCard>>,
+) -> Option<Vec<Entity>> {
+    let mut entities = vec![];
+
+    for (card_type, count) in selection.market.iter().counts() {
+        let market_entities = market_card_query
+            .iter()
+            .filter(|(_, card)| card.0 == *card_type)
+            .map(|(entity, _)| entity)
+            .take(count)
+            .collect::<Vec<_>>();
+
+        if market_entities.len() < count {
+            return None;
+        }
+        entities.extend(market_entities);
+    }
+
+    for (good, count) in selection.goods_hand.iter().counts() {
+        let goods_entities = goods_hand_card_query
+            .iter()
+            .filter(|(_, card)| card.0 == CardType::Good(*good))
+            .map(|(entity, _)| entity)
+            .take(count)
+            .collect::<Vec<_>>();
+
+        if goods_entities.len() < count {
+            return None;
+        }
+        entities.extend(goods_entities);
+    }
+
+    let camel_entities = camel_hand_card_query
+        .iter()
+        .take(selection.num_camels_from_hand)
+        .collect::<Vec<_>>();
+
+    if camel_entities.len() < selection.num_camels_from_hand {
+        return None;
+    }
+    entities.extend(camel_entities);
+
+    Some(entities)
+}
+
+// Only the move highlighted for the current step can be confirmed
+fn restrict_move_to_tutorial_step(
+    tutorial_state: Option<Res<TutorialState>>,
+    mut move_validity_state: ResMut<MoveValidity>,
+    market_selected_card_query: Query<&Card, (With<MarketCard>, With<SelectedCard>)>,
+    goods_hand_selected_card_query: Query<&Card, (With<ActivePlayerGoodsCard>, With<SelectedCard>)>,
+    camel_hand_selected_card_query: Query<&Card, (With<ActivePlayerCamelCard>, With<SelectedCard>)>,
+) {
+    let tutorial_state = match tutorial_state {
+        Some(tutorial_state) if move_validity_state.is_changed() => tutorial_state,
+        _ => return,
+    };
+
+    let move_type = match *move_validity_state {
+        MoveValidity::Valid(move_type) => move_type,
+        MoveValidity::Invalid(_) => return,
+    };
+
+    let selection = get_card_selection(
+        &market_selected_card_query,
+        &goods_hand_selected_card_query,
+        &camel_hand_selected_card_query,
+    );
+
+    let (expected_selection, _) = &tutorial_state.moves[tutorial_state.step];
+
+    if move_type != STEPS[tutorial_state.step].player_move.move_type
+        || !is_same_selection(&selection, expected_selection)
+    {
+        *move_validity_state = MoveValidity::Invalid(InvalidMoveReason::NotTutorialMove);
+    }
+}
+
+// Plays the computer's scripted reply. Runs alongside the computer player's actions, so that the cards are
+// selected before the move is executed.
+fn play_tutorial_opponent_move(
+    mut commands: Commands,
+    app_state: Res<State<AppState>>,
+    tutorial_state: Option<ResMut<TutorialState>>,
+    mut ev_confirm_turn: EventWriter<ConfirmTurnEvent>,
+    market_card_query: Query<(Entity, &Card), With<MarketCard>>,
+    goods_hand_card_query: Query<(Entity, &Card), With<ActivePlayerGoodsCard>>,
+    camel_hand_card_query: Query<Entity, With<ActivePlayerCamelCard>>,
+) {
+    let mut tutorial_state = match tutorial_state {
+        Some(tutorial_state) if *app_state.current() == AppState::AiTurn => tutorial_state,
+        _ => return,
+    };
+
+    if tutorial_state.is_opponent_move_sent {
+        return;
+    }
+
+    let step = tutorial_state.step;
+    let selection = match &tutorial_state.moves[step].1 {
+        Some(selection) => selection,
+        None => return,
+    };
+
+    let entities = find_card_entities(
+        selection,
+        &market_card_query,
+        &goods_hand_card_query,
+        &camel_hand_card_query,
+    )
+    .expect("The tutorial deck should leave the cards for the computer's move");
+
+    for entity in entities {
+        commands.entity(entity).insert(SelectedCard);
+    }
+
+    let move_type = STEPS[step].opponent_move.as_ref().unwrap().move_type;
+    ev_confirm_turn.send(ConfirmTurnEvent(move_type));
+    tutorial_state.is_opponent_move_sent = true;
+}
+
+// Runs as each turn ends, before the active player changes, like puzzle::check_puzzle_goal
+fn advance_tutorial(
+    tutorial_state: Option<ResMut<TutorialState>>,
+    mut game_state: ResMut<GameState>,
+    human_player_query: Query<Option<&ActivePlayer>, With<HumanPlayer>>,
+) {
+    let mut tutorial_state = match tutorial_state {
+        Some(tutorial_state) if !tutorial_state.is_complete => tutorial_state,
+        _ => return,
+    };
+
+    let is_human_turn = human_player_query.single().is_some();
+
+    if !is_human_turn {
+        tutorial_state.step += 1;
+        tutorial_state.is_opponent_move_sent = false;
+    } else if tutorial_state.step == STEPS.len() - 1 {
+        tutorial_state.is_complete = true;
+        game_state.is_game_over = true;
+    }
+}
+
+#[derive(Component)]
+struct TutorialHighlight;
+
+fn spawn_highlights_for_tutorial_step(
+    mut commands: Commands,
+    tutorial_state: Option<Res<TutorialState>>,
+    market_card_query: Query<(Entity, &Card), With<MarketCard>>,
+    goods_hand_card_query: Query<(Entity, &Card), With<ActivePlayerGoodsCard>>,
+    camel_hand_card_query: Query<Entity, With<ActivePlayerCamelCard>>,
+    // Cards are children of the game root at the origin, so their local translation is their position on screen
+    transform_query: Query<&Transform>,
+) {
+    let tutorial_state = match tutorial_state {
+        Some(tutorial_state) if !tutorial_state.is_complete => tutorial_state,
+        _ => return,
+    };
+
+    let (selection, _) = &tutorial_state.moves[tutorial_state.step];
+
+    let entities = find_card_entities(
+        selection,
+        &market_card_query,
+        &goods_hand_card_query,
+        &camel_hand_card_query,
+    )
+    .unwrap_or_default();
+
+    let outline = Rectangle {
+        extents: CARD_DIMENSION + Vec2::splat(16.0),
+        origin: RectangleOrigin::Center,
+    };
+
+    for transform in entities.iter().filter_map(|e| transform_query.get(*e).ok()) {
+        // Drawn as separate entities, since each card's children are its selection outline
+        commands
+            .spawn_bundle(GeometryBuilder::build_as(
+                &outline,
+                DrawMode::Stroke(StrokeMode::new(Color::CYAN, 6.0)),
+                Transform::from_translation(transform.translation + Vec3::Z),
+            ))
+            .insert(TutorialHighlight);
+    }
+}
+
+#[derive(Component)]
+struct TutorialUiRoot;
+
+fn setup_tutorial_ui(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    localization: Res<Localization>,
+    tutorial_state: Option<Res<TutorialState>>,
+) {
+    let tutorial_state = match tutorial_state {
+        Some(tutorial_state) => tutorial_state,
+        None => return,
+    };
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect::new(Val::Px(0.), Val::Px(0.), Val::Px(0.), Val::Auto),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .insert(TutorialUiRoot)
+        .with_children(|parent| {
+            parent.spawn_bundle(
+                TextBundle::from_section(
+                    tutorial_state.get_text(&localization),
+                    TextStyle {
+                        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                        font_size: 24.0,
+                        color: Color::rgb(0.9, 0.9, 0.9),
+                    },
+                )
+                .with_text_alignment(TextAlignment::TOP_CENTER)
+                .with_style(Style {
+                    margin: UiRect::all(Val::Px(10.)),
+                    ..default()
+                }),
+            );
+        });
+}
+
+fn update_tutorial_text(
+    tutorial_state: Option<Res<TutorialState>>,
+    localization: Res<Localization>,
+    ui_root_query: Query<&Children, With<TutorialUiRoot>>,
+    mut text_query: Query<&mut Text>,
+) {
+    let tutorial_state = match tutorial_state {
+        Some(tutorial_state)
+            if (tutorial_state.is_changed() || localization.is_changed())
+                && !tutorial_state.is_complete =>
+        {
+            tutorial_state
+        }
+        _ => return,
+    };
+
+    for children in ui_root_query.iter() {
+        for &child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(child) {
+                text.sections[0].value = tutorial_state.get_text(&localization);
+            }
+        }
+    }
+}
+
+pub struct TutorialPlugin;
+
+impl Plugin for TutorialPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_exit(AppState::InitGame).with_system(setup_tutorial_ui))
+            .add_system_set(
+                SystemSet::on_enter(AppState::InGame)
+                    .with_system(spawn_highlights_for_tutorial_step),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::InGame)
+                    .with_system(despawn_entity_with_component::<TutorialHighlight>),
+            )
+            .add_system_set(
+                SystemSet::on_enter(AppState::WaitForTweensToFinish).with_system(advance_tutorial),
+            )
+            .add_system(update_tutorial_text)
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                restrict_move_to_tutorial_step.after(Label::MoveValidityWriter),
+            )
+            .add_system_set_to_stage(
+                BigBrainStage::Actions,
+                SystemSet::new()
+                    .label(Label::ConfirmTurnEventWriter)
+                    .with_system(play_tutorial_opponent_move),
+            );
+
+        for state in [AppState::GameOver, AppState::MainMenu, AppState::InitGame] {
+            app.add_system_set(
+                SystemSet::on_enter(state)
+                    .with_system(despawn_entity_with_component::<TutorialUiRoot>),
+            );
+        }
+    }
+}
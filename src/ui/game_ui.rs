@@ -1,14 +1,22 @@
 use bevy::prelude::*;
 use itertools::Itertools;
-use std::{fmt, ops::DerefMut};
+use std::ops::DerefMut;
 
 use crate::{
-    card_selection::SelectedCardState,
-    event::ConfirmTurnEvent,
-    game::{ActivePlayer, HumanPlayer, TokensOwner},
-    game_resources::tokens::Tokens,
+    card_selection::{SelectedCard, SelectedCardState},
+    common_systems::despawn_entity_with_component,
+    event::{ConfirmTurnEvent, SelectionAssistEvent},
+    game_resources::{
+        card::{ActivePlayerGoodsCard, Card, CardType, GoodType},
+        deck_definition::DeckDefinition,
+        game_config::GameConfig,
+        tokens::{SalePreview, Tokens},
+    },
     label::Label,
-    move_validation::MoveValidity,
+    layout::GAME_UI_PANEL_HEIGHT,
+    localization::{Localization, LocalizedText},
+    move_validation::{MoveType, MoveValidity},
+    positioning::{Layout, Orientation},
     states::{AppState, TurnState},
 };
 
@@ -19,9 +27,13 @@ enum GameButtonKind {
     Confirm,
 }
 
-impl fmt::Display for GameButtonKind {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:?}", self)
+impl GameButtonKind {
+    fn get_text_key(&self) -> &'static str {
+        match self {
+            GameButtonKind::Take => "game.take",
+            GameButtonKind::Sell => "game.sell",
+            GameButtonKind::Confirm => "game.confirm",
+        }
     }
 }
 
@@ -70,6 +82,7 @@ const CONFIRM_BUTTON_DATA: GameButtonData = GameButtonData {
 fn create_button<C: Component>(
     commands: &mut Commands,
     asset_server: &Res<AssetServer>,
+    localization: &Localization,
     button_component: C,
     game_button_data: GameButtonData,
 ) -> Entity {
@@ -90,9 +103,10 @@ fn create_button<C: Component>(
         })
         .insert(button_component)
         .insert(GameButton(game_button_data))
+        .insert(LocalizedText(game_button_data.kind.get_text_key()))
         .with_children(|parent| {
             parent.spawn_bundle(TextBundle::from_section(
-                game_button_data.kind.to_string(),
+                localization.get(game_button_data.kind.get_text_key()),
                 TextStyle {
                     font: asset_server.load("fonts/FiraSans-Bold.ttf"),
                     font_size: 20.0,
@@ -103,21 +117,98 @@ fn create_button<C: Component>(
         .id()
 }
 
-#[derive(Component)]
-struct GameUiRoot;
+#[derive(Component, Copy, Clone)]
+enum AssistButton {
+    ClearSelection,
+    SelectAllMatchingGoods,
+}
 
-fn setup_game_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let root_node_entity = commands
-        .spawn_bundle(NodeBundle {
+const ASSIST_BUTTON_NORMAL_COLOR: Color = Color::rgb(0.15, 0.15, 0.15);
+const ASSIST_BUTTON_HOVERED_COLOR: Color = Color::GRAY;
+
+fn create_assist_button<C: Component>(
+    commands: &mut Commands,
+    asset_server: &Res<AssetServer>,
+    assist_button: C,
+    text: String,
+    display: Display,
+) -> Entity {
+    commands
+        .spawn_bundle(ButtonBundle {
             style: Style {
-                position_type: PositionType::Absolute,
-                margin: UiRect::all(Val::Auto),
-                flex_direction: FlexDirection::ColumnReverse,
-                align_items: AlignItems::Center,
+                size: Size::new(Val::Px(150.0), Val::Px(45.0)),
+                margin: UiRect::all(Val::Px(10.0)),
                 justify_content: JustifyContent::Center,
-                position: UiRect::new(Val::Auto, Val::Px(0.0), Val::Auto, Val::Px(0.0)),
+                align_items: AlignItems::Center,
+                display,
                 ..default()
             },
+            color: ASSIST_BUTTON_NORMAL_COLOR.into(),
+            ..default()
+        })
+        .insert(assist_button)
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle::from_section(
+                text,
+                TextStyle {
+                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                    font_size: 16.0,
+                    color: Color::rgb(0.9, 0.9, 0.9),
+                },
+            ));
+        })
+        .id()
+}
+
+// Pauses the game, showing the settings menu
+#[derive(Component)]
+struct PauseButton;
+
+#[derive(Component)]
+struct GameUiRoot;
+
+#[derive(Component)]
+struct MoveValidityText;
+
+#[derive(Component)]
+struct SalePreviewText;
+
+// A column in the bottom right corner of a landscape window, or a row along the bottom of a portrait one, kept
+// clear of the cards by the layout
+fn get_game_ui_root_style(orientation: Orientation) -> Style {
+    match orientation {
+        Orientation::Landscape => Style {
+            position_type: PositionType::Absolute,
+            margin: UiRect::all(Val::Auto),
+            flex_direction: FlexDirection::ColumnReverse,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            position: UiRect::new(Val::Auto, Val::Px(0.0), Val::Auto, Val::Px(0.0)),
+            ..default()
+        },
+        Orientation::Portrait => Style {
+            position_type: PositionType::Absolute,
+            size: Size::new(Val::Percent(100.0), Val::Px(GAME_UI_PANEL_HEIGHT)),
+            flex_direction: FlexDirection::Row,
+            flex_wrap: FlexWrap::Wrap,
+            align_items: AlignItems::Center,
+            align_content: AlignContent::Center,
+            justify_content: JustifyContent::Center,
+            position: UiRect::new(Val::Px(0.0), Val::Px(0.0), Val::Auto, Val::Px(0.0)),
+            ..default()
+        },
+    }
+}
+
+fn setup_game_ui(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    layout: Res<Layout>,
+    localization: Res<Localization>,
+) {
+    let root_node_entity = commands
+        .spawn_bundle(NodeBundle {
+            style: get_game_ui_root_style(layout.orientation),
             color: Color::DARK_GRAY.into(),
             ..default()
         })
@@ -127,179 +218,248 @@ fn setup_game_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
     let take_button_entity = create_button(
         &mut commands,
         &asset_server,
+        &localization,
         TakeGameButton,
         TAKE_BUTTON_DATA,
     );
     let sell_button_entity = create_button(
         &mut commands,
         &asset_server,
+        &localization,
         SellGameButton,
         SELL_BUTTON_DATA,
     );
     let confirm_button_entity = create_button(
         &mut commands,
         &asset_server,
+        &localization,
         ConfirmGameButton,
         CONFIRM_BUTTON_DATA,
     );
 
-    commands.entity(root_node_entity).push_children(&[
-        take_button_entity,
-        sell_button_entity,
-        confirm_button_entity,
-    ]);
-}
-
-#[derive(Component)]
-struct GameTokensUiRoot;
-
-fn refresh_tokens_ui_after_ai_turn(
-    commands: Commands,
-    asset_server: Res<AssetServer>,
-    tokens: Res<Tokens>,
-    active_player_tokens_query: Query<&TokensOwner, With<HumanPlayer>>,
-) {
-    setup_tokens_ui(commands, asset_server, tokens, active_player_tokens_query);
-}
+    let move_validity_text_entity = commands
+        .spawn_bundle(
+            TextBundle::from_section(
+                MoveValidity::default().localize(&localization),
+                TextStyle {
+                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                    font_size: 16.0,
+                    color: Color::rgb(0.9, 0.9, 0.9),
+                },
+            )
+            .with_style(Style {
+                margin: UiRect::all(Val::Px(10.0)),
+                max_size: Size::new(Val::Px(150.0), Val::Undefined),
+                ..default()
+            }),
+        )
+        .insert(MoveValidityText)
+        .id();
 
-fn setup_tokens_ui<T: Component>(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    tokens: Res<Tokens>,
-    active_player_tokens_query: Query<&TokensOwner, With<T>>,
-) {
-    let game_tokens_root_node_entity = commands
-        .spawn_bundle(NodeBundle {
-            style: Style {
-                position_type: PositionType::Absolute,
-                margin: UiRect::all(Val::Auto),
-                flex_direction: FlexDirection::ColumnReverse,
-                align_items: AlignItems::FlexStart,
-                justify_content: JustifyContent::Center,
-                position: UiRect::new(Val::Px(0.), Val::Auto, Val::Px(0.), Val::Auto),
+    // Only shown while a valid sale is selected
+    let sale_preview_text_entity = commands
+        .spawn_bundle(
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                    font_size: 16.0,
+                    color: Color::rgb(0.9, 0.9, 0.9),
+                },
+            )
+            .with_style(Style {
+                margin: UiRect::all(Val::Px(10.0)),
+                max_size: Size::new(Val::Px(150.0), Val::Undefined),
+                display: Display::None,
                 ..default()
-            },
-            color: Color::NONE.into(),
-            ..default()
-        })
-        .insert(GameTokensUiRoot)
+            }),
+        )
+        .insert(SalePreviewText)
         .id();
 
-    let game_tokens_children = create_tokens_ui(
+    // Only shown when some, but not all, of a good in the hand are selected in Sell mode
+    let select_all_matching_goods_button_entity = create_assist_button(
         &mut commands,
         &asset_server,
-        tokens.as_ref(),
-        "Remaining game tokens".to_string(),
+        AssistButton::SelectAllMatchingGoods,
+        "".to_string(),
+        Display::None,
     );
 
+    let clear_selection_button_entity = create_assist_button(
+        &mut commands,
+        &asset_server,
+        AssistButton::ClearSelection,
+        localization.get("game.clear_selection"),
+        Display::Flex,
+    );
     commands
-        .entity(game_tokens_root_node_entity)
-        .push_children(&game_tokens_children);
-
-    let player_tokens_root_node_entity = commands
-        .spawn_bundle(NodeBundle {
-            style: Style {
-                position_type: PositionType::Absolute,
-                margin: UiRect::all(Val::Auto),
-                flex_direction: FlexDirection::ColumnReverse,
-                align_items: AlignItems::FlexStart,
-                justify_content: JustifyContent::Center,
-                position: UiRect::new(Val::Auto, Val::Px(0.), Val::Px(0.), Val::Auto),
-                ..default()
-            },
-            color: Color::NONE.into(),
-            ..default()
-        })
-        .insert(GameTokensUiRoot)
-        .id();
+        .entity(clear_selection_button_entity)
+        .insert(LocalizedText("game.clear_selection"));
 
-    let player_tokens_children = create_tokens_ui(
+    let pause_button_entity = create_assist_button(
         &mut commands,
         &asset_server,
-        &active_player_tokens_query.single().0,
-        "Your tokens".to_string(),
+        PauseButton,
+        localization.get("game.pause"),
+        Display::Flex,
     );
-
     commands
-        .entity(player_tokens_root_node_entity)
-        .push_children(&player_tokens_children);
+        .entity(pause_button_entity)
+        .insert(LocalizedText("game.pause"));
+
+    commands.entity(root_node_entity).push_children(&[
+        take_button_entity,
+        sell_button_entity,
+        confirm_button_entity,
+        move_validity_text_entity,
+        sale_preview_text_entity,
+        select_all_matching_goods_button_entity,
+        clear_selection_button_entity,
+        pause_button_entity,
+    ]);
 }
 
-fn create_tokens_ui(
-    commands: &mut Commands,
-    asset_server: &Res<AssetServer>,
-    tokens: &Tokens,
-    title: String,
-) -> Vec<Entity> {
-    let mut v: Vec<Entity> = vec![];
-    v.push(
-        commands
-            .spawn_bundle(
-                TextBundle::from_section(
-                    title,
-                    TextStyle {
-                        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
-                        font_size: 20.0,
-                        color: Color::rgb(0.9, 0.9, 0.9),
-                    },
-                )
-                .with_style(Style {
-                    margin: UiRect::all(Val::Px(10.)),
-                    ..default()
-                }),
-            )
-            .id(),
-    );
-    tokens.goods.iter().for_each(|(good_type, token_values)| {
-        let t = commands
-            .spawn_bundle(
-                TextBundle::from_section(
-                    format!("{:?}: {:?}", good_type, token_values),
-                    TextStyle {
-                        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
-                        font_size: 20.0,
-                        color: Color::rgb(0.9, 0.9, 0.9),
-                    },
-                )
-                .with_style(Style {
-                    margin: UiRect::all(Val::Px(10.)),
-                    ..default()
-                }),
-            )
-            .id();
-
-        v.push(t);
-    });
-
-    tokens.bonus.iter().for_each(|(bonus_type, token_values)| {
-        let t = commands
-            .spawn_bundle(
-                TextBundle::from_section(
-                    format!("{:?} bonus tokens: {:?}", bonus_type, token_values.len()),
-                    TextStyle {
-                        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
-                        font_size: 20.0,
-                        color: Color::rgb(0.9, 0.9, 0.9),
-                    },
-                )
-                .with_style(Style {
-                    margin: UiRect::all(Val::Px(10.)),
-                    ..default()
-                }),
-            )
-            .id();
+fn update_game_ui_layout(
+    layout: Res<Layout>,
+    mut ui_root_query: Query<&mut Style, With<GameUiRoot>>,
+) {
+    if !layout.is_changed() {
+        return;
+    }
+
+    for mut style in ui_root_query.iter_mut() {
+        *style = get_game_ui_root_style(layout.orientation);
+    }
+}
 
-        v.push(t);
-    });
-    v
+// The good type to offer selecting all of, if the selected goods are all the same type and more of that good are unselected
+fn get_good_to_select_all(
+    goods_hand_query: &Query<(&Card, Option<&SelectedCard>), With<ActivePlayerGoodsCard>>,
+) -> Option<GoodType> {
+    let selected_goods = goods_hand_query
+        .iter()
+        .filter(|(_, selected)| selected.is_some())
+        .map(|(card, _)| card.0)
+        .unique()
+        .collect::<Vec<_>>();
+
+    match selected_goods[..] {
+        [CardType::Good(good_type)] => goods_hand_query
+            .iter()
+            .any(|(card, selected)| selected.is_none() && card.0 == CardType::Good(good_type))
+            .then_some(good_type),
+        _ => None,
+    }
 }
 
-fn cleanup_tokens_ui(
-    mut commands: Commands,
-    tokens_ui_query: Query<Entity, With<GameTokensUiRoot>>,
+fn handle_assist_button_interaction(
+    mut ev_selection_assist: EventWriter<SelectionAssistEvent>,
+    mut interaction_query: Query<
+        (&Interaction, &mut UiColor, &AssistButton),
+        (Changed<Interaction>, With<Button>),
+    >,
+    goods_hand_query: Query<(&Card, Option<&SelectedCard>), With<ActivePlayerGoodsCard>>,
+) {
+    for (interaction, mut color, assist_button) in &mut interaction_query {
+        match *interaction {
+            Interaction::Clicked => match assist_button {
+                AssistButton::ClearSelection => {
+                    ev_selection_assist.send(SelectionAssistEvent::ClearSelection);
+                }
+                AssistButton::SelectAllMatchingGoods => {
+                    if let Some(good_type) = get_good_to_select_all(&goods_hand_query) {
+                        ev_selection_assist
+                            .send(SelectionAssistEvent::SelectAllMatchingGoods(good_type));
+                    }
+                }
+            },
+            Interaction::Hovered => {
+                *color = ASSIST_BUTTON_HOVERED_COLOR.into();
+            }
+            Interaction::None => {
+                *color = ASSIST_BUTTON_NORMAL_COLOR.into();
+            }
+        }
+    }
+}
+
+// Escape clears the selection first, if there is one - see card_focus.rs
+fn handle_pause_button_interaction(
+    mut state: ResMut<State<AppState>>,
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    selected_card_state: Res<SelectedCardState>,
+    mut interaction_query: Query<
+        (&Interaction, &mut UiColor),
+        (Changed<Interaction>, With<PauseButton>),
+    >,
 ) {
-    for e in tokens_ui_query.iter() {
-        commands.entity(e).despawn_recursive();
+    let pause_key = if keyboard_input.just_pressed(KeyCode::P) {
+        Some(KeyCode::P)
+    } else if keyboard_input.just_pressed(KeyCode::Escape) && selected_card_state.0.is_empty() {
+        Some(KeyCode::Escape)
+    } else {
+        None
+    };
+
+    if let Some(pause_key) = pause_key {
+        // The settings menu runs in the same frame, so the key press is used up to stop Escape closing it again
+        keyboard_input.reset(pause_key);
+        state.push(AppState::Settings).unwrap();
+        return;
+    }
+
+    for (interaction, mut color) in &mut interaction_query {
+        match *interaction {
+            Interaction::Clicked => {
+                state.push(AppState::Settings).unwrap();
+            }
+            Interaction::Hovered => {
+                *color = ASSIST_BUTTON_HOVERED_COLOR.into();
+            }
+            Interaction::None => {
+                *color = ASSIST_BUTTON_NORMAL_COLOR.into();
+            }
+        }
+    }
+}
+
+fn update_select_all_matching_goods_button(
+    turn_state: Res<State<TurnState>>,
+    selected_card_state: Res<SelectedCardState>,
+    deck_definition: Option<Res<DeckDefinition>>,
+    localization: Res<Localization>,
+    goods_hand_query: Query<(&Card, Option<&SelectedCard>), With<ActivePlayerGoodsCard>>,
+    mut button_query: Query<(&mut Style, &Children, &AssistButton)>,
+    mut text_query: Query<&mut Text>,
+) {
+    if !selected_card_state.is_changed() && !turn_state.is_changed() && !localization.is_changed() {
+        return;
+    }
+
+    let good_name_to_select_all = match (turn_state.current(), &deck_definition) {
+        (TurnState::Sell, Some(deck_definition)) => get_good_to_select_all(&goods_hand_query)
+            .map(|good_type| localization.get_good_name(&deck_definition.goods[good_type].name)),
+        _ => None,
+    };
+
+    for (mut style, children, assist_button) in button_query.iter_mut() {
+        if !matches!(assist_button, AssistButton::SelectAllMatchingGoods) {
+            continue;
+        }
+
+        match &good_name_to_select_all {
+            Some(good_name) => {
+                style.display = Display::Flex;
+                for &child in children.iter() {
+                    if let Ok(mut text) = text_query.get_mut(child) {
+                        text.sections[0].value =
+                            localization.format("game.select_all", &[("good", good_name)]);
+                    }
+                }
+            }
+            None => style.display = Display::None,
+        }
     }
 }
 
@@ -362,6 +522,28 @@ fn update_unclicked_turn_move_button_colors(
     }
 }
 
+// The move mode can also be changed by dropping a card, so the Take and Sell buttons follow it rather than only their own clicks
+fn update_turn_move_button_colors_on_turn_state_change(
+    turn_state: Res<State<TurnState>>,
+    mut buttons_query: Query<(&Interaction, &mut UiColor, &GameButton), Without<ConfirmGameButton>>,
+) {
+    if !turn_state.is_changed() {
+        return;
+    }
+
+    for (interaction, mut color, game_button) in &mut buttons_query {
+        if *interaction != Interaction::None {
+            continue;
+        }
+
+        *color = if *turn_state.current() == game_button.0.kind.into() {
+            game_button.0.pressed_color.into()
+        } else {
+            game_button.0.normal_color.into()
+        };
+    }
+}
+
 impl From<GameButtonKind> for TurnState {
     fn from(kind: GameButtonKind) -> Self {
         match kind {
@@ -372,6 +554,30 @@ impl From<GameButtonKind> for TurnState {
     }
 }
 
+// Shared by the Confirm button and the Enter key
+fn confirm_move(
+    commands: &mut Commands,
+    turn_state: &mut State<TurnState>,
+    ev_confirm_turn: &mut EventWriter<ConfirmTurnEvent>,
+    move_validity_state: &mut MoveValidity,
+    selected_card_state: &mut SelectedCardState,
+    ui_root_query: &Query<Entity, With<GameUiRoot>>,
+    move_type: MoveType,
+) {
+    if *turn_state.current() != TurnState::None {
+        turn_state.set(TurnState::None).unwrap();
+    }
+
+    selected_card_state.0.clear();
+
+    ev_confirm_turn.send(ConfirmTurnEvent(move_type));
+    *move_validity_state = MoveValidity::default();
+
+    for root_entity in ui_root_query.iter() {
+        commands.entity(root_entity).despawn_recursive();
+    }
+}
+
 fn handle_confirm_button_interaction(
     mut commands: Commands,
     mut turn_state: ResMut<State<TurnState>>,
@@ -385,12 +591,12 @@ fn handle_confirm_button_interaction(
     ui_root_query: Query<Entity, With<GameUiRoot>>,
 ) {
     for (interaction, mut color, game_button) in &mut interaction_query {
-        if *move_validity_state.as_ref() == MoveValidity::Invalid {
+        if matches!(move_validity_state.as_ref(), MoveValidity::Invalid(_)) {
             return;
         }
 
         let move_type = match move_validity_state.deref_mut() {
-            MoveValidity::Invalid => return,
+            MoveValidity::Invalid(_) => return,
             MoveValidity::Valid(m) => m,
         };
 
@@ -398,19 +604,16 @@ fn handle_confirm_button_interaction(
             Interaction::Clicked => {
                 *color = game_button.0.pressed_color.into();
 
-                let desired_turn_state: TurnState = game_button.0.kind.into();
-                if *turn_state.current() != desired_turn_state {
-                    turn_state.set(desired_turn_state).unwrap();
-                }
-
-                selected_card_state.0.clear();
-
-                ev_confirm_turn.send(ConfirmTurnEvent(*move_type));
-                *move_validity_state = MoveValidity::default();
-
-                for root_entity in ui_root_query.iter() {
-                    commands.entity(root_entity).despawn_recursive();
-                }
+                let move_type = *move_type;
+                confirm_move(
+                    &mut commands,
+                    &mut turn_state,
+                    &mut ev_confirm_turn,
+                    &mut move_validity_state,
+                    &mut selected_card_state,
+                    &ui_root_query,
+                    move_type,
+                );
             }
             Interaction::Hovered => {
                 *color = game_button.0.hovered_color.into();
@@ -422,57 +625,334 @@ fn handle_confirm_button_interaction(
     }
 }
 
+// T and S switch the move mode like the Take and Sell buttons, and Enter confirms a valid move
+fn handle_turn_keys(
+    mut commands: Commands,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut turn_state: ResMut<State<TurnState>>,
+    mut ev_confirm_turn: EventWriter<ConfirmTurnEvent>,
+    mut move_validity_state: ResMut<MoveValidity>,
+    mut selected_card_state: ResMut<SelectedCardState>,
+    ui_root_query: Query<Entity, With<GameUiRoot>>,
+) {
+    let toggled_turn_state = if keyboard_input.just_pressed(KeyCode::T) {
+        Some(TurnState::Take)
+    } else if keyboard_input.just_pressed(KeyCode::S) {
+        Some(TurnState::Sell)
+    } else {
+        None
+    };
+
+    if let Some(toggled_turn_state) = toggled_turn_state {
+        let desired_turn_state = if *turn_state.current() == toggled_turn_state {
+            TurnState::None
+        } else {
+            toggled_turn_state
+        };
+        turn_state.set(desired_turn_state).unwrap();
+        return;
+    }
+
+    if !keyboard_input.just_pressed(KeyCode::Return) {
+        return;
+    }
+
+    if let MoveValidity::Valid(move_type) = *move_validity_state {
+        confirm_move(
+            &mut commands,
+            &mut turn_state,
+            &mut ev_confirm_turn,
+            &mut move_validity_state,
+            &mut selected_card_state,
+            &ui_root_query,
+            move_type,
+        );
+    }
+}
+
 fn handle_move_validity_change(
     move_validity_state: Res<MoveValidity>,
+    localization: Res<Localization>,
     mut confirm_button_query: Query<(&mut UiColor, &GameButton), With<ConfirmGameButton>>,
+    mut move_validity_text_query: Query<&mut Text, With<MoveValidityText>>,
 ) {
-    if !move_validity_state.is_changed() {
+    if !move_validity_state.is_changed() && !localization.is_changed() {
         return;
     }
 
     let (mut confirm_button_color, game_button) = confirm_button_query.single_mut();
 
     match move_validity_state.as_ref() {
-        MoveValidity::Invalid => *confirm_button_color = Color::RED.into(),
+        MoveValidity::Invalid(_) => *confirm_button_color = Color::RED.into(),
         MoveValidity::Valid(_) => *confirm_button_color = game_button.0.normal_color.into(),
     }
+
+    for mut text in move_validity_text_query.iter_mut() {
+        text.sections[0].value = move_validity_state.localize(&localization);
+    }
+}
+
+fn get_sale_preview_text(sale_preview: &SalePreview, localization: &Localization) -> String {
+    let goods_tokens_text = sale_preview
+        .goods_tokens
+        .iter()
+        .map(|value| value.to_string())
+        .join(" + ");
+
+    let mut lines = vec![if sale_preview.goods_tokens.len() > 1 {
+        localization.format(
+            "sale_preview.earns_sum",
+            &[
+                ("tokens", &goods_tokens_text),
+                ("value", &sale_preview.get_goods_tokens_value()),
+            ],
+        )
+    } else {
+        localization.format(
+            "sale_preview.earns",
+            &[("value", &sale_preview.get_goods_tokens_value())],
+        )
+    }];
+
+    match (sale_preview.bonus_type, sale_preview.bonus_value_range) {
+        (Some(_), Some((min, max))) if min == max => {
+            lines.push(localization.format("sale_preview.bonus", &[("value", &min)]))
+        }
+        (Some(_), Some((min, max))) => lines
+            .push(localization.format("sale_preview.bonus_range", &[("min", &min), ("max", &max)])),
+        (Some(_), None) => lines.push(localization.get("sale_preview.no_bonus")),
+        (None, _) => {}
+    }
+
+    if sale_preview.is_game_ending {
+        lines.push(localization.get("sale_preview.ends_game"));
+    }
+
+    lines.join("\n")
+}
+
+// Runs after the move validity is updated for the current selection
+fn update_sale_preview(
+    move_validity_state: Res<MoveValidity>,
+    tokens: Option<Res<Tokens>>,
+    game_config: Res<GameConfig>,
+    localization: Res<Localization>,
+    selected_goods_query: Query<&Card, (With<ActivePlayerGoodsCard>, With<SelectedCard>)>,
+    mut sale_preview_text_query: Query<(&mut Text, &mut Style), With<SalePreviewText>>,
+) {
+    if !move_validity_state.is_changed() && !localization.is_changed() {
+        return;
+    }
+
+    let sale_preview = match (
+        move_validity_state.as_ref(),
+        tokens,
+        selected_goods_query.iter().next(),
+    ) {
+        (MoveValidity::Valid(MoveType::SellGoods), Some(tokens), Some(card)) => {
+            Some(tokens.preview_sale(
+                card.0.into_good_type(),
+                selected_goods_query.iter().count(),
+                game_config.num_empty_token_stacks_to_end,
+            ))
+        }
+        _ => None,
+    };
+
+    for (mut text, mut style) in sale_preview_text_query.iter_mut() {
+        match &sale_preview {
+            Some(sale_preview) => {
+                text.sections[0].value = get_sale_preview_text(sale_preview, &localization);
+                style.display = Display::Flex;
+            }
+            None => style.display = Display::None,
+        }
+    }
 }
 
 pub struct GameUiPlugin;
 
 impl Plugin for GameUiPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app.add_system_set(
-            SystemSet::on_enter(AppState::InGame)
-                .with_system(setup_game_ui)
-                .with_system(setup_tokens_ui::<ActivePlayer>),
-        )
-        .add_system_set(
-            SystemSet::on_update(AppState::InGame)
-                .with_system(handle_turn_state_button)
-                .with_system(
-                    update_unclicked_turn_move_button_colors.after(handle_turn_state_button),
-                )
-                .with_system(
-                    handle_confirm_button_interaction
-                        .label(Label::ConfirmTurnEventWriter)
-                        .before(Label::ConfirmTurnEventReader),
-                )
-                .with_system(handle_move_validity_change),
-        )
-        .add_system_set(
-            SystemSet::on_exit(TurnState::Sell)
-                .with_system(cleanup_tokens_ui.before(setup_tokens_ui::<ActivePlayer>))
-                .with_system(setup_tokens_ui::<ActivePlayer>),
-        )
-        .add_system_set(
-            SystemSet::on_exit(AppState::AiTurn)
-                .with_system(cleanup_tokens_ui.before(refresh_tokens_ui_after_ai_turn))
-                .with_system(refresh_tokens_ui_after_ai_turn),
-        )
-        .add_system_set(
-            SystemSet::on_enter(AppState::TurnTransition).with_system(cleanup_tokens_ui),
-        )
-        .add_system_set(SystemSet::on_enter(AppState::GameOver).with_system(cleanup_tokens_ui));
+        app.add_system_set(SystemSet::on_enter(AppState::InGame).with_system(setup_game_ui))
+            .add_system_set(
+                SystemSet::on_update(AppState::InGame)
+                    .with_system(handle_turn_state_button)
+                    .with_system(
+                        update_unclicked_turn_move_button_colors.after(handle_turn_state_button),
+                    )
+                    .with_system(update_turn_move_button_colors_on_turn_state_change)
+                    .with_system(
+                        handle_confirm_button_interaction
+                            .label(Label::ConfirmTurnEventWriter)
+                            .before(Label::ConfirmTurnEventReader),
+                    )
+                    .with_system(
+                        handle_turn_keys
+                            .label(Label::ConfirmTurnEventWriter)
+                            .before(Label::ConfirmTurnEventReader),
+                    )
+                    .with_system(handle_move_validity_change)
+                    .with_system(handle_assist_button_interaction)
+                    .with_system(handle_pause_button_interaction)
+                    .with_system(update_game_ui_layout),
+            )
+            // component removal occurs at the end of the stage (i.e. update stage), so this system needs to go in PostUpdate
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                update_select_all_matching_goods_button,
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                update_sale_preview.after(Label::MoveValidityWriter),
+            )
+            // Left behind if the game is restarted or abandoned from the pause menu
+            .add_system_set(
+                SystemSet::on_exit(AppState::InGame)
+                    .with_system(despawn_entity_with_component::<GameUiRoot>),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::move_validation::InvalidMoveReason;
+    use bevy::ecs::event::Events;
+
+    fn create_app(move_validity: MoveValidity) -> App {
+        let mut app = App::new();
+        app.init_resource::<Input<KeyCode>>()
+            .init_resource::<SelectedCardState>()
+            .insert_resource(move_validity)
+            .add_event::<ConfirmTurnEvent>()
+            .add_state(TurnState::None)
+            .add_system(handle_turn_keys);
+        app
+    }
+
+    // Runs a second update once the key is released, so that any change to the turn state is applied
+    fn press_key(app: &mut App, key: KeyCode) {
+        app.world.resource_mut::<Input<KeyCode>>().press(key);
+        app.update();
+
+        let mut keyboard_input = app.world.resource_mut::<Input<KeyCode>>();
+        keyboard_input.release(key);
+        keyboard_input.clear();
+        app.update();
+    }
+
+    fn get_confirmed_moves(app: &App) -> Vec<MoveType> {
+        let events = app.world.resource::<Events<ConfirmTurnEvent>>();
+        events.get_reader().iter(events).map(|ev| ev.0).collect()
+    }
+
+    #[test]
+    fn t_and_s_toggle_the_move_mode() {
+        let mut app = create_app(MoveValidity::default());
+
+        press_key(&mut app, KeyCode::T);
+        assert_eq!(
+            *app.world.resource::<State<TurnState>>().current(),
+            TurnState::Take
+        );
+
+        press_key(&mut app, KeyCode::S);
+        assert_eq!(
+            *app.world.resource::<State<TurnState>>().current(),
+            TurnState::Sell
+        );
+
+        press_key(&mut app, KeyCode::S);
+        assert_eq!(
+            *app.world.resource::<State<TurnState>>().current(),
+            TurnState::None
+        );
+    }
+
+    #[test]
+    fn enter_confirms_a_valid_move() {
+        let mut app = create_app(MoveValidity::Valid(MoveType::TakeSingleGood));
+        app.world
+            .resource_mut::<SelectedCardState>()
+            .0
+            .push(Entity::from_raw(0));
+
+        press_key(&mut app, KeyCode::Return);
+
+        assert_eq!(get_confirmed_moves(&app), vec![MoveType::TakeSingleGood]);
+        assert_eq!(
+            *app.world.resource::<MoveValidity>(),
+            MoveValidity::default()
+        );
+        assert!(app.world.resource::<SelectedCardState>().0.is_empty());
+    }
+
+    #[test]
+    fn enter_ignores_an_invalid_move() {
+        let mut app = create_app(MoveValidity::Invalid(InvalidMoveReason::NoMoveModeSelected));
+
+        press_key(&mut app, KeyCode::Return);
+
+        assert!(get_confirmed_moves(&app).is_empty());
+    }
+
+    fn create_pause_app() -> App {
+        let mut app = App::new();
+        app.init_resource::<Input<KeyCode>>()
+            .init_resource::<SelectedCardState>()
+            .add_state(AppState::InGame)
+            .add_system(handle_pause_button_interaction);
+        app
+    }
+
+    #[test]
+    fn escape_pauses_the_game_and_uses_up_the_key_press() {
+        let mut app = create_pause_app();
+
+        app.world
+            .resource_mut::<Input<KeyCode>>()
+            .press(KeyCode::Escape);
+        app.update();
+
+        // The settings menu would otherwise see the same press and close straight away
+        assert!(!app
+            .world
+            .resource::<Input<KeyCode>>()
+            .just_pressed(KeyCode::Escape));
+
+        app.update();
+        assert_eq!(
+            *app.world.resource::<State<AppState>>().current(),
+            AppState::Settings
+        );
+    }
+
+    #[test]
+    fn p_pauses_the_game() {
+        let mut app = create_pause_app();
+
+        press_key(&mut app, KeyCode::P);
+
+        assert_eq!(
+            *app.world.resource::<State<AppState>>().current(),
+            AppState::Settings
+        );
+    }
+
+    #[test]
+    fn escape_does_not_pause_while_cards_are_selected() {
+        let mut app = create_pause_app();
+        app.world
+            .resource_mut::<SelectedCardState>()
+            .0
+            .push(Entity::from_raw(0));
+
+        press_key(&mut app, KeyCode::Escape);
+
+        assert_eq!(
+            *app.world.resource::<State<AppState>>().current(),
+            AppState::InGame
+        );
     }
 }
@@ -1,16 +1,25 @@
 use bevy::prelude::*;
 
 use crate::{
-    common_systems::despawn_entity_with_component, resources::GameState, states::AppState,
+    common_systems::despawn_entity_with_component,
+    daily_challenge,
+    localization::{Localization, LocalizedText},
+    puzzle::PUZZLES,
+    resources::GameState,
+    states::AppState,
 };
 
-const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
-const HOVERED_BUTTON: Color = Color::rgb(0.25, 0.25, 0.25);
-const PRESSED_BUTTON: Color = Color::rgb(0.35, 0.75, 0.35);
+pub(super) const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
+pub(super) const HOVERED_BUTTON: Color = Color::rgb(0.25, 0.25, 0.25);
+pub(super) const PRESSED_BUTTON: Color = Color::rgb(0.35, 0.75, 0.35);
 
 #[derive(Component)]
 struct MenuRootNode;
 
+// The button chosen with the Up and Down keys, which Enter presses. Shared with the settings menu.
+#[derive(Default)]
+pub(super) struct MenuFocus(pub(super) Option<usize>);
+
 trait ClickHandler {
     fn on_click(self, state: &mut ResMut<State<AppState>>, game_state: &mut ResMut<GameState>);
 }
@@ -22,6 +31,11 @@ impl ClickHandler for PlayLocalMultiplayerButton {
     fn on_click(self, state: &mut ResMut<State<AppState>>, game_state: &mut ResMut<GameState>) {
         state.set(AppState::InitGame).unwrap();
         game_state.is_playing_ai = false;
+        game_state.is_playing_puzzle = false;
+        game_state.is_playing_daily_challenge = false;
+        game_state.is_playing_tutorial = false;
+        game_state.is_second_player_starting = false;
+        game_state.setup_error = None;
     }
 }
 
@@ -32,10 +46,160 @@ impl ClickHandler for PlayAIButton {
     fn on_click(self, state: &mut ResMut<State<AppState>>, game_state: &mut ResMut<GameState>) {
         state.set(AppState::InitGame).unwrap();
         game_state.is_playing_ai = true;
+        game_state.is_playing_puzzle = false;
+        game_state.is_playing_daily_challenge = false;
+        game_state.is_playing_tutorial = false;
+        game_state.is_second_player_starting = false;
+        game_state.setup_error = None;
+    }
+}
+
+#[derive(Component, Copy, Clone)]
+struct PlayPuzzleButton;
+
+impl ClickHandler for PlayPuzzleButton {
+    fn on_click(self, state: &mut ResMut<State<AppState>>, game_state: &mut ResMut<GameState>) {
+        state.set(AppState::InitGame).unwrap();
+        game_state.is_playing_ai = true;
+        game_state.is_playing_puzzle = true;
+        game_state.is_playing_daily_challenge = false;
+        game_state.is_playing_tutorial = false;
+        game_state.is_second_player_starting = false;
+        game_state.setup_error = None;
+    }
+}
+
+#[derive(Component, Copy, Clone)]
+struct PlayDailyChallengeButton;
+
+impl ClickHandler for PlayDailyChallengeButton {
+    fn on_click(self, state: &mut ResMut<State<AppState>>, game_state: &mut ResMut<GameState>) {
+        state.set(AppState::InitGame).unwrap();
+        game_state.is_playing_ai = true;
+        game_state.is_playing_puzzle = false;
+        game_state.is_playing_daily_challenge = true;
+        game_state.is_playing_tutorial = false;
+        game_state.is_second_player_starting = false;
+        game_state.setup_error = None;
+    }
+}
+
+// Shows the best score in today's challenge so far
+impl ButtonText for PlayDailyChallengeButton {
+    fn get_text(_game_state: &GameState, localization: &Localization) -> String {
+        daily_challenge::get_menu_text(localization)
+    }
+}
+
+#[derive(Component, Copy, Clone)]
+struct PlayTutorialButton;
+
+impl ClickHandler for PlayTutorialButton {
+    fn on_click(self, state: &mut ResMut<State<AppState>>, game_state: &mut ResMut<GameState>) {
+        state.set(AppState::InitGame).unwrap();
+        game_state.is_playing_ai = true;
+        game_state.is_playing_puzzle = false;
+        game_state.is_playing_daily_challenge = false;
+        game_state.is_playing_tutorial = true;
+        game_state.is_second_player_starting = false;
+        game_state.setup_error = None;
+    }
+}
+
+#[derive(Component, Copy, Clone)]
+struct PuzzleButton;
+
+impl ClickHandler for PuzzleButton {
+    fn on_click(self, _state: &mut ResMut<State<AppState>>, game_state: &mut ResMut<GameState>) {
+        game_state.selected_puzzle = (game_state.selected_puzzle + 1) % PUZZLES.len();
+    }
+}
+
+impl ButtonText for PuzzleButton {
+    fn get_text(game_state: &GameState, localization: &Localization) -> String {
+        localization.format(
+            "menu.puzzle",
+            &[
+                ("number", &(game_state.selected_puzzle + 1)),
+                ("count", &PUZZLES.len()),
+            ],
+        )
+    }
+}
+
+#[derive(Component, Copy, Clone)]
+struct AiPersonalityButton;
+
+impl ClickHandler for AiPersonalityButton {
+    fn on_click(self, _state: &mut ResMut<State<AppState>>, game_state: &mut ResMut<GameState>) {
+        game_state.ai_personality = game_state.ai_personality.next();
+    }
+}
+
+impl ButtonText for AiPersonalityButton {
+    fn get_text(game_state: &GameState, localization: &Localization) -> String {
+        localization.format(
+            "menu.computer",
+            &[(
+                "personality",
+                &game_state.ai_personality.localize(localization),
+            )],
+        )
+    }
+}
+
+#[derive(Component, Copy, Clone)]
+struct RulesPresetButton;
+
+impl ClickHandler for RulesPresetButton {
+    fn on_click(self, _state: &mut ResMut<State<AppState>>, game_state: &mut ResMut<GameState>) {
+        game_state.rules_preset = game_state.rules_preset.next();
     }
 }
 
-fn create_button<C: ClickHandler + Component>(
+impl ButtonText for RulesPresetButton {
+    fn get_text(game_state: &GameState, localization: &Localization) -> String {
+        localization.format(
+            "menu.rules",
+            &[("rules", &game_state.rules_preset.localize(localization))],
+        )
+    }
+}
+
+#[derive(Component, Copy, Clone)]
+struct SettingsButton;
+
+impl ClickHandler for SettingsButton {
+    fn on_click(self, state: &mut ResMut<State<AppState>>, _game_state: &mut ResMut<GameState>) {
+        state.push(AppState::Settings).unwrap();
+    }
+}
+
+// For buttons whose text shows a game setting, or otherwise changes
+trait ButtonText {
+    fn get_text(game_state: &GameState, localization: &Localization) -> String;
+}
+
+fn update_button_text<T: ButtonText + Component>(
+    game_state: Res<GameState>,
+    localization: Res<Localization>,
+    button_query: Query<&Children, With<T>>,
+    mut text_query: Query<&mut Text>,
+) {
+    if !game_state.is_changed() && !localization.is_changed() {
+        return;
+    }
+
+    for children in button_query.iter() {
+        for &child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(child) {
+                text.sections[0].value = T::get_text(&game_state, &localization);
+            }
+        }
+    }
+}
+
+pub(super) fn create_button<C: Component>(
     commands: &mut Commands,
     asset_server: &Res<AssetServer>,
     click_handler_component: C,
@@ -67,7 +231,14 @@ fn create_button<C: ClickHandler + Component>(
         .id()
 }
 
-fn setup_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn setup_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    game_state: Res<GameState>,
+    localization: Res<Localization>,
+) {
+    commands.insert_resource(MenuFocus::default());
+
     let root_node_entity = commands
         .spawn_bundle(NodeBundle {
             style: Style {
@@ -83,23 +254,118 @@ fn setup_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
         .insert(MenuRootNode)
         .id();
 
+    let play_tutorial_button_entity = create_button(
+        &mut commands,
+        &asset_server,
+        PlayTutorialButton,
+        localization.get("menu.tutorial"),
+    );
+    commands
+        .entity(play_tutorial_button_entity)
+        .insert(LocalizedText("menu.tutorial"));
+
     let play_human_button_entity = create_button(
         &mut commands,
         &asset_server,
         PlayLocalMultiplayerButton,
-        "Play Local Multiplayer".to_string(),
+        localization.get("menu.play_local_multiplayer"),
     );
+    commands
+        .entity(play_human_button_entity)
+        .insert(LocalizedText("menu.play_local_multiplayer"));
 
     let play_ai_button_entity = create_button(
         &mut commands,
         &asset_server,
         PlayAIButton,
-        "Play Computer".to_string(),
+        localization.get("menu.play_computer"),
     );
+    commands
+        .entity(play_ai_button_entity)
+        .insert(LocalizedText("menu.play_computer"));
 
+    let ai_personality_button_entity = create_button(
+        &mut commands,
+        &asset_server,
+        AiPersonalityButton,
+        AiPersonalityButton::get_text(&game_state, &localization),
+    );
+
+    let rules_preset_button_entity = create_button(
+        &mut commands,
+        &asset_server,
+        RulesPresetButton,
+        RulesPresetButton::get_text(&game_state, &localization),
+    );
+
+    let play_puzzle_button_entity = create_button(
+        &mut commands,
+        &asset_server,
+        PlayPuzzleButton,
+        localization.get("menu.play_puzzle"),
+    );
     commands
-        .entity(root_node_entity)
-        .push_children(&[play_human_button_entity, play_ai_button_entity]);
+        .entity(play_puzzle_button_entity)
+        .insert(LocalizedText("menu.play_puzzle"));
+
+    let puzzle_button_entity = create_button(
+        &mut commands,
+        &asset_server,
+        PuzzleButton,
+        PuzzleButton::get_text(&game_state, &localization),
+    );
+
+    let play_daily_challenge_button_entity = create_button(
+        &mut commands,
+        &asset_server,
+        PlayDailyChallengeButton,
+        PlayDailyChallengeButton::get_text(&game_state, &localization),
+    );
+
+    let settings_button_entity = create_button(
+        &mut commands,
+        &asset_server,
+        SettingsButton,
+        localization.get("menu.settings"),
+    );
+    commands
+        .entity(settings_button_entity)
+        .insert(LocalizedText("menu.settings"));
+
+    commands.entity(root_node_entity).push_children(&[
+        play_tutorial_button_entity,
+        play_human_button_entity,
+        play_ai_button_entity,
+        ai_personality_button_entity,
+        rules_preset_button_entity,
+        play_puzzle_button_entity,
+        puzzle_button_entity,
+        play_daily_challenge_button_entity,
+        settings_button_entity,
+    ]);
+
+    if let Some(setup_error) = &game_state.setup_error {
+        let error_text_entity = commands
+            .spawn_bundle(
+                TextBundle::from_section(
+                    setup_error,
+                    TextStyle {
+                        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                        font_size: 24.0,
+                        color: Color::MAROON,
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::all(Val::Px(10.0)),
+                    ..default()
+                }),
+            )
+            .id();
+
+        commands
+            .entity(root_node_entity)
+            .add_child(error_text_entity);
+    }
 }
 
 fn handle_menu_interaction<T: ClickHandler + Component + Copy>(
@@ -126,15 +392,111 @@ fn handle_menu_interaction<T: ClickHandler + Component + Copy>(
     }
 }
 
+// The buttons under the menu's root node R, from top to bottom
+pub(super) fn get_menu_buttons<R: Component>(
+    root_query: &Query<&Children, With<R>>,
+    button_query: &Query<&mut UiColor, With<Button>>,
+) -> Vec<Entity> {
+    root_query
+        .iter()
+        .flat_map(|children| children.iter())
+        .filter(|&&child| button_query.contains(child))
+        .copied()
+        .collect()
+}
+
+pub(super) fn handle_menu_focus_keys<R: Component>(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut menu_focus: ResMut<MenuFocus>,
+    root_query: Query<&Children, With<R>>,
+    mut button_query: Query<&mut UiColor, With<Button>>,
+) {
+    let buttons = get_menu_buttons(&root_query, &button_query);
+    if buttons.is_empty() {
+        return;
+    }
+
+    let last_index = buttons.len() - 1;
+    let focused_index = if keyboard_input.just_pressed(KeyCode::Down) {
+        menu_focus.0.map_or(0, |index| (index + 1).min(last_index))
+    } else if keyboard_input.just_pressed(KeyCode::Up) {
+        menu_focus.0.map_or(0, |index| index.saturating_sub(1))
+    } else {
+        return;
+    };
+    menu_focus.0 = Some(focused_index);
+
+    for (index, &button) in buttons.iter().enumerate() {
+        if let Ok(mut color) = button_query.get_mut(button) {
+            *color = if index == focused_index {
+                HOVERED_BUTTON.into()
+            } else {
+                NORMAL_BUTTON.into()
+            };
+        }
+    }
+}
+
+fn handle_menu_key<T: ClickHandler + Component + Copy>(
+    keyboard_input: Res<Input<KeyCode>>,
+    menu_focus: Res<MenuFocus>,
+    mut state: ResMut<State<AppState>>,
+    mut game_state: ResMut<GameState>,
+    root_query: Query<&Children, With<MenuRootNode>>,
+    mut button_query: Query<&mut UiColor, With<Button>>,
+    click_handler_query: Query<&T>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::Return) {
+        return;
+    }
+
+    let focused_button = menu_focus.0.and_then(|index| {
+        get_menu_buttons(&root_query, &button_query)
+            .get(index)
+            .copied()
+    });
+
+    if let Some(button) = focused_button {
+        if let Ok(click_handler) = click_handler_query.get(button) {
+            if let Ok(mut color) = button_query.get_mut(button) {
+                *color = PRESSED_BUTTON.into();
+            }
+            click_handler.on_click(&mut state, &mut game_state);
+        }
+    }
+}
+
 pub struct MainMenuPlugin;
 
 impl Plugin for MainMenuPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system_set(SystemSet::on_enter(AppState::MainMenu).with_system(setup_menu))
+        app.init_resource::<MenuFocus>()
+            .add_system_set(SystemSet::on_enter(AppState::MainMenu).with_system(setup_menu))
             .add_system_set(
                 SystemSet::on_update(AppState::MainMenu)
                     .with_system(handle_menu_interaction::<PlayLocalMultiplayerButton>)
-                    .with_system(handle_menu_interaction::<PlayAIButton>),
+                    .with_system(handle_menu_interaction::<PlayAIButton>)
+                    .with_system(handle_menu_interaction::<AiPersonalityButton>)
+                    .with_system(handle_menu_interaction::<RulesPresetButton>)
+                    .with_system(handle_menu_interaction::<SettingsButton>)
+                    .with_system(handle_menu_interaction::<PlayPuzzleButton>)
+                    .with_system(handle_menu_interaction::<PuzzleButton>)
+                    .with_system(handle_menu_interaction::<PlayDailyChallengeButton>)
+                    .with_system(handle_menu_interaction::<PlayTutorialButton>)
+                    .with_system(handle_menu_focus_keys::<MenuRootNode>)
+                    .with_system(handle_menu_key::<PlayLocalMultiplayerButton>)
+                    .with_system(handle_menu_key::<PlayAIButton>)
+                    .with_system(handle_menu_key::<AiPersonalityButton>)
+                    .with_system(handle_menu_key::<RulesPresetButton>)
+                    .with_system(handle_menu_key::<SettingsButton>)
+                    .with_system(handle_menu_key::<PlayPuzzleButton>)
+                    .with_system(handle_menu_key::<PuzzleButton>)
+                    .with_system(handle_menu_key::<PlayDailyChallengeButton>)
+                    .with_system(handle_menu_key::<PlayTutorialButton>)
+                    .with_system(update_button_text::<AiPersonalityButton>)
+                    .with_system(update_button_text::<RulesPresetButton>)
+                    .with_system(update_button_text::<PuzzleButton>)
+                    .with_system(update_button_text::<PlayDailyChallengeButton>),
             )
             .add_system_set(
                 SystemSet::on_exit(AppState::MainMenu)
@@ -142,3 +504,108 @@ impl Plugin for MainMenuPlugin {
             );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spawn_button<T: Component>(world: &mut World, click_handler: T) -> Entity {
+        world
+            .spawn()
+            .insert(Button)
+            .insert(UiColor(NORMAL_BUTTON))
+            .insert(click_handler)
+            .id()
+    }
+
+    // A menu of a Play Local Multiplayer, a Play AI and a puzzle button, from top to bottom
+    fn create_app() -> (App, Vec<Entity>) {
+        let mut app = App::new();
+        app.init_resource::<Input<KeyCode>>()
+            .init_resource::<MenuFocus>()
+            .init_resource::<GameState>()
+            .add_state(AppState::MainMenu)
+            .add_system(handle_menu_focus_keys::<MenuRootNode>)
+            .add_system(handle_menu_key::<PlayLocalMultiplayerButton>)
+            .add_system(handle_menu_key::<PlayAIButton>)
+            .add_system(handle_menu_key::<PuzzleButton>);
+
+        let buttons = vec![
+            spawn_button(&mut app.world, PlayLocalMultiplayerButton),
+            spawn_button(&mut app.world, PlayAIButton),
+            spawn_button(&mut app.world, PuzzleButton),
+        ];
+
+        app.world
+            .spawn()
+            .insert(MenuRootNode)
+            .push_children(&buttons);
+
+        (app, buttons)
+    }
+
+    // Runs a second update once the key is released, so that any change to the app state is applied
+    fn press_key(app: &mut App, key: KeyCode) {
+        app.world.resource_mut::<Input<KeyCode>>().press(key);
+        app.update();
+
+        let mut keyboard_input = app.world.resource_mut::<Input<KeyCode>>();
+        keyboard_input.release(key);
+        keyboard_input.clear();
+        app.update();
+    }
+
+    fn get_button_color(app: &App, button: Entity) -> Color {
+        app.world.get::<UiColor>(button).unwrap().0
+    }
+
+    #[test]
+    fn up_and_down_move_the_focus_between_buttons() {
+        let (mut app, buttons) = create_app();
+
+        press_key(&mut app, KeyCode::Down);
+        assert_eq!(app.world.resource::<MenuFocus>().0, Some(0));
+        assert_eq!(get_button_color(&app, buttons[0]), HOVERED_BUTTON);
+
+        // The focus stops at the last button
+        for _ in 0..3 {
+            press_key(&mut app, KeyCode::Down);
+        }
+        assert_eq!(app.world.resource::<MenuFocus>().0, Some(2));
+
+        press_key(&mut app, KeyCode::Up);
+        assert_eq!(app.world.resource::<MenuFocus>().0, Some(1));
+        assert_eq!(get_button_color(&app, buttons[0]), NORMAL_BUTTON);
+        assert_eq!(get_button_color(&app, buttons[1]), HOVERED_BUTTON);
+        assert_eq!(get_button_color(&app, buttons[2]), NORMAL_BUTTON);
+    }
+
+    #[test]
+    fn enter_presses_the_focused_button() {
+        let (mut app, buttons) = create_app();
+
+        press_key(&mut app, KeyCode::Down);
+        press_key(&mut app, KeyCode::Down);
+        press_key(&mut app, KeyCode::Return);
+
+        assert!(app.world.resource::<GameState>().is_playing_ai);
+        assert_eq!(
+            *app.world.resource::<State<AppState>>().current(),
+            AppState::InitGame
+        );
+        assert_eq!(get_button_color(&app, buttons[1]), PRESSED_BUTTON);
+    }
+
+    #[test]
+    fn enter_without_a_focused_button_does_nothing() {
+        let (mut app, _) = create_app();
+
+        press_key(&mut app, KeyCode::Return);
+
+        assert!(!app.world.resource::<GameState>().is_playing_ai);
+        assert_eq!(
+            *app.world.resource::<State<AppState>>().current(),
+            AppState::MainMenu
+        );
+    }
+}
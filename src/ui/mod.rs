@@ -1,2 +1,3 @@
 pub mod game_ui;
 pub mod main_menu;
+pub mod settings_menu;
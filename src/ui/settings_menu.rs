@@ -0,0 +1,445 @@
+use bevy::prelude::*;
+
+use crate::{
+    common_systems::despawn_entity_with_component,
+    event::ForfeitEvent,
+    localization::{Localization, LocalizedText},
+    resources::GameState,
+    settings::{Settings, MAX_PLAYER_NAME_LENGTH, NUM_PLAYER_NAMES},
+    states::AppState,
+};
+
+use super::main_menu::{
+    create_button, get_menu_buttons, handle_menu_focus_keys, MenuFocus, HOVERED_BUTTON,
+    NORMAL_BUTTON, PRESSED_BUTTON,
+};
+
+#[derive(Component)]
+struct SettingsRootNode;
+
+#[derive(Component, Copy, Clone, Eq, PartialEq)]
+enum SettingsButton {
+    PlayerName(usize),
+    AiDifficulty,
+    AnimationSpeed,
+    HandSortOrder,
+    ShowOpponentCamelCount,
+    Language,
+    // Only offered when the game is paused
+    Restart,
+    Forfeit,
+    MainMenu,
+    Back,
+}
+
+impl SettingsButton {
+    // The text of Back and the buttons below it doesn't change
+    fn get_text(
+        &self,
+        settings: &Settings,
+        edited_player_name: &EditedPlayerName,
+        localization: &Localization,
+    ) -> Option<String> {
+        match self {
+            SettingsButton::PlayerName(player_idx) => {
+                // The default name is only shown while the name isn't being typed in
+                let name = if edited_player_name.0 == Some(*player_idx) {
+                    format!("{}_", settings.player_names[*player_idx])
+                } else {
+                    settings.get_player_name(*player_idx, localization)
+                };
+
+                Some(localization.format(
+                    "settings.player_name",
+                    &[("number", &(player_idx + 1)), ("name", &name)],
+                ))
+            }
+            SettingsButton::AiDifficulty => Some(localization.format(
+                "settings.ai_difficulty",
+                &[("difficulty", &settings.ai_difficulty.localize(localization))],
+            )),
+            SettingsButton::AnimationSpeed => Some(localization.format(
+                "settings.animation_speed",
+                &[("speed", &settings.animation_speed.localize(localization))],
+            )),
+            SettingsButton::HandSortOrder => Some(localization.format(
+                "settings.hand_sort_order",
+                &[("order", &settings.hand_sort_order.localize(localization))],
+            )),
+            SettingsButton::ShowOpponentCamelCount => Some(localization.format(
+                "settings.opponent_camel_count",
+                &[(
+                    "visibility",
+                    &localization.get(if settings.show_opponent_camel_count {
+                        "settings.shown"
+                    } else {
+                        "settings.hidden"
+                    }),
+                )],
+            )),
+            SettingsButton::Language => Some(localization.format(
+                "settings.language",
+                &[(
+                    "language",
+                    &localization.get_language_name(&settings.language),
+                )],
+            )),
+            SettingsButton::Restart
+            | SettingsButton::Forfeit
+            | SettingsButton::MainMenu
+            | SettingsButton::Back => None,
+        }
+    }
+}
+
+// The player name being typed in, if any
+#[derive(Default)]
+struct EditedPlayerName(Option<usize>);
+
+// A name left empty goes back to the default name
+fn stop_editing_player_name(settings: &mut Settings, edited_player_name: &mut EditedPlayerName) {
+    if let Some(player_idx) = edited_player_name.0.take() {
+        let name = &mut settings.player_names[player_idx];
+        *name = name.trim().to_string();
+    }
+}
+
+fn press_settings_button(
+    settings_button: SettingsButton,
+    state: &mut State<AppState>,
+    settings: &mut Settings,
+    edited_player_name: &mut EditedPlayerName,
+    localization: &Localization,
+    ev_forfeit: &mut EventWriter<ForfeitEvent>,
+) {
+    let previously_edited_player_name = edited_player_name.0;
+    stop_editing_player_name(settings, edited_player_name);
+
+    match settings_button {
+        // Pressing the name being edited again finishes editing it
+        SettingsButton::PlayerName(player_idx) => {
+            if previously_edited_player_name != Some(player_idx) {
+                edited_player_name.0 = Some(player_idx);
+            }
+        }
+        SettingsButton::AiDifficulty => {
+            settings.ai_difficulty = settings.ai_difficulty.next();
+        }
+        SettingsButton::AnimationSpeed => {
+            settings.animation_speed = settings.animation_speed.next();
+        }
+        SettingsButton::HandSortOrder => {
+            settings.hand_sort_order = settings.hand_sort_order.next();
+        }
+        SettingsButton::ShowOpponentCamelCount => {
+            settings.show_opponent_camel_count = !settings.show_opponent_camel_count;
+        }
+        // Only the languages that have loaded are offered
+        SettingsButton::Language => {
+            settings.language = localization.get_next_language(&settings.language);
+        }
+        // Replacing the whole state stack leaves the game underneath the pause menu too, which tears it down
+        SettingsButton::Restart => {
+            state.replace(AppState::InitGame).unwrap();
+        }
+        // The game over screen is shown once the forfeit has been handled - see game.rs
+        SettingsButton::Forfeit => {
+            ev_forfeit.send(ForfeitEvent);
+        }
+        SettingsButton::MainMenu => {
+            state.replace(AppState::MainMenu).unwrap();
+        }
+        SettingsButton::Back => {
+            state.pop().unwrap();
+        }
+    }
+}
+
+// The settings menu is reached from the main menu, or by pausing the game
+fn setup_settings_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    state: Res<State<AppState>>,
+    settings: Res<Settings>,
+    game_state: Res<GameState>,
+    localization: Res<Localization>,
+) {
+    commands.insert_resource(MenuFocus::default());
+    commands.insert_resource(EditedPlayerName::default());
+
+    let is_paused = !matches!(state.inactives().last(), Some(AppState::MainMenu));
+    let edited_player_name = EditedPlayerName::default();
+    let (title_key, back_key) = if is_paused {
+        ("settings.paused", "settings.resume")
+    } else {
+        ("settings.title", "settings.back")
+    };
+
+    let root_node_entity = commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                flex_direction: FlexDirection::ColumnReverse,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            color: Color::GRAY.into(),
+            ..default()
+        })
+        .insert(SettingsRootNode)
+        .id();
+
+    let title_text_entity = commands
+        .spawn_bundle(
+            TextBundle::from_section(
+                localization.get(title_key),
+                TextStyle {
+                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                    font_size: 50.0,
+                    color: Color::rgb(0.9, 0.9, 0.9),
+                },
+            )
+            .with_style(Style {
+                margin: UiRect::all(Val::Px(10.0)),
+                ..default()
+            }),
+        )
+        .insert(LocalizedText(title_key))
+        .id();
+
+    commands
+        .entity(root_node_entity)
+        .add_child(title_text_entity);
+
+    let settings_buttons = (0..NUM_PLAYER_NAMES)
+        .map(SettingsButton::PlayerName)
+        .chain([
+            SettingsButton::AiDifficulty,
+            SettingsButton::AnimationSpeed,
+            SettingsButton::HandSortOrder,
+            SettingsButton::ShowOpponentCamelCount,
+            SettingsButton::Language,
+        ]);
+
+    for settings_button in settings_buttons {
+        let text = settings_button
+            .get_text(&settings, &edited_player_name, &localization)
+            .unwrap_or_default();
+        let button_entity = create_button(&mut commands, &asset_server, settings_button, text);
+
+        commands.entity(root_node_entity).add_child(button_entity);
+    }
+
+    if is_paused {
+        let game_buttons = [
+            (SettingsButton::Restart, "settings.restart"),
+            (SettingsButton::Forfeit, "settings.forfeit"),
+            (SettingsButton::MainMenu, "settings.main_menu"),
+        ]
+        .into_iter()
+        // There's no one to forfeit to in the tutorial
+        .filter(|(settings_button, _)| {
+            *settings_button != SettingsButton::Forfeit || !game_state.is_playing_tutorial
+        });
+
+        for (settings_button, text_key) in game_buttons {
+            let button_entity = create_button(
+                &mut commands,
+                &asset_server,
+                settings_button,
+                localization.get(text_key),
+            );
+
+            commands
+                .entity(button_entity)
+                .insert(LocalizedText(text_key));
+            commands.entity(root_node_entity).add_child(button_entity);
+        }
+    }
+
+    let back_button_entity = create_button(
+        &mut commands,
+        &asset_server,
+        SettingsButton::Back,
+        localization.get(back_key),
+    );
+
+    commands
+        .entity(back_button_entity)
+        .insert(LocalizedText(back_key));
+    commands
+        .entity(root_node_entity)
+        .add_child(back_button_entity);
+}
+
+fn handle_settings_interaction(
+    mut state: ResMut<State<AppState>>,
+    mut settings: ResMut<Settings>,
+    mut edited_player_name: ResMut<EditedPlayerName>,
+    localization: Res<Localization>,
+    mut ev_forfeit: EventWriter<ForfeitEvent>,
+    mut interaction_query: Query<
+        (&Interaction, &mut UiColor, &SettingsButton),
+        (Changed<Interaction>, With<Button>),
+    >,
+) {
+    for (interaction, mut color, settings_button) in &mut interaction_query {
+        match *interaction {
+            Interaction::Clicked => {
+                *color = PRESSED_BUTTON.into();
+                press_settings_button(
+                    *settings_button,
+                    &mut state,
+                    &mut settings,
+                    &mut edited_player_name,
+                    &localization,
+                    &mut ev_forfeit,
+                );
+            }
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
+            }
+        }
+    }
+}
+
+// While a player name is being edited, typing changes it and Enter or Escape finishes it. Otherwise Enter presses
+// the focused button, and Escape goes back.
+#[allow(clippy::too_many_arguments)]
+fn handle_settings_keys(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut ev_received_character: EventReader<ReceivedCharacter>,
+    menu_focus: Res<MenuFocus>,
+    mut state: ResMut<State<AppState>>,
+    mut settings: ResMut<Settings>,
+    mut edited_player_name: ResMut<EditedPlayerName>,
+    localization: Res<Localization>,
+    mut ev_forfeit: EventWriter<ForfeitEvent>,
+    root_query: Query<&Children, With<SettingsRootNode>>,
+    mut button_query: Query<&mut UiColor, With<Button>>,
+    settings_button_query: Query<&SettingsButton>,
+) {
+    if let Some(player_idx) = edited_player_name.0 {
+        if keyboard_input.any_just_pressed([KeyCode::Return, KeyCode::Escape]) {
+            stop_editing_player_name(&mut settings, &mut edited_player_name);
+            return;
+        }
+
+        let name = &mut settings.player_names[player_idx];
+
+        if keyboard_input.just_pressed(KeyCode::Back) {
+            name.pop();
+        }
+
+        for ev in ev_received_character.iter() {
+            if !ev.char.is_control() && name.chars().count() < MAX_PLAYER_NAME_LENGTH {
+                name.push(ev.char);
+            }
+        }
+
+        return;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        // The key press is used up, so that the menu or game underneath doesn't handle it too
+        keyboard_input.reset(KeyCode::Escape);
+        state.pop().unwrap();
+        return;
+    }
+
+    if !keyboard_input.just_pressed(KeyCode::Return) {
+        return;
+    }
+    keyboard_input.reset(KeyCode::Return);
+
+    let focused_button = menu_focus.0.and_then(|index| {
+        get_menu_buttons(&root_query, &button_query)
+            .get(index)
+            .copied()
+    });
+
+    if let Some(button) = focused_button {
+        if let Ok(settings_button) = settings_button_query.get(button) {
+            if let Ok(mut color) = button_query.get_mut(button) {
+                *color = PRESSED_BUTTON.into();
+            }
+            press_settings_button(
+                *settings_button,
+                &mut state,
+                &mut settings,
+                &mut edited_player_name,
+                &localization,
+                &mut ev_forfeit,
+            );
+        }
+    }
+}
+
+fn update_settings_button_text(
+    settings: Res<Settings>,
+    edited_player_name: Res<EditedPlayerName>,
+    localization: Res<Localization>,
+    button_query: Query<(&Children, &SettingsButton)>,
+    mut text_query: Query<&mut Text>,
+) {
+    if !settings.is_changed() && !edited_player_name.is_changed() && !localization.is_changed() {
+        return;
+    }
+
+    for (children, settings_button) in button_query.iter() {
+        if let Some(value) = settings_button.get_text(&settings, &edited_player_name, &localization)
+        {
+            for &child in children.iter() {
+                if let Ok(mut text) = text_query.get_mut(child) {
+                    text.sections[0].value = value.clone();
+                }
+            }
+        }
+    }
+}
+
+fn save_settings(
+    mut commands: Commands,
+    mut settings: ResMut<Settings>,
+    mut edited_player_name: ResMut<EditedPlayerName>,
+) {
+    // A name left being edited is kept as it was typed
+    stop_editing_player_name(&mut settings, &mut edited_player_name);
+
+    settings.save();
+
+    // The focus belongs to the menu underneath again
+    commands.insert_resource(MenuFocus::default());
+}
+
+pub struct SettingsMenuPlugin;
+
+impl Plugin for SettingsMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EditedPlayerName>()
+            .add_system_set(
+                SystemSet::on_enter(AppState::Settings).with_system(setup_settings_menu),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Settings)
+                    .with_system(handle_settings_interaction)
+                    .with_system(handle_menu_focus_keys::<SettingsRootNode>)
+                    .with_system(handle_settings_keys)
+                    .with_system(
+                        update_settings_button_text
+                            .after(handle_settings_interaction)
+                            .after(handle_settings_keys),
+                    ),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::Settings)
+                    .with_system(despawn_entity_with_component::<SettingsRootNode>)
+                    .with_system(save_settings),
+            );
+    }
+}